@@ -0,0 +1,7 @@
+//! Headless command line interface to MCP Hub, see [`mcp_hub_lib::cli`].
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    mcp_hub_lib::cli::main()
+}
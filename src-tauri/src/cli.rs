@@ -0,0 +1,854 @@
+//! Headless command line interface to MCP Hub.
+//!
+//! Works on the same database and services as the desktop app, so servers,
+//! instances and syncs can be managed from scripts, dotfile setups and CI.
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::models::{
+    ArgsMode, ClientInstance, ClientType, CredentialBackend, DoctorIssue, DoctorReport,
+    ExtractedSecret, ImportResult, IssueSeverity, McpServer, OrphanRepair, ServerOverride,
+    SyncPlan, ToolFilter, TransportType,
+};
+use crate::services::{config, credentials, doctor, inheritance, proxy, sync};
+
+/// Env var holding the passphrase of the secrets vault, when the settings
+/// keep secrets there
+const VAULT_PASSPHRASE_VAR: &str = "MCP_HUB_VAULT_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(
+    name = "mcp-hub-cli",
+    version,
+    about = "Manage MCP servers from the command line"
+)]
+struct Cli {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Use this database instead of the app's default one
+    #[arg(long, global = true, value_name = "PATH")]
+    database: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage servers in the registry
+    #[command(subcommand)]
+    Servers(ServersCommand),
+    /// Manage client instances
+    #[command(subcommand)]
+    Instances(InstancesCommand),
+    /// Enable a server for an instance
+    Enable {
+        /// Server ID or name
+        server: String,
+        /// Instance ID or name
+        #[arg(long, short)]
+        instance: String,
+    },
+    /// Disable a server for an instance
+    Disable {
+        /// Server ID or name
+        server: String,
+        /// Instance ID or name
+        #[arg(long, short)]
+        instance: String,
+    },
+    /// Limit which tools of a server an instance may use. Patterns may use
+    /// `*` and `?`; giving no patterns removes the filter.
+    Tools {
+        /// Server ID or name
+        server: String,
+        /// Instance ID or name
+        #[arg(long, short)]
+        instance: String,
+        /// Only allow tools matching this pattern (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        allow: Vec<String>,
+        /// Deny tools matching this pattern (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        deny: Vec<String>,
+    },
+    /// Change how an instance runs a server it shares with other
+    /// instances. Giving nothing removes the override.
+    Override {
+        /// Server ID or name
+        server: String,
+        /// Instance ID or name
+        #[arg(long, short)]
+        instance: String,
+        /// Arguments used instead of the server's, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+        /// Environment variable set on top of the server's, as KEY=VALUE
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Config entry key used instead of the server's name
+        #[arg(long)]
+        alias: Option<String>,
+    },
+    /// Write enabled servers to client config files
+    Sync {
+        /// Instance ID or name
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        instance: Option<String>,
+        /// Sync every instance
+        #[arg(long)]
+        all: bool,
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import servers from an MCP config file into the registry, moving
+    /// env values that look like secrets into the keyring
+    Import { file: PathBuf },
+    /// Detect installed MCP clients
+    Detect,
+    /// Check that server commands and the runtimes they need (node, uv,
+    /// python, docker) can be found, and suggest fixes
+    Doctor {
+        /// Only report problems of this instance (ID or name)
+        #[arg(long, short)]
+        instance: Option<String>,
+    },
+    /// Check the database for corruption and for rows left behind by
+    /// deleted servers and instances
+    CheckDb {
+        /// Delete the rows left behind, or clear their references
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Run as an MCP server over stdio that proxies every server enabled for
+    /// an instance, so a client config needs only this one entry
+    Serve {
+        /// Instance ID or name
+        #[arg(long, short)]
+        instance: String,
+    },
+}
+
+// Parsed once, so the size of `Add` does not matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand)]
+enum ServersCommand {
+    /// List servers in the registry
+    List,
+    /// Add a server to the registry
+    Add {
+        name: String,
+        /// Command and arguments of a stdio server, after `--`
+        #[arg(last = true)]
+        command: Vec<String>,
+        /// URL of a remote server
+        #[arg(long, conflicts_with = "command")]
+        url: Option<String>,
+        /// Transport of a remote server: sse or streamable-http (default)
+        #[arg(long, requires = "url", conflicts_with = "command")]
+        transport: Option<String>,
+        /// Environment variable, as KEY=VALUE
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Environment variable whose value is kept in the keyring, as
+        /// KEY=VALUE
+        #[arg(long = "secret", value_name = "KEY=VALUE")]
+        secrets: Vec<String>,
+        /// HTTP header sent to a remote server, as KEY=VALUE
+        #[arg(long = "header", value_name = "KEY=VALUE", conflicts_with = "command")]
+        headers: Vec<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Tag, may be repeated
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Server to inherit the command, args, env and tags from, by ID or
+        /// name. Only arguments then follow `--`.
+        #[arg(long)]
+        parent: Option<String>,
+        /// Add the arguments after the parent's instead of replacing them
+        #[arg(long, requires = "parent")]
+        append_args: bool,
+    },
+    /// Show a server as it is synced, with what it inherits filled in
+    Show {
+        /// Server ID or name
+        server: String,
+    },
+    /// Remove a server from the registry
+    Rm {
+        /// Server ID or name
+        server: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum InstancesCommand {
+    /// List client instances
+    List,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncResult {
+    instance_id: String,
+    instance_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DetectedClient {
+    client_type: ClientType,
+    name: &'static str,
+    config_path: String,
+    has_config: bool,
+}
+
+/// Run the command line from the process arguments. Both `mcp-hub-cli` and
+/// `mcp-hub serve` end up here.
+pub fn main() -> ExitCode {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, String> {
+    let db_path = match cli.database {
+        Some(path) => path,
+        None => config::get_database_path().ok_or("Could not determine database path")?,
+    };
+    let db = Database::new(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+    let json = cli.json;
+
+    let backend = config::load_settings(&db)
+        .map_err(|e| e.to_string())?
+        .credential_backend;
+    credentials::use_backend(backend);
+    if backend == CredentialBackend::Vault {
+        if let Ok(passphrase) = std::env::var(VAULT_PASSPHRASE_VAR) {
+            let vault = credentials::vault().map_err(|e| e.to_string())?;
+            vault.unlock(&passphrase).map_err(|e| e.to_string())?;
+        }
+    }
+
+    match cli.command {
+        Command::Servers(ServersCommand::List) => {
+            let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+            if json {
+                print_json(&servers)?;
+            } else {
+                for server in &servers {
+                    println!("{}  {}  {}", server.id, server.name, server_target(server));
+                }
+            }
+        }
+        Command::Servers(ServersCommand::Add {
+            name,
+            command,
+            url,
+            transport,
+            env,
+            secrets,
+            headers,
+            description,
+            tags,
+            parent,
+            append_args,
+        }) => {
+            let parent = parent.map(|parent| find_server(&db, &parent)).transpose()?;
+            let mut server = match url {
+                Some(url) => {
+                    let transport = transport.as_deref().unwrap_or("streamable-http");
+                    let transport = transport
+                        .parse::<TransportType>()
+                        .ok()
+                        .filter(TransportType::is_remote)
+                        .ok_or_else(|| format!("Invalid remote transport: {}", transport))?;
+                    let mut server = McpServer::new(name, String::new(), Vec::new());
+                    server.transport = transport;
+                    server.url = Some(url);
+                    server
+                }
+                None if parent.is_some() => McpServer::new(name, String::new(), command),
+                None => {
+                    let mut command = command.into_iter();
+                    let program = command
+                        .next()
+                        .ok_or("A command after `--`, a --url or a --parent is required")?;
+                    McpServer::new(name, program, command.collect())
+                }
+            };
+            server.parent_id = parent.map(|parent| parent.id);
+            if append_args {
+                server.args_mode = ArgsMode::Append;
+            }
+            server.env = parse_key_values(&env)?;
+            server.headers = parse_key_values(&headers)?;
+            let secrets = parse_key_values(&secrets)?;
+            let names: Vec<String> = secrets.keys().cloned().collect();
+            server.env.extend(secrets);
+            server.description = description;
+            server.tags = tags;
+
+            let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+            inheritance::ancestors(&server, &servers).map_err(|e| e.to_string())?;
+
+            let moved =
+                credentials::move_env_to_keyring(&mut server, &names).map_err(|e| e.to_string())?;
+            if let Err(e) = db.create_server(&server) {
+                credentials::discard_server_credentials(&server.id, &moved);
+                return Err(e.to_string());
+            }
+            if json {
+                print_json(&server)?;
+            } else {
+                println!("Added server {} ({})", server.name, server.id);
+            }
+        }
+        Command::Servers(ServersCommand::Show { server }) => {
+            let server = find_server(&db, &server)?;
+            let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+            let resolved = inheritance::resolve(&server, &servers).map_err(|e| e.to_string())?;
+            if json {
+                print_json(&resolved)?;
+            } else {
+                println!("{}  {}", resolved.id, resolved.name);
+                if !resolved.inherits_from.is_empty() {
+                    let parents: Vec<&str> = resolved
+                        .inherits_from
+                        .iter()
+                        .filter_map(|id| servers.iter().find(|s| &s.id == id))
+                        .map(|s| s.name.as_str())
+                        .collect();
+                    println!("  inherits from: {}", parents.join(" → "));
+                }
+                println!("  runs: {}", server_target(&resolved));
+                if !resolved.env.is_empty() {
+                    let mut keys: Vec<&str> = resolved.env.keys().map(String::as_str).collect();
+                    keys.sort();
+                    println!("  env: {}", keys.join(", "));
+                }
+                if !resolved.headers.is_empty() {
+                    let mut keys: Vec<&str> = resolved.headers.keys().map(String::as_str).collect();
+                    keys.sort();
+                    println!("  headers: {}", keys.join(", "));
+                }
+                if !resolved.tags.is_empty() {
+                    println!("  tags: {}", resolved.tags.join(", "));
+                }
+            }
+        }
+        Command::Servers(ServersCommand::Rm { server }) => {
+            let server = find_server(&db, &server)?;
+            let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+            let heirs = inheritance::heirs(&server.id, &servers);
+            db.delete_server(&server.id).map_err(|e| e.to_string())?;
+            let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+            credentials::retire_server_credentials(&server, &heirs, &servers)
+                .map_err(|e| e.to_string())?;
+            if json {
+                print_json(&server)?;
+            } else {
+                println!("Removed server {} ({})", server.name, server.id);
+            }
+        }
+        Command::Instances(InstancesCommand::List) => {
+            let mut instances = db.get_all_instances().map_err(|e| e.to_string())?;
+            for instance in &mut instances {
+                instance.enabled_servers = db
+                    .get_enabled_servers_for_instance(&instance.id)
+                    .map_err(|e| e.to_string())?;
+            }
+            if json {
+                print_json(&instances)?;
+            } else {
+                for instance in &instances {
+                    println!(
+                        "{}  {}  {}  {}  {} servers",
+                        instance.id,
+                        instance.name,
+                        instance.client_type.as_str(),
+                        instance.status.as_str(),
+                        instance.enabled_servers.len()
+                    );
+                }
+            }
+        }
+        Command::Enable { server, instance } => set_enabled(&db, &server, &instance, true, json)?,
+        Command::Disable { server, instance } => set_enabled(&db, &server, &instance, false, json)?,
+        Command::Tools {
+            server,
+            instance,
+            allow,
+            deny,
+        } => {
+            let server = find_server(&db, &server)?;
+            let instance = find_instance(&db, &instance)?;
+            let filter = ToolFilter { allow, deny };
+            db.set_tool_filter(&instance.id, &server.id, &filter)
+                .map_err(|e| e.to_string())?;
+            if json {
+                print_json(&filter)?;
+            } else if filter.is_empty() {
+                println!("{} may use every tool of {}", instance.name, server.name);
+            } else {
+                println!(
+                    "Updated tool filter of {} for {}",
+                    server.name, instance.name
+                );
+            }
+        }
+        Command::Override {
+            server,
+            instance,
+            args,
+            env,
+            alias,
+        } => {
+            let server = find_server(&db, &server)?;
+            let instance = find_instance(&db, &instance)?;
+            let server_override = ServerOverride {
+                args: (!args.is_empty()).then_some(args),
+                env: parse_key_values(&env)?,
+                alias,
+            };
+            db.set_server_override(&instance.id, &server.id, &server_override)
+                .map_err(|e| e.to_string())?;
+            if json {
+                print_json(&server_override)?;
+            } else if server_override.is_empty() {
+                println!("{} runs {} as configured", instance.name, server.name);
+            } else {
+                println!("Updated override of {} for {}", server.name, instance.name);
+            }
+        }
+        Command::Sync {
+            instance,
+            all: _,
+            dry_run,
+        } => {
+            let instances = match instance {
+                Some(instance) => vec![find_instance(&db, &instance)?],
+                None => db.get_all_instances().map_err(|e| e.to_string())?,
+            };
+            let ok = if dry_run {
+                preview_sync(&db, &instances, json)?
+            } else {
+                run_sync(&db, &instances, json)?
+            };
+            if !ok {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Import { file } => {
+            let mut servers =
+                config::import_servers_from_config(&file).map_err(|e| e.to_string())?;
+            let secrets: Vec<ExtractedSecret> = servers
+                .iter_mut()
+                .flat_map(credentials::extract_secrets)
+                .collect();
+            for (i, server) in servers.iter().enumerate() {
+                if let Err(e) = db.create_server(server) {
+                    for server in &servers[i..] {
+                        credentials::discard_extracted(&server.id, &secrets);
+                    }
+                    return Err(e.to_string());
+                }
+            }
+            let result = ImportResult { servers, secrets };
+            if json {
+                print_json(&result)?;
+            } else {
+                println!(
+                    "Imported {} servers from {}",
+                    result.servers.len(),
+                    file.display()
+                );
+                for server in &result.servers {
+                    println!("  {}  {}", server.id, server.name);
+                }
+                for secret in &result.secrets {
+                    match &secret.error {
+                        None => println!(
+                            "Moved {} of {} into the keyring ({})",
+                            secret.env_var,
+                            secret.server_name,
+                            secret.reason.as_str()
+                        ),
+                        Some(e) => eprintln!(
+                            "Kept {} of {} in the registry: {}",
+                            secret.env_var, secret.server_name, e
+                        ),
+                    }
+                }
+            }
+        }
+        Command::Detect => {
+            let detected: Vec<DetectedClient> = config::detect_installed_clients()
+                .into_iter()
+                .map(|(client_type, path)| DetectedClient {
+                    name: client_type.display_name(),
+                    client_type,
+                    has_config: path.exists(),
+                    config_path: path.to_string_lossy().to_string(),
+                })
+                .collect();
+            if json {
+                print_json(&detected)?;
+            } else {
+                for client in &detected {
+                    let marker = if client.has_config {
+                        ""
+                    } else {
+                        "  (no config yet)"
+                    };
+                    println!("{}  {}{}", client.name, client.config_path, marker);
+                }
+            }
+        }
+        Command::Doctor { instance } => {
+            let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+            let mut instances = match instance {
+                Some(instance) => vec![find_instance(&db, &instance)?],
+                None => db.get_all_instances().map_err(|e| e.to_string())?,
+            };
+            for instance in &mut instances {
+                instance.enabled_servers = db
+                    .get_enabled_servers_for_instance(&instance.id)
+                    .map_err(|e| e.to_string())?;
+            }
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+            let report = runtime
+                .block_on(doctor::run(&servers, &instances))
+                .map_err(|e| e.to_string())?;
+
+            if json {
+                print_json(&report)?;
+            } else {
+                print_doctor_report(&report, &servers);
+            }
+            let failed = report
+                .servers
+                .iter()
+                .flat_map(|s| &s.issues)
+                .chain(report.instances.iter().flat_map(|i| &i.issues))
+                .any(|issue| issue.severity == IssueSeverity::Error);
+            if failed {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::CheckDb { repair } => {
+            let report = db.check_integrity(repair).map_err(|e| e.to_string())?;
+            if json {
+                print_json(&report)?;
+            } else {
+                for problem in &report.corruption {
+                    println!("corrupt: {}", problem);
+                }
+                for orphans in &report.orphans {
+                    let action = match orphans.repair {
+                        OrphanRepair::Delete => "deleted",
+                        OrphanRepair::ClearReference => "reference cleared",
+                    };
+                    println!(
+                        "{} rows of {} refer to a missing {} by {}{}",
+                        orphans.count,
+                        orphans.table,
+                        orphans.parent_table,
+                        orphans.column,
+                        if report.repaired {
+                            format!(" ({})", action)
+                        } else {
+                            String::new()
+                        }
+                    );
+                }
+                if report.corruption.is_empty() && report.orphans.is_empty() {
+                    println!("No problems found");
+                } else if !report.orphans.is_empty() && !report.repaired {
+                    println!("Run with --repair to fix the rows left behind");
+                }
+            }
+            if !report.corruption.is_empty() || (!report.orphans.is_empty() && !report.repaired) {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Serve { instance } => {
+            let instance = find_instance(&db, &instance)?;
+            let timeout = Duration::from_secs(
+                config::load_settings(&db)
+                    .map_err(|e| e.to_string())?
+                    .health_check_timeout_secs,
+            );
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+            runtime.block_on(proxy::serve(db, &instance.id, timeout))?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn set_enabled(
+    db: &Database,
+    server: &str,
+    instance: &str,
+    enabled: bool,
+    json: bool,
+) -> Result<(), String> {
+    let server = find_server(db, server)?;
+    let instance = find_instance(db, instance)?;
+    db.set_server_enabled_for_instance(&instance.id, &server.id, enabled)
+        .map_err(|e| e.to_string())?;
+
+    if json {
+        print_json(&serde_json::json!({
+            "instanceId": instance.id,
+            "serverId": server.id,
+            "enabled": enabled,
+        }))
+    } else {
+        let action = if enabled { "Enabled" } else { "Disabled" };
+        println!("{} {} for {}", action, server.name, instance.name);
+        Ok(())
+    }
+}
+
+/// Print the sync plan of each instance. Returns false if any plan failed.
+fn preview_sync(db: &Database, instances: &[ClientInstance], json: bool) -> Result<bool, String> {
+    let mut plans: Vec<SyncPlan> = Vec::new();
+    let mut ok = true;
+    for instance in instances {
+        match sync::prepare_instance_sync(db, &instance.id) {
+            Ok((_, prepared)) => plans.push(prepared.plan),
+            Err(e) => {
+                eprintln!("error: {}: {}", instance.name, e);
+                ok = false;
+            }
+        }
+    }
+
+    if json {
+        print_json(&plans)?;
+        return Ok(ok);
+    }
+
+    for plan in &plans {
+        println!("{} ({})", plan.instance_name, plan.config_path);
+        if !plan.has_changes() {
+            println!("  up to date");
+            continue;
+        }
+        if plan.creates_file {
+            println!("  creates the config file");
+        }
+        for entry in &plan.added {
+            println!("  + {}", entry.key);
+        }
+        for entry in &plan.changed {
+            let fields: Vec<&str> = entry.changes.iter().map(|c| c.field.as_str()).collect();
+            println!("  ~ {} ({})", entry.key, fields.join(", "));
+        }
+        for entry in &plan.removed {
+            println!("  - {}", entry.key);
+        }
+        if plan.touches_other_settings {
+            println!("  ! also rewrites settings outside the server entries");
+        }
+    }
+
+    Ok(ok)
+}
+
+/// Sync each instance. Returns false if any sync failed.
+fn run_sync(db: &Database, instances: &[ClientInstance], json: bool) -> Result<bool, String> {
+    let results: Vec<SyncResult> = instances
+        .iter()
+        .map(|instance| {
+            let (backup_path, error) = match sync::sync_instance(db, &instance.id, None) {
+                Ok(path) => (path.map(|p| p.to_string_lossy().to_string()), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            SyncResult {
+                instance_id: instance.id.clone(),
+                instance_name: instance.name.clone(),
+                backup_path,
+                error,
+            }
+        })
+        .collect();
+    let ok = results.iter().all(|result| result.error.is_none());
+
+    if json {
+        print_json(&results)?;
+        return Ok(ok);
+    }
+
+    for result in &results {
+        match (&result.error, &result.backup_path) {
+            (Some(e), _) => eprintln!("error: {}: {}", result.instance_name, e),
+            (None, Some(backup)) => {
+                println!("Synced {} (backup: {})", result.instance_name, backup)
+            }
+            (None, None) => println!("Synced {}", result.instance_name),
+        }
+    }
+
+    Ok(ok)
+}
+
+/// Find a server by ID, or else by name
+/// Print each server with its problems, then the problems particular to how
+/// each instance's client starts its servers
+fn print_doctor_report(report: &DoctorReport, servers: &[McpServer]) {
+    let server_name = |id: &str| {
+        servers
+            .iter()
+            .find(|s| s.id == id)
+            .map_or_else(|| id.to_string(), |s| s.name.clone())
+    };
+    let print_issue = |issue: &DoctorIssue, indent: &str| {
+        let severity = match issue.severity {
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Error => "error",
+        };
+        println!("{}{}: {}", indent, severity, issue.message);
+        println!("{}  fix: {}", indent, issue.fix);
+    };
+
+    for server in &report.servers {
+        let location = server
+            .resolved_path
+            .as_ref()
+            .or(server.gui_path.as_ref())
+            .or(server.found_elsewhere.as_ref())
+            .map(String::as_str)
+            .unwrap_or("not found");
+        let runtimes: Vec<String> = server
+            .runtimes
+            .iter()
+            .map(|r| format!("{} {}", r.name, r.version.as_deref().unwrap_or("?")))
+            .collect();
+        println!(
+            "{}  {}  {}  {}",
+            server.server_name,
+            server.command,
+            location,
+            runtimes.join(", ")
+        );
+        for issue in &server.issues {
+            print_issue(issue, "  ");
+        }
+    }
+
+    for instance in &report.instances {
+        let particular: Vec<&DoctorIssue> = instance
+            .issues
+            .iter()
+            .filter(|issue| {
+                !report
+                    .servers
+                    .iter()
+                    .flat_map(|s| &s.issues)
+                    .any(|s| s.server_id == issue.server_id && s.message == issue.message)
+            })
+            .collect();
+        if particular.is_empty() {
+            continue;
+        }
+        println!(
+            "\n{} ({}):",
+            instance.instance_name,
+            instance.client_type.display_name()
+        );
+        for issue in particular {
+            println!("  {}", server_name(&issue.server_id));
+            print_issue(issue, "    ");
+        }
+    }
+}
+
+fn find_server(db: &Database, key: &str) -> Result<McpServer, String> {
+    if let Some(server) = db.get_server(key).map_err(|e| e.to_string())? {
+        return Ok(server);
+    }
+
+    let mut matches: Vec<McpServer> = db
+        .get_all_servers()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|server| server.name == key)
+        .collect();
+    match matches.len() {
+        0 => Err(format!("Server not found: {}", key)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(format!("Several servers are named {}, use an ID", key)),
+    }
+}
+
+/// Find an instance by ID, or else by name
+fn find_instance(db: &Database, key: &str) -> Result<ClientInstance, String> {
+    if let Some(instance) = db.get_instance(key).map_err(|e| e.to_string())? {
+        return Ok(instance);
+    }
+
+    let mut matches: Vec<ClientInstance> = db
+        .get_all_instances()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|instance| instance.name == key)
+        .collect();
+    match matches.len() {
+        0 => Err(format!("Instance not found: {}", key)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(format!("Several instances are named {}, use an ID", key)),
+    }
+}
+
+/// Parse `KEY=VALUE` arguments
+fn parse_key_values(vars: &[String]) -> Result<HashMap<String, String>, String> {
+    vars.iter()
+        .map(|var| {
+            var.split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| format!("Expected KEY=VALUE: {}", var))
+        })
+        .collect()
+}
+
+/// What a server runs or connects to, for list output
+fn server_target(server: &McpServer) -> String {
+    match server.url {
+        Some(ref url) if server.transport.is_remote() => url.clone(),
+        _ => std::iter::once(server.command.as_str())
+            .chain(server.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let output = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", output);
+    Ok(())
+}
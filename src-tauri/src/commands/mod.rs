@@ -1,76 +1,159 @@
-use chrono::Utc;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use tauri::State;
+use std::time::Duration;
+use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 
 use crate::db::Database;
+use crate::error::HubError;
 use crate::models::{
-    AppSettings, ClientInstance, ClientType, ConfigBackup, DiscoverySettings, McpServer,
-    ServerHealth, HealthStatus,
+    AppSettings, BackupPreview, CapabilityInventory, CapabilityMatch, ClientInstance, ClientType,
+    ConfigBackup, CredentialStatus, DiscoverySettings, DoctorReport, DriftReport, ExtractedSecret,
+    HealthCheck, HealthStats, ImportResult, IntegrityReport, McpServer, McpServerEntry,
+    ReconcileAction, ReconcileResult, ServerHealth, ServerOverride, SyncPlan, ToolFilter,
+};
+use crate::services::{
+    self, backup, config, credentials, discovery, doctor, drift, health, health_monitor,
+    inheritance, inventory, sync, watcher,
 };
-use crate::services::{self, config, discovery};
 
 pub struct AppState {
     pub db: Mutex<Database>,
     pub discovery_server: Arc<RwLock<Option<discovery::DiscoveryServerHandle>>>,
+    pub config_watcher: Arc<Mutex<Option<watcher::ConfigWatcher>>>,
+}
+
+/// Point the config file watcher at the current set of instances
+fn refresh_watched_instances(state: &AppState, db: &Database) {
+    let Ok(guard) = state.config_watcher.lock() else {
+        return;
+    };
+    if let Some(ref config_watcher) = *guard {
+        match db.get_all_instances() {
+            Ok(instances) => config_watcher.watch_instances(&instances),
+            Err(e) => log::error!("Failed to update watched config files: {}", e),
+        }
+    }
 }
 
 // ==================== Server Commands ====================
 
 #[tauri::command]
-pub fn get_servers(state: State<AppState>) -> Result<Vec<McpServer>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_servers().map_err(|e| e.to_string())
+pub fn get_servers(state: State<AppState>) -> Result<Vec<McpServer>, HubError> {
+    let db = state.db.lock()?;
+    db.get_all_servers()
 }
 
 #[tauri::command]
-pub fn get_server(state: State<AppState>, id: String) -> Result<Option<McpServer>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_server(&id).map_err(|e| e.to_string())
+pub fn get_server(state: State<AppState>, id: String) -> Result<Option<McpServer>, HubError> {
+    let db = state.db.lock()?;
+    db.get_server(&id)
 }
 
+/// A server with what it inherits from its parents filled in, as it is
+/// synced to clients
 #[tauri::command]
-pub fn create_server(state: State<AppState>, server: McpServer) -> Result<McpServer, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_server(&server).map_err(|e| e.to_string())?;
+pub fn get_effective_server(state: State<AppState>, id: String) -> Result<McpServer, HubError> {
+    let db = state.db.lock()?;
+    let servers = db.get_all_servers()?;
+    let server = servers
+        .iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| HubError::not_found("Server"))?;
+    inheritance::resolve(server, &servers)
+}
+
+#[tauri::command]
+pub fn create_server(state: State<AppState>, server: McpServer) -> Result<McpServer, HubError> {
+    let db = state.db.lock()?;
+    inheritance::ancestors(&server, &db.get_all_servers()?)?;
+    db.create_server(&server)?;
     Ok(server)
 }
 
 #[tauri::command]
-pub fn update_server(state: State<AppState>, server: McpServer) -> Result<McpServer, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_server(&server).map_err(|e| e.to_string())?;
+pub fn update_server(state: State<AppState>, server: McpServer) -> Result<McpServer, HubError> {
+    let db = state.db.lock()?;
+    inheritance::ancestors(&server, &db.get_all_servers()?)?;
+    let previous = db.get_server(&server.id)?;
+    db.update_server(&server)?;
+
+    if let Some(previous) = previous {
+        if let Err(e) = credentials::forget_unreferenced(&server.id, &previous.env, &server.env) {
+            log::warn!("Failed to delete unused secrets of {}: {}", server.name, e);
+        }
+    }
     Ok(server)
 }
 
 #[tauri::command]
-pub fn delete_server(state: State<AppState>, id: String) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_server(&id).map_err(|e| e.to_string())
+pub fn delete_server(state: State<AppState>, id: String) -> Result<(), HubError> {
+    let db = state.db.lock()?;
+    let servers = db.get_all_servers()?;
+    db.delete_server(&id)?;
+
+    if let Some(server) = servers.iter().find(|s| s.id == id) {
+        let heirs = inheritance::heirs(&id, &servers);
+        let deleted = db
+            .get_all_servers()
+            .and_then(|servers| credentials::retire_server_credentials(server, &heirs, &servers));
+        if let Err(e) = deleted {
+            log::warn!("Failed to delete secrets of {}: {}", server.name, e);
+        }
+    }
+    Ok(())
+}
+
+/// Move the values of a server's env vars into the keyring, leaving
+/// `keyring:NAME` references in the registry
+#[tauri::command]
+pub fn secure_server_env(
+    state: State<AppState>,
+    server_id: String,
+    names: Vec<String>,
+) -> Result<McpServer, HubError> {
+    let db = state.db.lock()?;
+    let mut server = db
+        .get_server(&server_id)?
+        .ok_or_else(|| HubError::not_found("Server"))?;
+
+    let moved = credentials::move_env_to_keyring(&mut server, &names)?;
+    if !moved.is_empty() {
+        server.updated_at = chrono::Utc::now();
+        if let Err(e) = db.update_server(&server) {
+            credentials::discard_server_credentials(&server.id, &moved);
+            return Err(e);
+        }
+    }
+    Ok(server)
 }
 
 // ==================== Instance Commands ====================
 
 #[tauri::command]
-pub fn get_instances(state: State<AppState>) -> Result<Vec<ClientInstance>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_instances().map_err(|e| e.to_string())
+pub fn get_instances(state: State<AppState>) -> Result<Vec<ClientInstance>, HubError> {
+    let db = state.db.lock()?;
+    db.get_all_instances()
 }
 
 #[tauri::command]
-pub fn get_instance(state: State<AppState>, id: String) -> Result<Option<ClientInstance>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_instance(&id).map_err(|e| e.to_string())
+pub fn get_instance(
+    state: State<AppState>,
+    id: String,
+) -> Result<Option<ClientInstance>, HubError> {
+    let db = state.db.lock()?;
+    db.get_instance(&id)
 }
 
 #[tauri::command]
 pub fn create_instance(
     state: State<AppState>,
     instance: ClientInstance,
-) -> Result<ClientInstance, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_instance(&instance).map_err(|e| e.to_string())?;
+) -> Result<ClientInstance, HubError> {
+    let db = state.db.lock()?;
+    db.create_instance(&instance)?;
+    refresh_watched_instances(&state, &db);
     Ok(instance)
 }
 
@@ -78,16 +161,19 @@ pub fn create_instance(
 pub fn update_instance(
     state: State<AppState>,
     instance: ClientInstance,
-) -> Result<ClientInstance, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_instance(&instance).map_err(|e| e.to_string())?;
+) -> Result<ClientInstance, HubError> {
+    let db = state.db.lock()?;
+    db.update_instance(&instance)?;
+    refresh_watched_instances(&state, &db);
     Ok(instance)
 }
 
 #[tauri::command]
-pub fn delete_instance(state: State<AppState>, id: String) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_instance(&id).map_err(|e| e.to_string())
+pub fn delete_instance(state: State<AppState>, id: String) -> Result<(), HubError> {
+    let db = state.db.lock()?;
+    db.delete_instance(&id)?;
+    refresh_watched_instances(&state, &db);
+    Ok(())
 }
 
 // ==================== Server-Instance Mapping Commands ====================
@@ -98,100 +184,280 @@ pub fn set_server_enabled(
     instance_id: String,
     server_id: String,
     enabled: bool,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<(), HubError> {
+    let db = state.db.lock()?;
     db.set_server_enabled_for_instance(&instance_id, &server_id, enabled)
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn get_enabled_servers(
     state: State<AppState>,
     instance_id: String,
-) -> Result<Vec<String>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<String>, HubError> {
+    let db = state.db.lock()?;
     db.get_enabled_servers_for_instance(&instance_id)
-        .map_err(|e| e.to_string())
 }
 
-// ==================== Sync Commands ====================
-
+/// Tool filters of an instance by server ID
 #[tauri::command]
-pub fn sync_instance(state: State<AppState>, instance_id: String) -> Result<Option<String>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub fn get_tool_filters(
+    state: State<AppState>,
+    instance_id: String,
+) -> Result<HashMap<String, ToolFilter>, HubError> {
+    let db = state.db.lock()?;
+    db.get_tool_filters(&instance_id)
+}
 
-    // Get instance
-    let mut instance = db
-        .get_instance(&instance_id)
-        .map_err(|e| e.to_string())?
-        .ok_or("Instance not found")?;
+/// Set which tools of a server an instance may use; an empty filter allows all
+#[tauri::command]
+pub fn set_tool_filter(
+    state: State<AppState>,
+    instance_id: String,
+    server_id: String,
+    filter: ToolFilter,
+) -> Result<(), HubError> {
+    let db = state.db.lock()?;
+    db.set_tool_filter(&instance_id, &server_id, &filter)
+}
 
-    // Get enabled servers for this instance
-    instance.enabled_servers = db
-        .get_enabled_servers_for_instance(&instance_id)
-        .map_err(|e| e.to_string())?;
+/// Server overrides of an instance by server ID
+#[tauri::command]
+pub fn get_server_overrides(
+    state: State<AppState>,
+    instance_id: String,
+) -> Result<HashMap<String, ServerOverride>, HubError> {
+    let db = state.db.lock()?;
+    db.get_server_overrides(&instance_id)
+}
 
-    // Get all servers
-    let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+/// Set how an instance runs a server differently from its other instances;
+/// an empty override runs it as configured
+#[tauri::command]
+pub fn set_server_override(
+    state: State<AppState>,
+    instance_id: String,
+    server_id: String,
+    server_override: ServerOverride,
+) -> Result<(), HubError> {
+    let db = state.db.lock()?;
+    db.set_server_override(&instance_id, &server_id, &server_override)
+}
 
-    // Get backup directory
-    let backup_dir = config::get_backup_dir();
+// ==================== Sync Commands ====================
 
-    // Sync configuration
-    let backup_path = config::sync_servers_to_instance(
-        &instance,
-        &servers,
-        backup_dir.as_ref(),
-    )?;
+/// Compute sync plans without writing anything. Previews every instance
+/// unless `instance_ids` is given.
+#[tauri::command]
+pub fn preview_sync(
+    state: State<AppState>,
+    instance_ids: Option<Vec<String>>,
+) -> Result<Vec<SyncPlan>, HubError> {
+    let db = state.db.lock()?;
+
+    if let Some(ids) = instance_ids {
+        return ids
+            .iter()
+            .map(|id| sync::prepare_instance_sync(&db, id).map(|(_, prepared)| prepared.plan))
+            .collect();
+    }
 
-    // Record backup if created
-    if let Some(ref path) = backup_path {
-        let backup = ConfigBackup::new(instance_id.clone(), path.to_string_lossy().to_string());
-        db.create_backup(&backup).map_err(|e| e.to_string())?;
+    let instances = db.get_all_instances()?;
+    let mut plans = Vec::new();
+    for instance in instances {
+        match sync::prepare_instance_sync(&db, &instance.id) {
+            Ok((_, prepared)) => plans.push(prepared.plan),
+            Err(e) => log::error!("Failed to preview sync for instance {}: {}", instance.id, e),
+        }
     }
 
-    // Update last synced timestamp
-    instance.last_synced = Some(Utc::now());
-    db.update_instance(&instance).map_err(|e| e.to_string())?;
+    Ok(plans)
+}
+
+/// Sync an instance. Pass the `fingerprint` of a previewed plan to apply
+/// only that plan.
+#[tauri::command]
+pub fn sync_instance(
+    state: State<AppState>,
+    instance_id: String,
+    fingerprint: Option<String>,
+) -> Result<Option<String>, HubError> {
+    let db = state.db.lock()?;
+
+    let backup_path = sync::sync_instance(&db, &instance_id, fingerprint.as_deref())?;
 
     Ok(backup_path.map(|p| p.to_string_lossy().to_string()))
 }
 
+/// Sync every instance, or with `fingerprints` (instance ID -> plan
+/// fingerprint) only the previewed plans the user confirmed
 #[tauri::command]
-pub fn sync_all_instances(state: State<AppState>) -> Result<Vec<String>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let instances = db.get_all_instances().map_err(|e| e.to_string())?;
-    drop(db); // Release lock before calling sync_instance
+pub fn sync_all_instances(
+    state: State<AppState>,
+    fingerprints: Option<HashMap<String, String>>,
+) -> Result<Vec<String>, HubError> {
+    let db = state.db.lock()?;
+
+    let instance_ids: Vec<String> = match fingerprints {
+        Some(ref confirmed) => confirmed.keys().cloned().collect(),
+        None => db
+            .get_all_instances()?
+            .into_iter()
+            .map(|instance| instance.id)
+            .collect(),
+    };
 
     let mut synced = Vec::new();
-    for instance in instances {
-        // Re-acquire state for each instance
-        match sync_instance(state.clone(), instance.id.clone()) {
-            Ok(_) => synced.push(instance.id),
-            Err(e) => log::error!("Failed to sync instance {}: {}", instance.id, e),
+    for instance_id in instance_ids {
+        let expected = fingerprints
+            .as_ref()
+            .and_then(|confirmed| confirmed.get(&instance_id))
+            .map(String::as_str);
+        match sync::sync_instance(&db, &instance_id, expected) {
+            Ok(_) => synced.push(instance_id),
+            Err(e) => log::error!("Failed to sync instance {}: {}", instance_id, e),
         }
     }
 
     Ok(synced)
 }
 
+/// Check instances for edits made to their config files since the last
+/// sync. Checks every instance unless `instance_ids` is given.
+#[tauri::command]
+pub fn check_drift(
+    state: State<AppState>,
+    instance_ids: Option<Vec<String>>,
+) -> Result<Vec<DriftReport>, HubError> {
+    let db = state.db.lock()?;
+
+    let instance_ids = match instance_ids {
+        Some(ids) => ids,
+        None => db
+            .get_all_instances()?
+            .into_iter()
+            .map(|instance| instance.id)
+            .collect(),
+    };
+
+    instance_ids
+        .iter()
+        .map(|id| drift::check_drift(&db, id))
+        .collect()
+}
+
+#[tauri::command]
+pub fn reconcile_instance(
+    state: State<AppState>,
+    instance_id: String,
+    action: ReconcileAction,
+) -> Result<ReconcileResult, HubError> {
+    let db = state.db.lock()?;
+    drift::reconcile(&db, &instance_id, action)
+}
+
 // ==================== Import/Export Commands ====================
 
+/// Move secrets in the env of imported servers into the keyring before the
+/// servers are stored. If a server can't be stored, the secrets of it and
+/// the servers after it are deleted again.
+fn import_servers(db: &Database, mut servers: Vec<McpServer>) -> Result<ImportResult, HubError> {
+    let secrets: Vec<ExtractedSecret> = servers
+        .iter_mut()
+        .flat_map(credentials::extract_secrets)
+        .collect();
+    for (i, server) in servers.iter().enumerate() {
+        if let Err(e) = db.create_server(server) {
+            for server in &servers[i..] {
+                credentials::discard_extracted(&server.id, &secrets);
+            }
+            return Err(e);
+        }
+    }
+    Ok(ImportResult { servers, secrets })
+}
+
 #[tauri::command]
-pub fn import_from_file(state: State<AppState>, path: String) -> Result<Vec<McpServer>, String> {
+pub fn import_from_file(state: State<AppState>, path: String) -> Result<ImportResult, HubError> {
     let path = PathBuf::from(path);
     let servers = config::import_servers_from_config(&path)?;
 
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    for server in &servers {
-        db.create_server(server).map_err(|e| e.to_string())?;
+    let db = state.db.lock()?;
+    import_servers(&db, servers)
+}
+
+#[tauri::command]
+pub fn import_from_instance(
+    state: State<AppState>,
+    instance_id: String,
+) -> Result<ImportResult, HubError> {
+    let db = state.db.lock()?;
+
+    let instance = db
+        .get_instance(&instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+
+    let servers = config::import_servers_from_instance(&instance)?;
+    import_servers(&db, servers)
+}
+
+/// List config entries of an instance that MCP Hub does not manage
+#[tauri::command]
+pub fn get_unmanaged_servers(
+    state: State<AppState>,
+    instance_id: String,
+) -> Result<HashMap<String, McpServerEntry>, HubError> {
+    let db = state.db.lock()?;
+
+    let instance = db
+        .get_instance(&instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+    let managed_keys = db.get_managed_keys(&instance_id)?;
+
+    config::read_unmanaged_entries(&instance, &managed_keys)
+}
+
+/// Adopt unmanaged config entries into the registry and enable them for the
+/// instance, so later syncs manage them. Adopts every unmanaged entry unless
+/// `keys` is given.
+#[tauri::command]
+pub fn adopt_unmanaged_servers(
+    state: State<AppState>,
+    instance_id: String,
+    keys: Option<Vec<String>>,
+) -> Result<ImportResult, HubError> {
+    let db = state.db.lock()?;
+
+    let instance = db
+        .get_instance(&instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+    let managed_keys = db.get_managed_keys(&instance_id)?;
+
+    let adopted = config::adopt_unmanaged_entries(&instance, &managed_keys, keys.as_deref())?;
+
+    let mut servers = Vec::new();
+    let mut secrets = Vec::new();
+    for (key, mut server) in adopted {
+        let extracted = credentials::extract_secrets(&mut server);
+        if let Err(e) = db.create_server(&server) {
+            credentials::discard_extracted(&server.id, &extracted);
+            return Err(e);
+        }
+        secrets.extend(extracted);
+        db.set_server_enabled_for_instance(&instance_id, &server.id, true)?;
+        db.add_managed_key(&instance_id, &key, &server.id)?;
+        // The next sync moves the entry to the key syncs write it under
+        let synced_key = config::sanitize_server_name(&server.name);
+        if synced_key != key {
+            db.add_managed_key(&instance_id, &synced_key, &server.id)?;
+        }
+        servers.push(server);
     }
 
-    Ok(servers)
+    Ok(ImportResult { servers, secrets })
 }
 
 #[tauri::command]
-pub fn detect_clients() -> Result<Vec<DetectedClient>, String> {
+pub fn detect_clients() -> Result<Vec<DetectedClient>, HubError> {
     let detected = config::detect_installed_clients();
 
     Ok(detected
@@ -215,17 +481,17 @@ pub struct DetectedClient {
 // ==================== Credential Commands ====================
 
 #[tauri::command]
-pub fn store_credential(key: String, value: String) -> Result<(), String> {
+pub fn store_credential(key: String, value: String) -> Result<(), HubError> {
     services::credentials::store_credential(&key, &value)
 }
 
 #[tauri::command]
-pub fn get_credential(key: String) -> Result<Option<String>, String> {
+pub fn get_credential(key: String) -> Result<Option<String>, HubError> {
     services::credentials::get_credential(&key)
 }
 
 #[tauri::command]
-pub fn delete_credential(key: String) -> Result<(), String> {
+pub fn delete_credential(key: String) -> Result<(), HubError> {
     services::credentials::delete_credential(&key)
 }
 
@@ -234,121 +500,260 @@ pub fn is_credential_storage_available() -> bool {
     services::credentials::is_credential_storage_available()
 }
 
+/// The credential backend in use and whether it works
+#[tauri::command]
+pub fn get_credential_status() -> CredentialStatus {
+    credentials::status()
+}
+
+/// Unlock the vault, or create it with this passphrase if there is none
+#[tauri::command]
+pub fn unlock_vault(passphrase: String) -> Result<CredentialStatus, HubError> {
+    credentials::vault()?.unlock(&passphrase)?;
+    Ok(credentials::status())
+}
+
+#[tauri::command]
+pub fn lock_vault() -> Result<CredentialStatus, HubError> {
+    credentials::vault()?.lock();
+    Ok(credentials::status())
+}
+
 // ==================== Backup Commands ====================
 
 #[tauri::command]
-pub fn get_backups(state: State<AppState>, instance_id: String) -> Result<Vec<ConfigBackup>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub fn get_backups(
+    state: State<AppState>,
+    instance_id: String,
+) -> Result<Vec<ConfigBackup>, HubError> {
+    let db = state.db.lock()?;
     db.get_backups_for_instance(&instance_id)
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn restore_backup(backup_id: String, state: State<AppState>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub fn get_backup(
+    state: State<AppState>,
+    backup_id: String,
+) -> Result<Option<ConfigBackup>, HubError> {
+    let db = state.db.lock()?;
+    db.get_backup(&backup_id)
+}
 
-    // This is a simplified restore - in production you'd want more validation
-    let _backups = db
-        .get_backups_for_instance(&backup_id)
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn preview_backup(
+    state: State<AppState>,
+    backup_id: String,
+) -> Result<BackupPreview, HubError> {
+    let db = state.db.lock()?;
+    backup::preview_backup(&db, &backup_id)
+}
+
+/// Restore a backup, returning the backup taken of the file it replaced
+#[tauri::command]
+pub fn restore_backup(
+    backup_id: String,
+    state: State<AppState>,
+) -> Result<Option<ConfigBackup>, HubError> {
+    let db = state.db.lock()?;
+    backup::restore_backup(&db, &backup_id)
+}
 
-    // TODO: Implement actual restore logic
-    Err("Restore not yet implemented".to_string())
+/// Apply the backup retention policy now, returning the number of backups removed
+#[tauri::command]
+pub fn prune_backups(state: State<AppState>) -> Result<usize, HubError> {
+    let db = state.db.lock()?;
+    let settings = config::load_settings(&db)?;
+    backup::prune_backups(&db, &settings)
 }
 
 // ==================== Settings Commands ====================
 
 #[tauri::command]
-pub fn get_settings(state: State<AppState>) -> Result<AppSettings, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub fn get_settings(state: State<AppState>) -> Result<AppSettings, HubError> {
+    let db = state.db.lock()?;
+    config::load_settings(&db)
+}
+
+#[tauri::command]
+pub fn save_settings(state: State<AppState>, settings: AppSettings) -> Result<(), HubError> {
+    let db = state.db.lock()?;
+    config::save_settings(&db, &settings)?;
+    credentials::use_backend(settings.credential_backend);
+    Ok(())
+}
 
-    let settings_json = db
-        .get_setting("app_settings")
-        .map_err(|e| e.to_string())?;
+// ==================== Health Check Commands ====================
 
-    match settings_json {
-        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
-        None => Ok(AppSettings::default()),
+/// Check a server with an MCP handshake. Uses the timeout from the settings
+/// unless `timeout_secs` is given. The server is checked with what it
+/// inherits from its parents. Checks of registry servers are added to their
+/// health history.
+#[tauri::command]
+pub async fn check_server_health(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    server: McpServer,
+    timeout_secs: Option<u64>,
+) -> Result<ServerHealth, HubError> {
+    let (server, timeout_secs) = {
+        let db = state.db.lock()?;
+        let server = inheritance::resolve(&server, &db.get_all_servers()?)?;
+        let timeout_secs = match timeout_secs {
+            Some(secs) => secs,
+            None => config::load_settings(&db)?.health_check_timeout_secs,
+        };
+        (server, timeout_secs)
+    };
+
+    let result = health::check_server_health(&server, Duration::from_secs(timeout_secs)).await;
+
+    let db = state.db.lock()?;
+    if db.get_server(&server.id)?.is_some() {
+        health_monitor::report(&app, &db, &server, &result)?;
     }
+    Ok(result)
 }
 
+/// Stored health checks of a server, newest first
 #[tauri::command]
-pub fn save_settings(state: State<AppState>, settings: AppSettings) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
-    db.set_setting("app_settings", &json)
-        .map_err(|e| e.to_string())
+pub fn get_health_history(
+    state: State<AppState>,
+    server_id: String,
+    limit: Option<u32>,
+) -> Result<Vec<HealthCheck>, HubError> {
+    let db = state.db.lock()?;
+    db.get_health_history(&server_id, limit.unwrap_or(100))
 }
 
-// ==================== Health Check Commands ====================
+/// Uptime and flakiness of every server with stored health checks
+#[tauri::command]
+pub fn get_health_stats(state: State<AppState>) -> Result<Vec<HealthStats>, HubError> {
+    let db = state.db.lock()?;
+    health_monitor::health_stats(&db)
+}
+
+// ==================== Capability Commands ====================
 
+/// Probe a server for its tools, resources and prompts and record them
 #[tauri::command]
-pub async fn check_server_health(server: McpServer) -> Result<ServerHealth, String> {
-    use std::process::Command;
-    use std::time::Duration;
-
-    // Try to run the command with --version or --help to check if it exists
-    let result = tokio::time::timeout(Duration::from_secs(5), async {
-        let output = Command::new(&server.command)
-            .args(["--version"])
-            .output();
-
-        match output {
-            Ok(output) => {
-                if output.status.success() {
-                    Ok(ServerHealth {
-                        server_id: server.id.clone(),
-                        status: HealthStatus::Healthy,
-                        error_message: None,
-                        last_checked: Utc::now(),
-                    })
-                } else {
-                    // Command exists but returned error - might still be healthy
-                    Ok(ServerHealth {
-                        server_id: server.id.clone(),
-                        status: HealthStatus::Unknown,
-                        error_message: Some("Command returned non-zero exit code".to_string()),
-                        last_checked: Utc::now(),
-                    })
-                }
+pub async fn probe_server_capabilities(
+    state: State<'_, AppState>,
+    server_id: String,
+) -> Result<CapabilityInventory, HubError> {
+    let (server, timeout) = {
+        let db = state.db.lock()?;
+        let servers = db.get_all_servers()?;
+        let server = servers
+            .iter()
+            .find(|s| s.id == server_id)
+            .ok_or_else(|| HubError::not_found("Server"))?;
+        let server = inheritance::resolve(server, &servers)?;
+        (
+            server,
+            config::load_settings(&db)?.health_check_timeout_secs,
+        )
+    };
+
+    let probed = inventory::probe_capabilities(&server, Duration::from_secs(timeout)).await?;
+
+    let db = state.db.lock()?;
+    inventory::record_inventory(&db, probed)
+}
+
+/// Probe every stdio server in the registry, one at a time. Servers that fail
+/// to answer keep their previous inventory.
+#[tauri::command]
+pub async fn probe_all_capabilities(
+    state: State<'_, AppState>,
+) -> Result<Vec<CapabilityInventory>, HubError> {
+    let (servers, timeout) = {
+        let db = state.db.lock()?;
+        let servers = inheritance::resolve_all(&db.get_all_servers()?);
+        (
+            servers,
+            config::load_settings(&db)?.health_check_timeout_secs,
+        )
+    };
+
+    let mut inventories = Vec::new();
+    for server in servers.iter().filter(|s| !s.transport.is_remote()) {
+        match inventory::probe_capabilities(server, Duration::from_secs(timeout)).await {
+            Ok(probed) => {
+                let db = state.db.lock()?;
+                inventories.push(inventory::record_inventory(&db, probed)?);
             }
-            Err(e) => Ok(ServerHealth {
-                server_id: server.id.clone(),
-                status: HealthStatus::Error,
-                error_message: Some(format!("Failed to execute command: {}", e)),
-                last_checked: Utc::now(),
-            }),
+            Err(e) => log::warn!("Failed to probe {}: {}", server.name, e),
         }
-    })
-    .await;
-
-    match result {
-        Ok(health) => health,
-        Err(_) => Ok(ServerHealth {
-            server_id: server.id,
-            status: HealthStatus::Error,
-            error_message: Some("Health check timed out".to_string()),
-            last_checked: Utc::now(),
-        }),
     }
+
+    Ok(inventories)
+}
+
+#[tauri::command]
+pub fn get_server_capabilities(
+    state: State<AppState>,
+    server_id: String,
+) -> Result<Option<CapabilityInventory>, HubError> {
+    let db = state.db.lock()?;
+    db.get_capability_inventory(&server_id)
+}
+
+/// Search the recorded tools, resources and prompts of every server
+#[tauri::command]
+pub fn search_capabilities(
+    state: State<AppState>,
+    query: String,
+) -> Result<Vec<CapabilityMatch>, HubError> {
+    let db = state.db.lock()?;
+    db.search_capabilities(query.trim())
+}
+
+// ==================== Doctor Commands ====================
+
+/// Check that every stdio server's command and runtimes can be found, and
+/// what that means for each instance
+#[tauri::command]
+pub async fn run_doctor(state: State<'_, AppState>) -> Result<DoctorReport, HubError> {
+    let (servers, instances) = {
+        let db = state.db.lock()?;
+        let servers = db.get_all_servers()?;
+        let mut instances = db.get_all_instances()?;
+        for instance in &mut instances {
+            instance.enabled_servers = db.get_enabled_servers_for_instance(&instance.id)?;
+        }
+        (servers, instances)
+    };
+
+    doctor::run(&servers, &instances).await
 }
 
 // ==================== Utility Commands ====================
 
+/// Check the database for corruption and for rows left behind by deleted
+/// servers and instances, repairing the latter if asked
 #[tauri::command]
-pub fn get_app_data_dir() -> Result<String, String> {
+pub fn check_database_integrity(
+    state: State<AppState>,
+    repair: bool,
+) -> Result<IntegrityReport, HubError> {
+    let db = state.db.lock()?;
+    db.check_integrity(repair)
+}
+
+#[tauri::command]
+pub fn get_app_data_dir() -> Result<String, HubError> {
     config::get_app_data_dir()
         .map(|p| p.to_string_lossy().to_string())
-        .ok_or_else(|| "Could not determine app data directory".to_string())
+        .ok_or_else(|| HubError::not_found("App data directory"))
 }
 
 #[tauri::command]
-pub fn get_default_config_path(client_type: ClientType) -> Result<Option<String>, String> {
+pub fn get_default_config_path(client_type: ClientType) -> Result<Option<String>, HubError> {
     Ok(config::get_default_config_path(&client_type).map(|p| p.to_string_lossy().to_string()))
 }
 
 #[tauri::command]
-pub fn read_config_file(path: String) -> Result<crate::models::McpConfigFile, String> {
+pub fn read_config_file(path: String) -> Result<crate::models::McpConfigFile, HubError> {
     let path = PathBuf::from(path);
     config::read_config_file(&path)
 }
@@ -361,8 +766,12 @@ pub fn get_registries() -> Vec<services::registry::RegistrySource> {
 }
 
 #[tauri::command]
-pub async fn get_registry_servers(registry_id: String) -> Result<Vec<services::registry::RegistryServer>, String> {
-    services::registry::fetch_registry_servers(&registry_id).await
+pub async fn get_registry_servers(
+    registry_id: String,
+) -> Result<Vec<services::registry::RegistryServer>, HubError> {
+    services::registry::fetch_registry_servers(&registry_id)
+        .await
+        .map_err(HubError::Other)
 }
 
 #[tauri::command]
@@ -370,34 +779,22 @@ pub fn import_from_registry(
     state: State<AppState>,
     registry_id: String,
     servers: Vec<services::registry::RegistryServer>,
-) -> Result<Vec<McpServer>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let mut imported = Vec::new();
-
-    for registry_server in servers {
-        let server = services::registry::registry_server_to_mcp_server(&registry_server, &registry_id);
-        db.create_server(&server).map_err(|e| e.to_string())?;
-        imported.push(server);
-    }
-
-    Ok(imported)
+) -> Result<ImportResult, HubError> {
+    let db = state.db.lock()?;
+    let servers = servers
+        .iter()
+        .map(|server| services::registry::registry_server_to_mcp_server(server, &registry_id))
+        .collect();
+    import_servers(&db, servers)
 }
 
 // ==================== Discovery Commands ====================
 
 /// Get current discovery settings
 #[tauri::command]
-pub fn get_discovery_settings(state: State<AppState>) -> Result<DiscoverySettings, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let settings_json = db.get_setting("app_settings").map_err(|e| e.to_string())?;
-
-    match settings_json {
-        Some(json) => {
-            let settings: AppSettings = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-            Ok(settings.discovery)
-        }
-        None => Ok(DiscoverySettings::default()),
-    }
+pub fn get_discovery_settings(state: State<AppState>) -> Result<DiscoverySettings, HubError> {
+    let db = state.db.lock()?;
+    Ok(config::load_settings(&db)?.discovery)
 }
 
 /// Update discovery settings and apply changes
@@ -405,26 +802,20 @@ pub fn get_discovery_settings(state: State<AppState>) -> Result<DiscoverySetting
 pub async fn update_discovery_settings(
     state: State<'_, AppState>,
     settings: DiscoverySettings,
-) -> Result<(), String> {
+) -> Result<(), HubError> {
     // Scope the mutex lock to avoid holding it across await points
     let (old_settings, servers) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let settings_json = db.get_setting("app_settings").map_err(|e| e.to_string())?;
-
-        let mut app_settings: AppSettings = match settings_json {
-            Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string())?,
-            None => AppSettings::default(),
-        };
+        let db = state.db.lock()?;
+        let mut app_settings = config::load_settings(&db)?;
 
         let old_settings = app_settings.discovery.clone();
         app_settings.discovery = settings.clone();
 
         // Save updated settings
-        let json = serde_json::to_string(&app_settings).map_err(|e| e.to_string())?;
-        db.set_setting("app_settings", &json).map_err(|e| e.to_string())?;
+        config::save_settings(&db, &app_settings)?;
 
         // Get servers for discovery updates
-        let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+        let servers = inheritance::resolve_all(&db.get_all_servers()?);
 
         (old_settings, servers)
     }; // db lock released here
@@ -432,15 +823,15 @@ pub async fn update_discovery_settings(
     // Handle ~/.mcp directory changes
     if settings.mcp_directory_enabled && !old_settings.mcp_directory_enabled {
         // Enable: write all servers
-        discovery::write_mcp_directory(&servers)?;
+        discovery::write_mcp_directory(&servers).map_err(HubError::Other)?;
         log::info!("MCP directory discovery enabled");
     } else if !settings.mcp_directory_enabled && old_settings.mcp_directory_enabled {
         // Disable: clear all managed files
-        discovery::clear_mcp_directory()?;
+        discovery::clear_mcp_directory().map_err(HubError::Other)?;
         log::info!("MCP directory discovery disabled");
     } else if settings.mcp_directory_enabled {
         // Still enabled: update files
-        discovery::write_mcp_directory(&servers)?;
+        discovery::write_mcp_directory(&servers).map_err(HubError::Other)?;
     }
 
     // Handle HTTP server changes
@@ -448,7 +839,9 @@ pub async fn update_discovery_settings(
 
     if settings.http_server_enabled && !old_settings.http_server_enabled {
         // Enable: start server
-        let handle = discovery::start_discovery_server(settings.http_server_port, servers).await?;
+        let handle = discovery::start_discovery_server(settings.http_server_port, servers)
+            .await
+            .map_err(HubError::Other)?;
         *server_guard = Some(handle);
         log::info!("Discovery HTTP server started on port {}", settings.http_server_port);
     } else if !settings.http_server_enabled && old_settings.http_server_enabled {
@@ -462,7 +855,9 @@ pub async fn update_discovery_settings(
         if let Some(handle) = server_guard.take() {
             handle.shutdown();
         }
-        let handle = discovery::start_discovery_server(settings.http_server_port, servers).await?;
+        let handle = discovery::start_discovery_server(settings.http_server_port, servers)
+            .await
+            .map_err(HubError::Other)?;
         *server_guard = Some(handle);
         log::info!("Discovery HTTP server restarted on port {}", settings.http_server_port);
     }
@@ -472,27 +867,23 @@ pub async fn update_discovery_settings(
 
 /// Manually refresh discovery (update ~/.mcp files and HTTP server)
 #[tauri::command]
-pub async fn refresh_discovery(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn refresh_discovery(state: State<'_, AppState>) -> Result<(), HubError> {
     // Scope the mutex lock to avoid holding it across await points
     let (settings, servers) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let db = state.db.lock()?;
 
         // Get settings
-        let settings_json = db.get_setting("app_settings").map_err(|e| e.to_string())?;
-        let settings: AppSettings = match settings_json {
-            Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string())?,
-            None => AppSettings::default(),
-        };
+        let settings = config::load_settings(&db)?;
 
         // Get servers
-        let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+        let servers = inheritance::resolve_all(&db.get_all_servers()?);
 
         (settings, servers)
     }; // db lock released here
 
     // Update ~/.mcp directory if enabled
     if settings.discovery.mcp_directory_enabled {
-        discovery::write_mcp_directory(&servers)?;
+        discovery::write_mcp_directory(&servers).map_err(HubError::Other)?;
         log::info!("Refreshed ~/.mcp directory with {} servers", servers.len());
     }
 
@@ -508,19 +899,11 @@ pub async fn refresh_discovery(state: State<'_, AppState>) -> Result<(), String>
 
 /// Get discovery server status
 #[tauri::command]
-pub async fn get_discovery_status(state: State<'_, AppState>) -> Result<DiscoveryStatus, String> {
+pub async fn get_discovery_status(state: State<'_, AppState>) -> Result<DiscoveryStatus, HubError> {
     // Scope the mutex lock to avoid holding it across await points
     let settings = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-
-        // Get settings
-        let settings_json = db.get_setting("app_settings").map_err(|e| e.to_string())?;
-        let settings: AppSettings = match settings_json {
-            Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string())?,
-            None => AppSettings::default(),
-        };
-
-        settings
+        let db = state.db.lock()?;
+        config::load_settings(&db)?
     }; // db lock released here
 
     // Check HTTP server status
@@ -0,0 +1,50 @@
+-- Database as left by MCP Hub before schema versioning: initial
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE servers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                command TEXT NOT NULL,
+                args TEXT NOT NULL,
+                env TEXT NOT NULL,
+                tags TEXT,
+                source_type TEXT,
+                source_url TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+INSERT INTO servers VALUES('s1','filesystem','Files','npx','["-y","@modelcontextprotocol/server-filesystem"]','{"LOG_LEVEL":"info"}','["files"]','manual',NULL,'2025-01-01T00:00:00+00:00','2025-01-01T00:00:00+00:00');
+CREATE TABLE client_instances (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                client_type TEXT NOT NULL,
+                config_path TEXT NOT NULL,
+                is_default INTEGER DEFAULT 0,
+                last_synced TEXT,
+                created_at TEXT NOT NULL
+            );
+INSERT INTO client_instances VALUES('i1','Claude','claude-desktop','/tmp/claude_desktop_config.json',1,NULL,'2025-01-01T00:00:00+00:00');
+CREATE TABLE instance_servers (
+                instance_id TEXT NOT NULL,
+                server_id TEXT NOT NULL,
+                enabled INTEGER DEFAULT 1,
+                PRIMARY KEY (instance_id, server_id),
+                FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE,
+                FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+            );
+INSERT INTO instance_servers VALUES('i1','s1',1);
+CREATE TABLE backups (
+                id TEXT PRIMARY KEY,
+                instance_id TEXT NOT NULL,
+                backup_path TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+            );
+INSERT INTO backups VALUES('b1','i1','/tmp/claude_desktop_config.json_20250101_000000.backup','2025-01-01T00:00:00+00:00');
+CREATE TABLE settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+INSERT INTO settings VALUES('app_settings','{"theme":"dark","autoStart":false,"createBackups":true,"backupRetentionDays":30}');
+COMMIT;
@@ -0,0 +1,120 @@
+-- Database at schema version 4: server overrides
+PRAGMA user_version=4;
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE servers (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            command TEXT NOT NULL,
+            args TEXT NOT NULL,
+            env TEXT NOT NULL,
+            tags TEXT,
+            source_type TEXT,
+            source_url TEXT,
+            parent_id TEXT,
+            transport_type TEXT NOT NULL DEFAULT 'stdio',
+            url TEXT,
+            headers TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL, args_mode TEXT NOT NULL DEFAULT 'replace',
+            FOREIGN KEY (parent_id) REFERENCES servers(id) ON DELETE SET NULL
+        );
+INSERT INTO servers VALUES('s1','filesystem','Files','npx','["-y","@modelcontextprotocol/server-filesystem"]','{"LOG_LEVEL":"info"}','["files"]','manual',NULL,NULL,'stdio',NULL,NULL,'2025-01-01T00:00:00+00:00','2025-01-01T00:00:00+00:00','replace');
+INSERT INTO servers VALUES('s2','filesystem (work)',NULL,'npx','[]','{}',NULL,NULL,NULL,'s1','stdio',NULL,NULL,'2025-01-01T00:00:00+00:00','2025-01-01T00:00:00+00:00','replace');
+CREATE TABLE client_instances (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            client_type TEXT NOT NULL,
+            config_path TEXT NOT NULL,
+            is_default INTEGER DEFAULT 0,
+            last_synced TEXT,
+            last_modified TEXT,
+            status TEXT NOT NULL DEFAULT 'unknown',
+            status_message TEXT,
+            created_at TEXT NOT NULL
+        , resolve_commands INTEGER NOT NULL DEFAULT 0, inject_path INTEGER NOT NULL DEFAULT 0);
+INSERT INTO client_instances VALUES('i1','Claude','claude-desktop','/tmp/claude_desktop_config.json',1,NULL,NULL,'unknown',NULL,'2025-01-01T00:00:00+00:00',0,0);
+CREATE TABLE instance_servers (
+            instance_id TEXT NOT NULL,
+            server_id TEXT NOT NULL,
+            enabled INTEGER DEFAULT 1, tool_allow TEXT, tool_deny TEXT, args_override TEXT, env_overlay TEXT, alias TEXT,
+            PRIMARY KEY (instance_id, server_id),
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+INSERT INTO instance_servers VALUES('i1','s1',1,NULL,NULL,NULL,NULL,NULL);
+CREATE TABLE managed_keys (
+            instance_id TEXT NOT NULL,
+            entry_key TEXT NOT NULL,
+            server_id TEXT,
+            PRIMARY KEY (instance_id, entry_key),
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+        );
+CREATE TABLE sync_snapshots (
+            instance_id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            file_mtime TEXT,
+            synced_at TEXT NOT NULL,
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+        );
+CREATE TABLE capability_probes (
+            server_id TEXT PRIMARY KEY,
+            probed_at TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+CREATE TABLE server_tools (
+            server_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            input_schema TEXT NOT NULL,
+            probed_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, name),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+CREATE TABLE server_resources (
+            server_id TEXT NOT NULL,
+            uri TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            mime_type TEXT,
+            probed_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, uri),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+CREATE TABLE server_prompts (
+            server_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            arguments TEXT NOT NULL,
+            probed_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, name),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+CREATE TABLE health_checks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            server_id TEXT NOT NULL,
+            status TEXT NOT NULL,
+            error_message TEXT,
+            latency_ms INTEGER,
+            checked_at TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+CREATE TABLE backups (
+            id TEXT PRIMARY KEY,
+            instance_id TEXT NOT NULL,
+            backup_path TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+        );
+INSERT INTO backups VALUES('b1','i1','/tmp/claude_desktop_config.json_20250101_000000.backup','2025-01-01T00:00:00+00:00');
+CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+INSERT INTO settings VALUES('app_settings','{"theme":"dark","autoStart":false,"createBackups":true,"backupRetentionDays":30}');
+CREATE INDEX idx_health_checks_server
+            ON health_checks(server_id, checked_at);
+COMMIT;
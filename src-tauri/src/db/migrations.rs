@@ -0,0 +1,523 @@
+//! Ordered schema migrations, tracked in `PRAGMA user_version`.
+//!
+//! Migration `n` takes the schema from version `n - 1` to `n`. Pending
+//! migrations run in one transaction after the database file has been
+//! copied aside, so a failed upgrade leaves the database as it was.
+
+use rusqlite::{params, Connection, Result as SqlResult, Transaction};
+use std::path::{Path, PathBuf};
+
+use crate::error::{HubError, HubResult};
+use crate::services::config::sanitize_server_name;
+
+type Migration = fn(&Transaction) -> SqlResult<()>;
+
+/// Every migration, oldest first. Only ever append to this list: a
+/// migration that has run on users' databases must not change.
+const MIGRATIONS: &[Migration] = &[
+    unversioned_schema,
+    remove_orphans,
+    add_args_mode,
+    add_server_overrides,
+    seed_managed_keys,
+];
+
+/// Version of the schema the migrations lead to
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Bring the database up to `SCHEMA_VERSION`. Unless the database is new,
+/// a copy of the file at `path` is taken first.
+pub fn migrate(conn: &mut Connection, path: Option<&Path>) -> HubResult<()> {
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(HubError::Conflict(format!(
+            "Database schema version {} is newer than this version of MCP Hub supports ({})",
+            version, SCHEMA_VERSION
+        )));
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    if let Some(path) = path {
+        if has_tables(conn)? {
+            let copy = backup_path(path, version);
+            log::info!(
+                "Copying database to {} before migrating it from schema version {}",
+                copy.display(),
+                version
+            );
+            copy_database(conn, &copy)?;
+        }
+    }
+
+    let tx = conn.transaction()?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating database to schema version {}", index + 1);
+        migration(&tx)?;
+    }
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn has_tables(conn: &Connection) -> SqlResult<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table')",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Where the database is copied before migrating from `version`, e.g.
+/// `mcp-hub.db.v0.bak` next to `mcp-hub.db`
+pub fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// Write a consistent copy of the database, replacing an earlier copy from
+/// the same version
+fn copy_database(conn: &Connection, copy: &Path) -> SqlResult<()> {
+    if copy.exists() {
+        std::fs::remove_file(copy).map_err(|e| {
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+                Some(format!("Failed to replace {}: {}", copy.display(), e)),
+            )
+        })?;
+    }
+    conn.execute("VACUUM INTO ?1", [copy.to_string_lossy()])?;
+    Ok(())
+}
+
+/// Version 1: the schema as it was before versioning. Databases from then
+/// may have been created by any earlier release, so tables are created if
+/// missing and columns added where they are not there yet.
+fn unversioned_schema(tx: &Transaction) -> SqlResult<()> {
+    tx.execute_batch(
+        "
+        -- Central server registry
+        CREATE TABLE IF NOT EXISTS servers (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            command TEXT NOT NULL,
+            args TEXT NOT NULL,
+            env TEXT NOT NULL,
+            tags TEXT,
+            source_type TEXT,
+            source_url TEXT,
+            parent_id TEXT,
+            transport_type TEXT NOT NULL DEFAULT 'stdio',
+            url TEXT,
+            headers TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (parent_id) REFERENCES servers(id) ON DELETE SET NULL
+        );
+
+        -- Client instances
+        CREATE TABLE IF NOT EXISTS client_instances (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            client_type TEXT NOT NULL,
+            config_path TEXT NOT NULL,
+            is_default INTEGER DEFAULT 0,
+            last_synced TEXT,
+            last_modified TEXT,
+            status TEXT NOT NULL DEFAULT 'unknown',
+            status_message TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        -- Server-to-instance mapping
+        CREATE TABLE IF NOT EXISTS instance_servers (
+            instance_id TEXT NOT NULL,
+            server_id TEXT NOT NULL,
+            enabled INTEGER DEFAULT 1,
+            PRIMARY KEY (instance_id, server_id),
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        -- Config entry keys written by MCP Hub, per instance
+        CREATE TABLE IF NOT EXISTS managed_keys (
+            instance_id TEXT NOT NULL,
+            entry_key TEXT NOT NULL,
+            server_id TEXT,
+            PRIMARY KEY (instance_id, entry_key),
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+        );
+
+        -- Config file content as of the last sync, per instance
+        CREATE TABLE IF NOT EXISTS sync_snapshots (
+            instance_id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            file_mtime TEXT,
+            synced_at TEXT NOT NULL,
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+        );
+
+        -- Latest capability probe per server
+        CREATE TABLE IF NOT EXISTS capability_probes (
+            server_id TEXT PRIMARY KEY,
+            probed_at TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        -- Tools, resources and prompts seen by the latest probe
+        CREATE TABLE IF NOT EXISTS server_tools (
+            server_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            input_schema TEXT NOT NULL,
+            probed_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, name),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS server_resources (
+            server_id TEXT NOT NULL,
+            uri TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            mime_type TEXT,
+            probed_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, uri),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS server_prompts (
+            server_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            arguments TEXT NOT NULL,
+            probed_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, name),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        -- Health check history
+        CREATE TABLE IF NOT EXISTS health_checks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            server_id TEXT NOT NULL,
+            status TEXT NOT NULL,
+            error_message TEXT,
+            latency_ms INTEGER,
+            checked_at TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_health_checks_server
+            ON health_checks(server_id, checked_at);
+
+        -- Config file backups
+        CREATE TABLE IF NOT EXISTS backups (
+            id TEXT PRIMARY KEY,
+            instance_id TEXT NOT NULL,
+            backup_path TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
+        );
+
+        -- App settings
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    // Add last_modified column if it doesn't exist
+    add_column_if_missing(tx, "client_instances", "last_modified", "TEXT")?;
+
+    // Add config file status columns to client_instances
+    add_column_if_missing(
+        tx,
+        "client_instances",
+        "status",
+        "TEXT NOT NULL DEFAULT 'unknown'",
+    )?;
+    add_column_if_missing(tx, "client_instances", "status_message", "TEXT")?;
+
+    // Add command resolution settings to client_instances
+    add_column_if_missing(
+        tx,
+        "client_instances",
+        "resolve_commands",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(
+        tx,
+        "client_instances",
+        "inject_path",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+
+    // Add parent_id column to servers if it doesn't exist
+    add_column_if_missing(
+        tx,
+        "servers",
+        "parent_id",
+        "TEXT REFERENCES servers(id) ON DELETE SET NULL",
+    )?;
+
+    // Add remote transport columns to servers
+    add_column_if_missing(
+        tx,
+        "servers",
+        "transport_type",
+        "TEXT NOT NULL DEFAULT 'stdio'",
+    )?;
+    add_column_if_missing(tx, "servers", "url", "TEXT")?;
+    add_column_if_missing(tx, "servers", "headers", "TEXT")?;
+
+    // Add per-instance tool filters
+    add_column_if_missing(tx, "instance_servers", "tool_allow", "TEXT")?;
+    add_column_if_missing(tx, "instance_servers", "tool_deny", "TEXT")?;
+
+    Ok(())
+}
+
+/// Version 2: foreign keys are enforced from now on. Before, deletes only
+/// cascaded with the bundled SQLite, so remove what deleted servers and
+/// instances may have left behind.
+fn remove_orphans(tx: &Transaction) -> SqlResult<()> {
+    tx.execute_batch(
+        "
+        -- Left by deleted instances
+        DELETE FROM instance_servers
+            WHERE instance_id NOT IN (SELECT id FROM client_instances)
+            OR server_id NOT IN (SELECT id FROM servers);
+        DELETE FROM managed_keys WHERE instance_id NOT IN (SELECT id FROM client_instances);
+        DELETE FROM sync_snapshots WHERE instance_id NOT IN (SELECT id FROM client_instances);
+        DELETE FROM backups WHERE instance_id NOT IN (SELECT id FROM client_instances);
+
+        -- Left by deleted servers
+        DELETE FROM capability_probes WHERE server_id NOT IN (SELECT id FROM servers);
+        DELETE FROM server_tools WHERE server_id NOT IN (SELECT id FROM servers);
+        DELETE FROM server_resources WHERE server_id NOT IN (SELECT id FROM servers);
+        DELETE FROM server_prompts WHERE server_id NOT IN (SELECT id FROM servers);
+        DELETE FROM health_checks WHERE server_id NOT IN (SELECT id FROM servers);
+        UPDATE servers SET parent_id = NULL
+            WHERE parent_id IS NOT NULL AND parent_id NOT IN (SELECT id FROM servers);
+        ",
+    )
+}
+
+/// Version 3: whether a server's args replace or extend its parent's
+fn add_args_mode(tx: &Transaction) -> SqlResult<()> {
+    tx.execute_batch("ALTER TABLE servers ADD COLUMN args_mode TEXT NOT NULL DEFAULT 'replace';")
+}
+
+/// Version 4: what an instance changes about a server it shares with other
+/// instances
+fn add_server_overrides(tx: &Transaction) -> SqlResult<()> {
+    tx.execute_batch(
+        "
+        ALTER TABLE instance_servers ADD COLUMN args_override TEXT;
+        ALTER TABLE instance_servers ADD COLUMN env_overlay TEXT;
+        ALTER TABLE instance_servers ADD COLUMN alias TEXT;
+        ",
+    )
+}
+
+/// Version 5: instances synced before managed keys were tracked get a key
+/// for every registry server, as those syncs wrote an entry named after
+/// each. From now on an instance without keys had nothing written by MCP Hub.
+fn seed_managed_keys(tx: &Transaction) -> SqlResult<()> {
+    let servers: Vec<(String, String)> = tx
+        .prepare("SELECT id, name FROM servers")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<SqlResult<_>>()?;
+    let instances: Vec<String> = tx
+        .prepare(
+            "SELECT id FROM client_instances WHERE last_synced IS NOT NULL
+             AND id NOT IN (SELECT instance_id FROM managed_keys)",
+        )?
+        .query_map([], |row| row.get(0))?
+        .collect::<SqlResult<_>>()?;
+
+    for instance_id in &instances {
+        for (server_id, name) in &servers {
+            tx.execute(
+                "INSERT OR IGNORE INTO managed_keys (instance_id, entry_key, server_id)
+                 VALUES (?1, ?2, ?3)",
+                params![instance_id, sanitize_server_name(name), server_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Add a column to a table created by an unversioned schema that predates
+/// it
+fn add_column_if_missing(
+    tx: &Transaction,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> SqlResult<()> {
+    let has_column: bool = {
+        let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == column)
+    };
+
+    if !has_column {
+        tx.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::models::{AppSettings, InstanceStatus};
+
+    /// Databases as the first release left them, and as left at every
+    /// schema version since
+    const FIXTURES: &[&str] = &[
+        include_str!("fixtures/00-initial.sql"),
+        include_str!("fixtures/01-unversioned-schema.sql"),
+        include_str!("fixtures/02-remove-orphans.sql"),
+        include_str!("fixtures/03-add-args-mode.sql"),
+        include_str!("fixtures/04-add-server-overrides.sql"),
+    ];
+
+    fn temp_db_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("mcp-hub-migrations-{}", uuid::Uuid::new_v4()))
+            .join("mcp-hub.db")
+    }
+
+    fn user_version(path: &Path) -> u32 {
+        Connection::open(path)
+            .unwrap()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_migrates_every_historical_schema() {
+        for fixture in FIXTURES {
+            let name = fixture.lines().next().unwrap();
+            let path = temp_db_path();
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            Connection::open(&path)
+                .unwrap()
+                .execute_batch(fixture)
+                .unwrap();
+            let version = user_version(&path);
+
+            let db = Database::new(path.clone())
+                .unwrap_or_else(|e| panic!("{} schema failed to migrate: {}", name, e));
+            assert_eq!(user_version(&path), SCHEMA_VERSION, "{}", name);
+            assert!(backup_path(&path, version).exists(), "{}", name);
+
+            let server = db.get_server("s1").unwrap().expect(name);
+            assert_eq!(
+                server.args,
+                ["-y", "@modelcontextprotocol/server-filesystem"]
+            );
+            assert_eq!(server.env["LOG_LEVEL"], "info");
+            let instance = db.get_instance("i1").unwrap().expect(name);
+            assert_eq!(instance.status, InstanceStatus::Unknown);
+            assert_eq!(db.get_enabled_servers_for_instance("i1").unwrap(), ["s1"]);
+            assert_eq!(db.get_backups_for_instance("i1").unwrap().len(), 1);
+            let settings = db.get_setting("app_settings").unwrap().expect(name);
+            serde_json::from_str::<AppSettings>(&settings).unwrap();
+
+            // Migrated data takes new writes
+            let mut server = server;
+            server.url = Some("https://example.com/mcp".to_string());
+            db.update_server(&server).unwrap();
+            db.set_tool_filter("i1", "s1", &Default::default()).unwrap();
+
+            drop(db);
+            std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_removes_orphans() {
+        let path = temp_db_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        // Version 1, the last one before foreign keys were enforced
+        conn.execute_batch(FIXTURES[1]).unwrap();
+        conn.execute_batch(
+            "
+            PRAGMA foreign_keys = OFF;
+            INSERT INTO instance_servers (instance_id, server_id) VALUES ('i1', 'gone');
+            INSERT INTO health_checks (server_id, status, checked_at)
+                VALUES ('gone', 'healthy', '2025-01-01T00:00:00+00:00');
+            UPDATE servers SET parent_id = 'gone' WHERE id = 's2';
+            ",
+        )
+        .unwrap();
+        drop(conn);
+
+        let db = Database::new(path.clone()).unwrap();
+        assert!(db.check_integrity(false).unwrap().orphans.is_empty());
+        assert_eq!(db.get_enabled_servers_for_instance("i1").unwrap(), ["s1"]);
+        assert_eq!(db.get_server("s2").unwrap().unwrap().parent_id, None);
+
+        drop(db);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_seeds_managed_keys_of_synced_instances() {
+        let path = temp_db_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(FIXTURES[0]).unwrap();
+        conn.execute_batch(
+            "
+            UPDATE client_instances SET last_synced = '2025-01-02T00:00:00+00:00';
+            INSERT INTO client_instances (id, name, client_type, config_path, created_at)
+                VALUES ('i2', 'Cursor', 'cursor', '/tmp/mcp.json', '2025-01-01T00:00:00+00:00');
+            ",
+        )
+        .unwrap();
+        drop(conn);
+
+        let db = Database::new(path.clone()).unwrap();
+        assert_eq!(db.get_managed_keys("i1").unwrap(), ["filesystem"]);
+        // Never synced, so nothing in its file is ours
+        assert!(db.get_managed_keys("i2").unwrap().is_empty());
+
+        drop(db);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_new_and_current_databases() {
+        let path = temp_db_path();
+        Database::new(path.clone()).unwrap();
+        assert_eq!(user_version(&path), SCHEMA_VERSION);
+        // Nothing to copy for a new database, nor once it is current
+        Database::new(path.clone()).unwrap();
+        assert!(!backup_path(&path, 0).exists());
+
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(Database::new(path.clone()).is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,132 +1,54 @@
+mod migrations;
+
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Result as SqlResult};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+use crate::error::{HubError, HubResult};
 use crate::models::{
-    ClientInstance, ClientType, ConfigBackup, McpServer, ServerSource, SourceType,
+    CapabilityInventory, CapabilityKind, CapabilityMatch, ClientInstance, ClientType, ConfigBackup,
+    HealthCheck, HealthStatus, InstanceStatus, IntegrityReport, McpServer, OrphanRepair,
+    OrphanedRows, ServerOverride, ServerPrompt, ServerResource, ServerSource, ServerTool,
+    SourceType, SyncSnapshot, ToolFilter,
 };
+use crate::services::config::sanitize_server_name;
+
+/// Table, column and parent table of a foreign key, and how to repair rows
+/// violating it
+type OrphanKey = (String, String, String, OrphanRepair);
 
 pub struct Database {
     conn: Mutex<Connection>,
 }
 
 impl Database {
-    pub fn new(path: PathBuf) -> SqlResult<Self> {
+    pub fn new(path: PathBuf) -> HubResult<Self> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent).ok();
         }
 
-        let conn = Connection::open(path)?;
-        let db = Self {
+        let mut conn = Connection::open(&path)?;
+        migrations::migrate(&mut conn, Some(&path))?;
+        // The bundled SQLite enforces foreign keys by default but a system
+        // one doesn't, and without them the cascades in the schema never run
+        conn.pragma_update(None, "foreign_keys", true)?;
+        Ok(Self {
             conn: Mutex::new(conn),
-        };
-        db.init_schema()?;
-        Ok(db)
-    }
-
-    fn init_schema(&self) -> SqlResult<()> {
-        let conn = self.conn.lock().unwrap();
-
-        conn.execute_batch(
-            "
-            -- Central server registry
-            CREATE TABLE IF NOT EXISTS servers (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT,
-                command TEXT NOT NULL,
-                args TEXT NOT NULL,
-                env TEXT NOT NULL,
-                tags TEXT,
-                source_type TEXT,
-                source_url TEXT,
-                parent_id TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (parent_id) REFERENCES servers(id) ON DELETE SET NULL
-            );
-
-            -- Client instances
-            CREATE TABLE IF NOT EXISTS client_instances (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                client_type TEXT NOT NULL,
-                config_path TEXT NOT NULL,
-                is_default INTEGER DEFAULT 0,
-                last_synced TEXT,
-                last_modified TEXT,
-                created_at TEXT NOT NULL
-            );
-
-            -- Server-to-instance mapping
-            CREATE TABLE IF NOT EXISTS instance_servers (
-                instance_id TEXT NOT NULL,
-                server_id TEXT NOT NULL,
-                enabled INTEGER DEFAULT 1,
-                PRIMARY KEY (instance_id, server_id),
-                FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE,
-                FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
-            );
-
-            -- Config file backups
-            CREATE TABLE IF NOT EXISTS backups (
-                id TEXT PRIMARY KEY,
-                instance_id TEXT NOT NULL,
-                backup_path TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (instance_id) REFERENCES client_instances(id) ON DELETE CASCADE
-            );
-
-            -- App settings
-            CREATE TABLE IF NOT EXISTS settings (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            );
-            ",
-        )?;
-
-        // Migration: Add last_modified column if it doesn't exist
-        // Check if column exists first
-        let has_last_modified: bool = {
-            let mut stmt = conn.prepare("PRAGMA table_info(client_instances)")?;
-            let columns: Vec<String> = stmt
-                .query_map([], |row| row.get::<_, String>(1))?
-                .filter_map(|r| r.ok())
-                .collect();
-            columns.contains(&"last_modified".to_string())
-        };
-
-        if !has_last_modified {
-            conn.execute("ALTER TABLE client_instances ADD COLUMN last_modified TEXT", [])?;
-        }
-
-        // Migration: Add parent_id column to servers if it doesn't exist
-        let has_parent_id: bool = {
-            let mut stmt = conn.prepare("PRAGMA table_info(servers)")?;
-            let columns: Vec<String> = stmt
-                .query_map([], |row| row.get::<_, String>(1))?
-                .filter_map(|r| r.ok())
-                .collect();
-            columns.contains(&"parent_id".to_string())
-        };
-
-        if !has_parent_id {
-            conn.execute("ALTER TABLE servers ADD COLUMN parent_id TEXT REFERENCES servers(id) ON DELETE SET NULL", [])?;
-        }
-
-        Ok(())
+        })
     }
 
     // ==================== Server CRUD ====================
 
-    pub fn create_server(&self, server: &McpServer) -> SqlResult<()> {
+    pub fn create_server(&self, server: &McpServer) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
         let args_json = serde_json::to_string(&server.args).unwrap_or_default();
         let env_json = serde_json::to_string(&server.env).unwrap_or_default();
         let tags_json = serde_json::to_string(&server.tags).unwrap_or_default();
+        let headers_json = serde_json::to_string(&server.headers).unwrap_or_default();
         let source_type = server
             .source
             .as_ref()
@@ -139,8 +61,8 @@ impl Database {
         let source_url = server.source.as_ref().and_then(|s| s.url.clone());
 
         conn.execute(
-            "INSERT INTO servers (id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            "INSERT INTO servers (id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at, transport_type, url, headers, args_mode)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             params![
                 server.id,
                 server.name,
@@ -154,40 +76,44 @@ impl Database {
                 server.parent_id,
                 server.created_at.to_rfc3339(),
                 server.updated_at.to_rfc3339(),
+                server.transport.as_str(),
+                server.url,
+                headers_json,
+                server.args_mode.as_str(),
             ],
         )?;
 
         Ok(())
     }
 
-    pub fn get_server(&self, id: &str) -> SqlResult<Option<McpServer>> {
+    pub fn get_server(&self, id: &str) -> HubResult<Option<McpServer>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare(
-            "SELECT id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at
+            "SELECT id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at,
+                    transport_type, url, headers, args_mode
              FROM servers WHERE id = ?1",
         )?;
 
-        let result = stmt.query_row(params![id], |row| {
-            Ok(Self::row_to_server(row)?)
-        });
+        let result = stmt.query_row(params![id], Self::row_to_server);
 
         match result {
             Ok(server) => Ok(Some(server)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub fn get_all_servers(&self) -> SqlResult<Vec<McpServer>> {
+    pub fn get_all_servers(&self) -> HubResult<Vec<McpServer>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare(
-            "SELECT id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at
+            "SELECT id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at,
+                    transport_type, url, headers, args_mode
              FROM servers ORDER BY name",
         )?;
 
-        let rows = stmt.query_map([], |row| Self::row_to_server(row))?;
+        let rows = stmt.query_map([], Self::row_to_server)?;
 
         let mut servers = Vec::new();
         for row in rows {
@@ -197,12 +123,13 @@ impl Database {
         Ok(servers)
     }
 
-    pub fn update_server(&self, server: &McpServer) -> SqlResult<()> {
+    pub fn update_server(&self, server: &McpServer) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
         let args_json = serde_json::to_string(&server.args).unwrap_or_default();
         let env_json = serde_json::to_string(&server.env).unwrap_or_default();
         let tags_json = serde_json::to_string(&server.tags).unwrap_or_default();
+        let headers_json = serde_json::to_string(&server.headers).unwrap_or_default();
         let source_type = server
             .source
             .as_ref()
@@ -214,9 +141,10 @@ impl Database {
             .unwrap_or("manual");
         let source_url = server.source.as_ref().and_then(|s| s.url.clone());
 
-        conn.execute(
+        let updated = conn.execute(
             "UPDATE servers SET name = ?2, description = ?3, command = ?4, args = ?5, env = ?6,
-             tags = ?7, source_type = ?8, source_url = ?9, parent_id = ?10, updated_at = ?11 WHERE id = ?1",
+             tags = ?7, source_type = ?8, source_url = ?9, parent_id = ?10, updated_at = ?11,
+             transport_type = ?12, url = ?13, headers = ?14, args_mode = ?15 WHERE id = ?1",
             params![
                 server.id,
                 server.name,
@@ -229,13 +157,20 @@ impl Database {
                 source_url,
                 server.parent_id,
                 server.updated_at.to_rfc3339(),
+                server.transport.as_str(),
+                server.url,
+                headers_json,
+                server.args_mode.as_str(),
             ],
         )?;
+        if updated == 0 {
+            return Err(HubError::not_found(format!("Server {}", server.id)));
+        }
 
         Ok(())
     }
 
-    pub fn delete_server(&self, id: &str) -> SqlResult<()> {
+    pub fn delete_server(&self, id: &str) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute("DELETE FROM servers WHERE id = ?1", params![id])?;
         Ok(())
@@ -250,6 +185,9 @@ impl Database {
         let parent_id: Option<String> = row.get(9)?;
         let created_at_str: String = row.get(10)?;
         let updated_at_str: String = row.get(11)?;
+        let transport_str: Option<String> = row.get(12)?;
+        let headers_str: Option<String> = row.get(14)?;
+        let args_mode_str: String = row.get(15)?;
 
         Ok(McpServer {
             id: row.get(0)?,
@@ -257,7 +195,15 @@ impl Database {
             description: row.get(2)?,
             command: row.get(3)?,
             args: serde_json::from_str(&args_str).unwrap_or_default(),
+            args_mode: args_mode_str.parse().unwrap_or_default(),
             env: serde_json::from_str(&env_str).unwrap_or_default(),
+            transport: transport_str
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            url: row.get(13)?,
+            headers: headers_str
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
             tags: tags_str
                 .and_then(|s| serde_json::from_str(&s).ok())
                 .unwrap_or_default(),
@@ -270,6 +216,7 @@ impl Database {
                 url: source_url,
             }),
             parent_id,
+            inherits_from: Vec::new(),
             created_at: DateTime::parse_from_rfc3339(&created_at_str)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now()),
@@ -281,12 +228,13 @@ impl Database {
 
     // ==================== Client Instance CRUD ====================
 
-    pub fn create_instance(&self, instance: &ClientInstance) -> SqlResult<()> {
+    pub fn create_instance(&self, instance: &ClientInstance) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
         conn.execute(
-            "INSERT INTO client_instances (id, name, client_type, config_path, is_default, last_synced, last_modified, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO client_instances (id, name, client_type, config_path, is_default, last_synced, last_modified, created_at,
+                                           resolve_commands, inject_path)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 instance.id,
                 instance.name,
@@ -296,17 +244,20 @@ impl Database {
                 instance.last_synced.map(|dt| dt.to_rfc3339()),
                 instance.last_modified.map(|dt| dt.to_rfc3339()),
                 instance.created_at.to_rfc3339(),
+                instance.resolve_commands as i32,
+                instance.inject_path as i32,
             ],
         )?;
 
         Ok(())
     }
 
-    pub fn get_instance(&self, id: &str) -> SqlResult<Option<ClientInstance>> {
+    pub fn get_instance(&self, id: &str) -> HubResult<Option<ClientInstance>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare(
-            "SELECT id, name, client_type, config_path, is_default, last_synced, last_modified, created_at
+            "SELECT id, name, client_type, config_path, is_default, last_synced, last_modified, created_at,
+                    status, status_message, resolve_commands, inject_path
              FROM client_instances WHERE id = ?1",
         )?;
 
@@ -315,15 +266,16 @@ impl Database {
         match result {
             Ok(instance) => Ok(Some(instance)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub fn get_all_instances(&self) -> SqlResult<Vec<ClientInstance>> {
+    pub fn get_all_instances(&self) -> HubResult<Vec<ClientInstance>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare(
-            "SELECT id, name, client_type, config_path, is_default, last_synced, last_modified, created_at
+            "SELECT id, name, client_type, config_path, is_default, last_synced, last_modified, created_at,
+                    status, status_message, resolve_commands, inject_path
              FROM client_instances ORDER BY name",
         )?;
 
@@ -341,18 +293,21 @@ impl Database {
         let mut instances_with_servers = Vec::new();
         for mut instance in instances {
             instance.enabled_servers = self.get_enabled_servers_for_instance(&instance.id)?;
+            instance.tool_filters = self.get_tool_filters(&instance.id)?;
+            instance.server_overrides = self.get_server_overrides(&instance.id)?;
             instances_with_servers.push(instance);
         }
 
         Ok(instances_with_servers)
     }
 
-    pub fn update_instance(&self, instance: &ClientInstance) -> SqlResult<()> {
+    pub fn update_instance(&self, instance: &ClientInstance) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
-        conn.execute(
+        let updated = conn.execute(
             "UPDATE client_instances SET name = ?2, client_type = ?3, config_path = ?4,
-             is_default = ?5, last_synced = ?6, last_modified = ?7 WHERE id = ?1",
+             is_default = ?5, last_synced = ?6, last_modified = ?7,
+             resolve_commands = ?8, inject_path = ?9 WHERE id = ?1",
             params![
                 instance.id,
                 instance.name,
@@ -361,13 +316,18 @@ impl Database {
                 instance.is_default as i32,
                 instance.last_synced.map(|dt| dt.to_rfc3339()),
                 instance.last_modified.map(|dt| dt.to_rfc3339()),
+                instance.resolve_commands as i32,
+                instance.inject_path as i32,
             ],
         )?;
+        if updated == 0 {
+            return Err(HubError::not_found(format!("Instance {}", instance.id)));
+        }
 
         Ok(())
     }
 
-    pub fn delete_instance(&self, id: &str) -> SqlResult<()> {
+    pub fn delete_instance(&self, id: &str) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute("DELETE FROM client_instances WHERE id = ?1", params![id])?;
         Ok(())
@@ -379,13 +339,18 @@ impl Database {
         let last_synced_str: Option<String> = row.get(5)?;
         let last_modified_str: Option<String> = row.get(6)?;
         let created_at_str: String = row.get(7)?;
+        let status_str: String = row.get(8)?;
 
         Ok(ClientInstance {
             id: row.get(0)?,
             name: row.get(1)?,
-            client_type: ClientType::from_str(&client_type_str).unwrap_or(ClientType::Custom),
+            client_type: client_type_str.parse().unwrap_or(ClientType::Custom),
             config_path: row.get(3)?,
             enabled_servers: Vec::new(), // Loaded separately
+            tool_filters: HashMap::new(),
+            server_overrides: HashMap::new(),
+            resolve_commands: row.get::<_, i32>(10)? != 0,
+            inject_path: row.get::<_, i32>(11)? != 0,
             is_default: is_default != 0,
             last_synced: last_synced_str.and_then(|s| {
                 DateTime::parse_from_rfc3339(&s)
@@ -397,12 +362,29 @@ impl Database {
                     .map(|dt| dt.with_timezone(&Utc))
                     .ok()
             }),
+            status: status_str.parse().unwrap_or_default(),
+            status_message: row.get(9)?,
             created_at: DateTime::parse_from_rfc3339(&created_at_str)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now()),
         })
     }
 
+    /// Record the state of an instance's config file
+    pub fn set_instance_status(
+        &self,
+        id: &str,
+        status: InstanceStatus,
+        message: Option<&str>,
+    ) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE client_instances SET status = ?2, status_message = ?3 WHERE id = ?1",
+            params![id, status.as_str(), message],
+        )?;
+        Ok(())
+    }
+
     // ==================== Instance-Server Mapping ====================
 
     pub fn set_server_enabled_for_instance(
@@ -410,7 +392,7 @@ impl Database {
         instance_id: &str,
         server_id: &str,
         enabled: bool,
-    ) -> SqlResult<()> {
+    ) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
         conn.execute(
@@ -429,7 +411,22 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_enabled_servers_for_instance(&self, instance_id: &str) -> SqlResult<Vec<String>> {
+    /// Servers enabled on at least one instance
+    pub fn get_servers_in_use(&self) -> HubResult<Vec<McpServer>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, description, command, args, env, tags, source_type, source_url, parent_id, created_at, updated_at,
+                    transport_type, url, headers, args_mode
+             FROM servers
+             WHERE id IN (SELECT server_id FROM instance_servers WHERE enabled = 1)
+             ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_server)?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    pub fn get_enabled_servers_for_instance(&self, instance_id: &str) -> HubResult<Vec<String>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare(
@@ -446,8 +443,177 @@ impl Database {
         Ok(server_ids)
     }
 
+    /// Tool filters of an instance by server ID, leaving out servers without one
+    pub fn get_tool_filters(&self, instance_id: &str) -> HubResult<HashMap<String, ToolFilter>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT server_id, tool_allow, tool_deny FROM instance_servers
+             WHERE instance_id = ?1 AND (tool_allow IS NOT NULL OR tool_deny IS NOT NULL)",
+        )?;
+        let rows = stmt.query_map(params![instance_id], |row| {
+            let allow: Option<String> = row.get(1)?;
+            let deny: Option<String> = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                ToolFilter {
+                    allow: allow
+                        .and_then(|s| serde_json::from_str(&s).ok())
+                        .unwrap_or_default(),
+                    deny: deny
+                        .and_then(|s| serde_json::from_str(&s).ok())
+                        .unwrap_or_default(),
+                },
+            ))
+        })?;
+
+        let mut filters = HashMap::new();
+        for row in rows {
+            let (server_id, filter) = row?;
+            if !filter.is_empty() {
+                filters.insert(server_id, filter);
+            }
+        }
+        Ok(filters)
+    }
+
+    /// Set which tools of a server an instance may use; an empty filter allows all
+    pub fn set_tool_filter(
+        &self,
+        instance_id: &str,
+        server_id: &str,
+        filter: &ToolFilter,
+    ) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let to_json = |patterns: &Vec<String>| {
+            (!patterns.is_empty()).then(|| serde_json::to_string(patterns).unwrap_or_default())
+        };
+        // A filter on its own does not enable the server
+        conn.execute(
+            "INSERT INTO instance_servers (instance_id, server_id, enabled, tool_allow, tool_deny)
+             VALUES (?1, ?2, 0, ?3, ?4)
+             ON CONFLICT(instance_id, server_id) DO UPDATE SET tool_allow = ?3, tool_deny = ?4",
+            params![
+                instance_id,
+                server_id,
+                to_json(&filter.allow),
+                to_json(&filter.deny)
+            ],
+        )?;
+
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "UPDATE client_instances SET last_modified = ?1 WHERE id = ?2",
+            params![now, instance_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Server overrides of an instance by server ID, leaving out servers
+    /// without one
+    pub fn get_server_overrides(
+        &self,
+        instance_id: &str,
+    ) -> HubResult<HashMap<String, ServerOverride>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT server_id, args_override, env_overlay, alias FROM instance_servers
+             WHERE instance_id = ?1
+             AND (args_override IS NOT NULL OR env_overlay IS NOT NULL OR alias IS NOT NULL)",
+        )?;
+        let rows = stmt.query_map(params![instance_id], |row| {
+            let args: Option<String> = row.get(1)?;
+            let env: Option<String> = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                ServerOverride {
+                    args: args.and_then(|s| serde_json::from_str(&s).ok()),
+                    env: env
+                        .and_then(|s| serde_json::from_str(&s).ok())
+                        .unwrap_or_default(),
+                    alias: row.get(3)?,
+                },
+            ))
+        })?;
+
+        let mut overrides = HashMap::new();
+        for row in rows {
+            let (server_id, server_override) = row?;
+            if !server_override.is_empty() {
+                overrides.insert(server_id, server_override);
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Set how an instance runs a server differently; an empty override
+    /// runs it as configured. An alias must give a config entry key that no
+    /// other server enabled on the instance is written under.
+    pub fn set_server_override(
+        &self,
+        instance_id: &str,
+        server_id: &str,
+        server_override: &ServerOverride,
+    ) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+
+        if let Some(alias) = &server_override.alias {
+            let key = sanitize_server_name(alias);
+            if key.is_empty() {
+                return Err(HubError::Validation(format!(
+                    "Alias {:?} is not a usable config key",
+                    alias
+                )));
+            }
+            let mut stmt = conn.prepare(
+                "SELECT s.name, i.alias FROM instance_servers i
+                 JOIN servers s ON s.id = i.server_id
+                 WHERE i.instance_id = ?1 AND i.server_id != ?2 AND i.enabled = 1",
+            )?;
+            let others = stmt.query_map(params![instance_id, server_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })?;
+            for other in others {
+                let (name, other_alias) = other?;
+                if sanitize_server_name(other_alias.as_deref().unwrap_or(&name)) == key {
+                    return Err(HubError::Conflict(format!(
+                        "Alias {} is already used by {} on this instance",
+                        alias, name
+                    )));
+                }
+            }
+        }
+
+        let args = server_override
+            .args
+            .as_ref()
+            .map(|args| serde_json::to_string(args).unwrap_or_default());
+        let env = (!server_override.env.is_empty())
+            .then(|| serde_json::to_string(&server_override.env).unwrap_or_default());
+        // An override on its own does not enable the server
+        conn.execute(
+            "INSERT INTO instance_servers
+                 (instance_id, server_id, enabled, args_override, env_overlay, alias)
+             VALUES (?1, ?2, 0, ?3, ?4, ?5)
+             ON CONFLICT(instance_id, server_id)
+             DO UPDATE SET args_override = ?3, env_overlay = ?4, alias = ?5",
+            params![instance_id, server_id, args, env, server_override.alias],
+        )?;
+
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "UPDATE client_instances SET last_modified = ?1 WHERE id = ?2",
+            params![now, instance_id],
+        )?;
+
+        Ok(())
+    }
+
     #[allow(dead_code)]
-    pub fn remove_server_from_instance(&self, instance_id: &str, server_id: &str) -> SqlResult<()> {
+    pub fn remove_server_from_instance(&self, instance_id: &str, server_id: &str) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
             "DELETE FROM instance_servers WHERE instance_id = ?1 AND server_id = ?2",
@@ -456,9 +622,417 @@ impl Database {
         Ok(())
     }
 
+    // ==================== Managed Keys ====================
+
+    /// Config entry keys MCP Hub wrote to an instance on its last sync
+    pub fn get_managed_keys(&self, instance_id: &str) -> HubResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT entry_key FROM managed_keys WHERE instance_id = ?1 ORDER BY entry_key",
+        )?;
+
+        let rows = stmt.query_map(params![instance_id], |row| row.get(0))?;
+
+        let mut keys = Vec::new();
+        for row in rows {
+            keys.push(row?);
+        }
+
+        Ok(keys)
+    }
+
+    /// Replace the managed keys of an instance with `keys` (entry key -> server ID)
+    pub fn set_managed_keys(
+        &self,
+        instance_id: &str,
+        keys: &HashMap<String, String>,
+    ) -> HubResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM managed_keys WHERE instance_id = ?1",
+            params![instance_id],
+        )?;
+        for (key, server_id) in keys {
+            tx.execute(
+                "INSERT INTO managed_keys (instance_id, entry_key, server_id) VALUES (?1, ?2, ?3)",
+                params![instance_id, key, server_id],
+            )?;
+        }
+
+        Ok(tx.commit()?)
+    }
+
+    /// Managed entry keys of an instance mapped to the server they were written for
+    pub fn get_managed_key_owners(&self, instance_id: &str) -> HubResult<HashMap<String, String>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT entry_key, server_id FROM managed_keys
+             WHERE instance_id = ?1 AND server_id IS NOT NULL",
+        )?;
+
+        let rows = stmt.query_map(params![instance_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut owners = HashMap::new();
+        for row in rows {
+            let (key, server_id) = row?;
+            owners.insert(key, server_id);
+        }
+
+        Ok(owners)
+    }
+
+    /// Mark a single config entry key as managed by MCP Hub
+    pub fn add_managed_key(&self, instance_id: &str, key: &str, server_id: &str) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO managed_keys (instance_id, entry_key, server_id) VALUES (?1, ?2, ?3)
+             ON CONFLICT(instance_id, entry_key) DO UPDATE SET server_id = ?3",
+            params![instance_id, key, server_id],
+        )?;
+
+        Ok(())
+    }
+
+    // ==================== Sync Snapshots ====================
+
+    pub fn get_sync_snapshot(&self, instance_id: &str) -> HubResult<Option<SyncSnapshot>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT instance_id, content, content_hash, file_mtime, synced_at
+             FROM sync_snapshots WHERE instance_id = ?1",
+        )?;
+
+        let mut rows = stmt.query(params![instance_id])?;
+
+        if let Some(row) = rows.next()? {
+            let file_mtime_str: Option<String> = row.get(3)?;
+            let synced_at_str: String = row.get(4)?;
+            Ok(Some(SyncSnapshot {
+                instance_id: row.get(0)?,
+                content: row.get(1)?,
+                content_hash: row.get(2)?,
+                file_mtime: file_mtime_str.and_then(|s| {
+                    DateTime::parse_from_rfc3339(&s)
+                        .ok()
+                        .map(|dt| dt.with_timezone(&Utc))
+                }),
+                synced_at: DateTime::parse_from_rfc3339(&synced_at_str)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_sync_snapshot(&self, snapshot: &SyncSnapshot) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO sync_snapshots (instance_id, content, content_hash, file_mtime, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(instance_id) DO UPDATE SET
+                content = ?2, content_hash = ?3, file_mtime = ?4, synced_at = ?5",
+            params![
+                snapshot.instance_id,
+                snapshot.content,
+                snapshot.content_hash,
+                snapshot.file_mtime.map(|dt| dt.to_rfc3339()),
+                snapshot.synced_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // ==================== Capabilities ====================
+
+    /// The tools, resources and prompts recorded by a server's latest probe
+    pub fn get_capability_inventory(
+        &self,
+        server_id: &str,
+    ) -> HubResult<Option<CapabilityInventory>> {
+        let conn = self.conn.lock().unwrap();
+
+        let probe: Option<(String, String)> = conn
+            .query_row(
+                "SELECT probed_at, changes FROM capability_probes WHERE server_id = ?1",
+                params![server_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((probed_at_str, changes_str)) = probe else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT name, description, input_schema FROM server_tools
+             WHERE server_id = ?1 ORDER BY name",
+        )?;
+        let tools = stmt
+            .query_map(params![server_id], |row| {
+                let schema_str: String = row.get(2)?;
+                Ok(ServerTool {
+                    name: row.get(0)?,
+                    description: row.get(1)?,
+                    input_schema: serde_json::from_str(&schema_str).unwrap_or_default(),
+                })
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT uri, name, description, mime_type FROM server_resources
+             WHERE server_id = ?1 ORDER BY uri",
+        )?;
+        let resources = stmt
+            .query_map(params![server_id], |row| {
+                Ok(ServerResource {
+                    uri: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    mime_type: row.get(3)?,
+                })
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT name, description, arguments FROM server_prompts
+             WHERE server_id = ?1 ORDER BY name",
+        )?;
+        let prompts = stmt
+            .query_map(params![server_id], |row| {
+                let arguments_str: String = row.get(2)?;
+                Ok(ServerPrompt {
+                    name: row.get(0)?,
+                    description: row.get(1)?,
+                    arguments: serde_json::from_str(&arguments_str).unwrap_or_default(),
+                })
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(Some(CapabilityInventory {
+            server_id: server_id.to_string(),
+            probed_at: DateTime::parse_from_rfc3339(&probed_at_str)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            tools,
+            resources,
+            prompts,
+            changes: serde_json::from_str(&changes_str).unwrap_or_default(),
+        }))
+    }
+
+    /// Replace the recorded capabilities of a server with a new probe
+    pub fn set_capability_inventory(&self, inventory: &CapabilityInventory) -> HubResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let server_id = &inventory.server_id;
+        let probed_at = inventory.probed_at.to_rfc3339();
+
+        for table in ["server_tools", "server_resources", "server_prompts"] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE server_id = ?1", table),
+                params![server_id],
+            )?;
+        }
+
+        for tool in &inventory.tools {
+            tx.execute(
+                "INSERT OR REPLACE INTO server_tools
+                 (server_id, name, description, input_schema, probed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    server_id,
+                    tool.name,
+                    tool.description,
+                    tool.input_schema.to_string(),
+                    probed_at,
+                ],
+            )?;
+        }
+        for resource in &inventory.resources {
+            tx.execute(
+                "INSERT OR REPLACE INTO server_resources
+                 (server_id, uri, name, description, mime_type, probed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    server_id,
+                    resource.uri,
+                    resource.name,
+                    resource.description,
+                    resource.mime_type,
+                    probed_at,
+                ],
+            )?;
+        }
+        for prompt in &inventory.prompts {
+            tx.execute(
+                "INSERT OR REPLACE INTO server_prompts
+                 (server_id, name, description, arguments, probed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    server_id,
+                    prompt.name,
+                    prompt.description,
+                    prompt.arguments.to_string(),
+                    probed_at,
+                ],
+            )?;
+        }
+
+        tx.execute(
+            "INSERT INTO capability_probes (server_id, probed_at, changes) VALUES (?1, ?2, ?3)
+             ON CONFLICT(server_id) DO UPDATE SET probed_at = ?2, changes = ?3",
+            params![
+                server_id,
+                probed_at,
+                serde_json::to_string(&inventory.changes).unwrap_or_default(),
+            ],
+        )?;
+
+        Ok(tx.commit()?)
+    }
+
+    /// Names of the tools each probed server offers, by server ID
+    pub fn get_tool_names(&self) -> HubResult<HashMap<String, Vec<String>>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare("SELECT server_id, name FROM server_tools ORDER BY name")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut names: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (server_id, name) = row?;
+            names.entry(server_id).or_default().push(name);
+        }
+        Ok(names)
+    }
+
+    /// Find tools, resources and prompts whose name or description contains
+    /// `query`, across every server in the registry
+    pub fn search_capabilities(&self, query: &str) -> HubResult<Vec<CapabilityMatch>> {
+        let conn = self.conn.lock().unwrap();
+
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+
+        let mut stmt = conn.prepare(
+            "SELECT c.server_id, s.name, 'tool', c.name, c.description
+             FROM server_tools c JOIN servers s ON s.id = c.server_id
+             WHERE c.name LIKE ?1 ESCAPE '\\' OR c.description LIKE ?1 ESCAPE '\\'
+             UNION ALL
+             SELECT c.server_id, s.name, 'resource', c.uri, c.description
+             FROM server_resources c JOIN servers s ON s.id = c.server_id
+             WHERE c.uri LIKE ?1 ESCAPE '\\' OR c.name LIKE ?1 ESCAPE '\\'
+                OR c.description LIKE ?1 ESCAPE '\\'
+             UNION ALL
+             SELECT c.server_id, s.name, 'prompt', c.name, c.description
+             FROM server_prompts c JOIN servers s ON s.id = c.server_id
+             WHERE c.name LIKE ?1 ESCAPE '\\' OR c.description LIKE ?1 ESCAPE '\\'
+             ORDER BY 4, 2",
+        )?;
+
+        let rows = stmt.query_map(params![pattern], |row| {
+            let kind: String = row.get(2)?;
+            Ok(CapabilityMatch {
+                server_id: row.get(0)?,
+                server_name: row.get(1)?,
+                kind: match kind.as_str() {
+                    "resource" => CapabilityKind::Resource,
+                    "prompt" => CapabilityKind::Prompt,
+                    _ => CapabilityKind::Tool,
+                },
+                name: row.get(3)?,
+                description: row.get(4)?,
+            })
+        })?;
+
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    // ==================== Health Checks ====================
+
+    /// Store the result of a health check
+    pub fn record_health_check(&self, check: &HealthCheck) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO health_checks (server_id, status, error_message, latency_ms, checked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                check.server_id,
+                check.status.as_str(),
+                check.error_message,
+                check.latency_ms.map(|ms| ms as i64),
+                check.checked_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Stored health checks of a server, newest first
+    pub fn get_health_history(&self, server_id: &str, limit: u32) -> HubResult<Vec<HealthCheck>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT server_id, status, error_message, latency_ms, checked_at FROM health_checks
+             WHERE server_id = ?1 ORDER BY checked_at DESC, id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![server_id, limit], Self::row_to_health_check)?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    /// Every stored health check, oldest first
+    pub fn get_all_health_checks(&self) -> HubResult<Vec<HealthCheck>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT server_id, status, error_message, latency_ms, checked_at FROM health_checks
+             ORDER BY checked_at, id",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_health_check)?;
+        Ok(rows.collect::<SqlResult<_>>()?)
+    }
+
+    /// Delete health checks made before `before`. Returns how many were deleted.
+    pub fn prune_health_checks(&self, before: DateTime<Utc>) -> HubResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM health_checks WHERE checked_at < ?1",
+            params![before.to_rfc3339()],
+        )?;
+        Ok(deleted)
+    }
+
+    fn row_to_health_check(row: &rusqlite::Row) -> SqlResult<HealthCheck> {
+        let status_str: String = row.get(1)?;
+        let latency_ms: Option<i64> = row.get(3)?;
+        let checked_at_str: String = row.get(4)?;
+
+        Ok(HealthCheck {
+            server_id: row.get(0)?,
+            status: status_str.parse().unwrap_or(HealthStatus::Unknown),
+            error_message: row.get(2)?,
+            latency_ms: latency_ms.map(|ms| ms as u64),
+            checked_at: DateTime::parse_from_rfc3339(&checked_at_str)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+        })
+    }
+
     // ==================== Backups ====================
 
-    pub fn create_backup(&self, backup: &ConfigBackup) -> SqlResult<()> {
+    pub fn create_backup(&self, backup: &ConfigBackup) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
         conn.execute(
@@ -474,7 +1048,23 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_backups_for_instance(&self, instance_id: &str) -> SqlResult<Vec<ConfigBackup>> {
+    pub fn get_backup(&self, id: &str) -> HubResult<Option<ConfigBackup>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, instance_id, backup_path, created_at FROM backups WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::row_to_backup(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_backups_for_instance(&self, instance_id: &str) -> HubResult<Vec<ConfigBackup>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare(
@@ -482,17 +1072,7 @@ impl Database {
              WHERE instance_id = ?1 ORDER BY created_at DESC",
         )?;
 
-        let rows = stmt.query_map(params![instance_id], |row| {
-            let created_at_str: String = row.get(3)?;
-            Ok(ConfigBackup {
-                id: row.get(0)?,
-                instance_id: row.get(1)?,
-                backup_path: row.get(2)?,
-                created_at: DateTime::parse_from_rfc3339(&created_at_str)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-            })
-        })?;
+        let rows = stmt.query_map(params![instance_id], Self::row_to_backup)?;
 
         let mut backups = Vec::new();
         for row in rows {
@@ -502,33 +1082,45 @@ impl Database {
         Ok(backups)
     }
 
-    #[allow(dead_code)]
-    pub fn delete_old_backups(&self, instance_id: &str, keep_count: usize) -> SqlResult<()> {
+    pub fn get_all_backups(&self) -> HubResult<Vec<ConfigBackup>> {
         let conn = self.conn.lock().unwrap();
 
-        // Get all backups sorted by date
         let mut stmt = conn.prepare(
-            "SELECT id FROM backups WHERE instance_id = ?1 ORDER BY created_at DESC",
+            "SELECT id, instance_id, backup_path, created_at FROM backups
+             ORDER BY instance_id, created_at DESC",
         )?;
 
-        let backup_ids: Vec<String> = stmt
-            .query_map(params![instance_id], |row| row.get(0))?
-            .filter_map(|r| r.ok())
-            .collect();
+        let rows = stmt.query_map([], Self::row_to_backup)?;
 
-        // Delete old ones
-        if backup_ids.len() > keep_count {
-            for id in backup_ids.into_iter().skip(keep_count) {
-                conn.execute("DELETE FROM backups WHERE id = ?1", params![id])?;
-            }
+        let mut backups = Vec::new();
+        for row in rows {
+            backups.push(row?);
         }
 
+        Ok(backups)
+    }
+
+    pub fn delete_backup(&self, id: &str) -> HubResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM backups WHERE id = ?1", params![id])?;
         Ok(())
     }
 
+    fn row_to_backup(row: &rusqlite::Row) -> SqlResult<ConfigBackup> {
+        let created_at_str: String = row.get(3)?;
+        Ok(ConfigBackup {
+            id: row.get(0)?,
+            instance_id: row.get(1)?,
+            backup_path: row.get(2)?,
+            created_at: DateTime::parse_from_rfc3339(&created_at_str)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+        })
+    }
+
     // ==================== Settings ====================
 
-    pub fn get_setting(&self, key: &str) -> SqlResult<Option<String>> {
+    pub fn get_setting(&self, key: &str) -> HubResult<Option<String>> {
         let conn = self.conn.lock().unwrap();
 
         let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
@@ -536,11 +1128,11 @@ impl Database {
         match stmt.query_row(params![key], |row| row.get(0)) {
             Ok(value) => Ok(Some(value)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub fn set_setting(&self, key: &str, value: &str) -> SqlResult<()> {
+    pub fn set_setting(&self, key: &str, value: &str) -> HubResult<()> {
         let conn = self.conn.lock().unwrap();
 
         conn.execute(
@@ -551,4 +1143,192 @@ impl Database {
 
         Ok(())
     }
+
+    // ==================== Integrity ====================
+
+    /// Check the database file, and look for rows whose foreign key points at
+    /// a row that no longer exists. With `repair` those rows are deleted, or
+    /// their reference cleared where the key is `ON DELETE SET NULL`.
+    pub fn check_integrity(&self, repair: bool) -> HubResult<IntegrityReport> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let corruption: Vec<String> = conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter(|result| !matches!(result.as_deref(), Ok("ok")))
+            .collect::<SqlResult<_>>()?;
+
+        let orphans = Self::orphaned_rows(&conn)?;
+        if repair && !orphans.is_empty() {
+            let tx = conn.transaction()?;
+            for ((table, column, _, action), rowids) in &orphans {
+                let sql = match action {
+                    OrphanRepair::Delete => format!("DELETE FROM {} WHERE rowid = ?1", table),
+                    OrphanRepair::ClearReference => {
+                        format!("UPDATE {} SET {} = NULL WHERE rowid = ?1", table, column)
+                    }
+                };
+                for rowid in rowids {
+                    tx.execute(&sql, params![rowid])?;
+                }
+            }
+            tx.commit()?;
+        }
+
+        Ok(IntegrityReport {
+            checked_at: Utc::now(),
+            corruption,
+            repaired: repair && !orphans.is_empty(),
+            orphans: orphans
+                .into_iter()
+                .map(
+                    |((table, column, parent_table, repair), rowids)| OrphanedRows {
+                        table,
+                        column,
+                        parent_table,
+                        count: rowids.len(),
+                        repair,
+                    },
+                )
+                .collect(),
+        })
+    }
+
+    /// Rows failing `PRAGMA foreign_key_check`, by foreign key
+    fn orphaned_rows(conn: &Connection) -> SqlResult<Vec<(OrphanKey, Vec<i64>)>> {
+        let violations: Vec<(String, i64, String, i64)> = conn
+            .prepare("PRAGMA foreign_key_check")?
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<SqlResult<_>>()?;
+
+        let mut orphans: Vec<(OrphanKey, Vec<i64>)> = Vec::new();
+        for (table, rowid, parent_table, fk_id) in violations {
+            // Column and ON DELETE action of the violated key
+            let (column, on_delete): (String, String) = conn.query_row(
+                &format!(
+                    "SELECT \"from\", on_delete FROM pragma_foreign_key_list('{}') WHERE id = ?1",
+                    table
+                ),
+                params![fk_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            let action = if on_delete.eq_ignore_ascii_case("SET NULL") {
+                OrphanRepair::ClearReference
+            } else {
+                OrphanRepair::Delete
+            };
+
+            let key = (table, column, parent_table, action);
+            match orphans.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, rowids)) => rowids.push(rowid),
+                None => orphans.push((key, vec![rowid])),
+            }
+        }
+        Ok(orphans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_integrity() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-integrity-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("mcp-hub.db");
+        let db = Database::new(path.clone()).unwrap();
+        let parent = McpServer::new("parent".to_string(), "npx".to_string(), vec![]);
+        db.create_server(&parent).unwrap();
+        let mut child = McpServer::new("child".to_string(), "npx".to_string(), vec![]);
+        child.parent_id = Some(parent.id.clone());
+        db.create_server(&child).unwrap();
+
+        // Enforced foreign keys refuse orphans and cascade deletes
+        assert!(db
+            .set_server_enabled_for_instance("missing", &child.id, true)
+            .is_err());
+        db.delete_server(&parent.id).unwrap();
+        assert_eq!(db.get_server(&child.id).unwrap().unwrap().parent_id, None);
+
+        // Orphans written without them, as with a system SQLite before
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.pragma_update(None, "foreign_keys", false).unwrap();
+            conn.execute(
+                "INSERT INTO instance_servers (instance_id, server_id) VALUES ('gone', ?1)",
+                params![child.id],
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE servers SET parent_id = 'gone' WHERE id = ?1",
+                params![child.id],
+            )
+            .unwrap();
+        }
+
+        let report = db.check_integrity(false).unwrap();
+        assert!(report.corruption.is_empty());
+        let mut found: Vec<(&str, OrphanRepair)> = report
+            .orphans
+            .iter()
+            .map(|o| (o.table.as_str(), o.repair))
+            .collect();
+        found.sort_by_key(|(table, _)| *table);
+        assert_eq!(
+            found,
+            [
+                ("instance_servers", OrphanRepair::Delete),
+                ("servers", OrphanRepair::ClearReference)
+            ]
+        );
+
+        assert!(db.check_integrity(true).unwrap().repaired);
+        assert!(db.check_integrity(false).unwrap().orphans.is_empty());
+        assert!(db.get_server(&child.id).unwrap().is_some());
+
+        drop(db);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_set_server_override_alias() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-alias-{}", uuid::Uuid::new_v4()));
+        let db = Database::new(dir.join("mcp-hub.db")).unwrap();
+        let instance =
+            ClientInstance::new("Work".to_string(), ClientType::ClaudeDesktop, String::new());
+        db.create_instance(&instance).unwrap();
+        let github = McpServer::new("GitHub".to_string(), "npx".to_string(), vec![]);
+        let gitlab = McpServer::new("GitLab".to_string(), "npx".to_string(), vec![]);
+        for server in [&github, &gitlab] {
+            db.create_server(server).unwrap();
+            db.set_server_enabled_for_instance(&instance.id, &server.id, true)
+                .unwrap();
+        }
+        let alias = |alias: &str| ServerOverride {
+            alias: Some(alias.to_string()),
+            ..Default::default()
+        };
+
+        let error = db
+            .set_server_override(&instance.id, &gitlab.id, &alias(" "))
+            .unwrap_err();
+        assert_eq!(error.kind(), "validation");
+        let error = db
+            .set_server_override(&instance.id, &gitlab.id, &alias("github"))
+            .unwrap_err();
+        assert_eq!(error.kind(), "conflict");
+
+        db.set_server_override(&instance.id, &github.id, &alias("code"))
+            .unwrap();
+        db.set_server_override(&instance.id, &gitlab.id, &alias("GitHub"))
+            .unwrap();
+        assert!(db
+            .set_server_override(&instance.id, &github.id, &alias("GitHub"))
+            .is_err());
+
+        drop(db);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
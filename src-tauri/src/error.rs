@@ -0,0 +1,110 @@
+//! Errors of the database, config and credential layers and of the Tauri
+//! commands, typed so the frontend can tell them apart.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+pub type HubResult<T> = Result<T, HubError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HubError {
+    /// A server, instance, backup, file or secret that doesn't exist
+    #[error("{0} not found")]
+    NotFound(String),
+    /// A config file or stored value that can't be parsed
+    #[error("{0}")]
+    Parse(String),
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The keyring or the vault failed or refused
+    #[error("{0}")]
+    Keyring(String),
+    /// A change that clashes with what is stored, e.g. a duplicate ID
+    #[error("{0}")]
+    Conflict(String),
+    /// Input rejected before anything was changed
+    #[error("{0}")]
+    Validation(String),
+    #[error(transparent)]
+    Database(rusqlite::Error),
+    /// Errors without a kind of their own, like those of the services that
+    /// still report them as text
+    #[error("{0}")]
+    Other(String),
+}
+
+impl HubError {
+    pub fn not_found(what: impl Into<String>) -> Self {
+        HubError::NotFound(what.into())
+    }
+
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        HubError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// The tag the frontend matches on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HubError::NotFound(_) => "not-found",
+            HubError::Parse(_) => "parse",
+            HubError::Io { .. } => "io",
+            HubError::Keyring(_) => "keyring",
+            HubError::Conflict(_) => "conflict",
+            HubError::Validation(_) => "validation",
+            HubError::Database(_) => "database",
+            HubError::Other(_) => "other",
+        }
+    }
+}
+
+/// Serialized as `{ "kind": "not-found", "message": "Instance not found" }`
+impl Serialize for HubError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("HubError", 2)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
+    }
+}
+
+/// A lock whose holder panicked
+impl<T> From<std::sync::PoisonError<T>> for HubError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        HubError::Other(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for HubError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::ConstraintViolation) => HubError::Conflict(e.to_string()),
+            _ => HubError::Database(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_tagged() {
+        let json = serde_json::to_value(HubError::not_found("Instance")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "not-found", "message": "Instance not found" })
+        );
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (id TEXT PRIMARY KEY); INSERT INTO t VALUES ('a');")
+            .unwrap();
+        let duplicate = conn.execute("INSERT INTO t VALUES ('a')", []).unwrap_err();
+        assert_eq!(HubError::from(duplicate).kind(), "conflict");
+    }
+}
@@ -1,7 +1,9 @@
+pub mod cli;
 mod commands;
-mod db;
-mod models;
-mod services;
+pub mod db;
+pub mod error;
+pub mod models;
+pub mod services;
 
 use commands::AppState;
 use db::Database;
@@ -20,25 +22,49 @@ pub fn run() {
     // Initialize database
     let database = Database::new(db_path).expect("Failed to initialize database");
 
+    // Keep secrets where the settings say; the vault stays locked until its
+    // passphrase is given
+    if let Ok(settings) = services::config::load_settings(&database) {
+        services::credentials::use_backend(settings.credential_backend);
+    }
+
     // Create shared discovery server handle
     let discovery_server = Arc::new(RwLock::new(None));
 
+    // Config file watcher, started once the app handle exists
+    let config_watcher = Arc::new(Mutex::new(None));
+
     // Clone for setup hook
     let discovery_server_setup = discovery_server.clone();
+    let config_watcher_setup = config_watcher.clone();
     let db_for_setup = Database::new(
         services::config::get_database_path().expect("Failed to determine database path"),
     )
     .expect("Failed to initialize database for setup");
+    let db_for_sweeper = Database::new(
+        services::config::get_database_path().expect("Failed to determine database path"),
+    )
+    .expect("Failed to initialize database for backup sweeper");
+    let db_for_watcher = Database::new(
+        services::config::get_database_path().expect("Failed to determine database path"),
+    )
+    .expect("Failed to initialize database for config watcher");
+    let db_for_health_monitor = Database::new(
+        services::config::get_database_path().expect("Failed to determine database path"),
+    )
+    .expect("Failed to initialize database for health monitor");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState {
             db: Mutex::new(database),
             discovery_server,
+            config_watcher,
         })
-        .setup(move |_app| {
+        .setup(move |app| {
             // Initialize discovery services based on saved settings
             let discovery_server = discovery_server_setup.clone();
 
@@ -50,7 +76,10 @@ pub fn run() {
                     .unwrap_or_default();
 
                 // Get servers for discovery
-                let servers = db_for_setup.get_all_servers().unwrap_or_default();
+                let servers = db_for_setup
+                    .get_all_servers()
+                    .map(|servers| services::inheritance::resolve_all(&servers))
+                    .unwrap_or_default();
 
                 // Initialize ~/.mcp directory if enabled
                 if settings.discovery.mcp_directory_enabled {
@@ -84,15 +113,41 @@ pub fn run() {
                 }
             });
 
+            // Watch client config files for changes made outside MCP Hub
+            match services::watcher::ConfigWatcher::start(app.handle().clone(), db_for_watcher) {
+                Ok(watcher) => *config_watcher_setup.lock().unwrap() = Some(watcher),
+                Err(e) => log::error!("{}", e),
+            }
+
+            // Check servers in use periodically and record their health
+            services::health_monitor::start(app.handle().clone(), db_for_health_monitor);
+
+            // Prune expired backups at startup and then hourly
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+                loop {
+                    interval.tick().await;
+                    let result =
+                        services::config::load_settings(&db_for_sweeper).and_then(|settings| {
+                            services::backup::prune_backups(&db_for_sweeper, &settings)
+                        });
+                    if let Err(e) = result {
+                        log::error!("Failed to prune backups: {}", e);
+                    }
+                }
+            });
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Server commands
             commands::get_servers,
             commands::get_server,
+            commands::get_effective_server,
             commands::create_server,
             commands::update_server,
             commands::delete_server,
+            commands::secure_server_env,
             // Instance commands
             commands::get_instances,
             commands::get_instance,
@@ -102,27 +157,53 @@ pub fn run() {
             // Server-Instance mapping
             commands::set_server_enabled,
             commands::get_enabled_servers,
+            commands::get_tool_filters,
+            commands::set_tool_filter,
+            commands::get_server_overrides,
+            commands::set_server_override,
             // Sync commands
+            commands::preview_sync,
             commands::sync_instance,
             commands::sync_all_instances,
+            commands::check_drift,
+            commands::reconcile_instance,
             // Import/Export
             commands::import_from_file,
+            commands::import_from_instance,
+            commands::get_unmanaged_servers,
+            commands::adopt_unmanaged_servers,
             commands::detect_clients,
             // Credentials
             commands::store_credential,
             commands::get_credential,
             commands::delete_credential,
             commands::is_credential_storage_available,
+            commands::get_credential_status,
+            commands::unlock_vault,
+            commands::lock_vault,
             // Backups
             commands::get_backups,
+            commands::get_backup,
+            commands::preview_backup,
             commands::restore_backup,
+            commands::prune_backups,
             // Settings
             commands::get_settings,
             commands::save_settings,
             // Health
             commands::check_server_health,
+            commands::get_health_history,
+            commands::get_health_stats,
+            // Capabilities
+            commands::probe_server_capabilities,
+            commands::probe_all_capabilities,
+            commands::get_server_capabilities,
+            commands::search_capabilities,
+            // Doctor
+            commands::run_doctor,
             // Utility
             commands::get_app_data_dir,
+            commands::check_database_integrity,
             commands::get_default_config_path,
             commands::read_config_file,
             // Registry
@@ -1,6 +1,14 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-fn main() {
-    mcp_hub_lib::run()
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // `mcp-hub serve` runs the MCP proxy like `mcp-hub-cli serve`, so client
+    // configs can point at the app itself
+    if std::env::args().nth(1).as_deref() == Some("serve") {
+        return mcp_hub_lib::cli::main();
+    }
+    mcp_hub_lib::run();
+    ExitCode::SUCCESS
 }
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use uuid::Uuid;
 
+use crate::error::{HubError, HubResult};
+
 /// Represents an MCP server configuration in the central registry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,17 +13,34 @@ pub struct McpServer {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(default)]
     pub command: String,
     pub args: Vec<String>,
+    /// How `args` combine with the parent's, for a server with a parent
+    #[serde(default)]
+    pub args_mode: ArgsMode,
     pub env: std::collections::HashMap<String, String>,
+    /// How clients connect to this server. Remote transports use `url` and
+    /// `headers` instead of `command`/`args`/`env`.
+    #[serde(default)]
+    pub transport: TransportType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub headers: std::collections::HashMap<String, String>,
     #[serde(default)]
     pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<ServerSource>,
     /// If set, this server is an instance of another server (the parent).
-    /// Instances inherit the parent's command but can have different args/env.
+    /// Instances inherit the parent's command but can have different args/env;
+    /// see `services::inheritance` for how they are merged.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<String>,
+    /// IDs of the servers this one inherits from, nearest first. Only set on
+    /// servers resolved by `services::inheritance`, not stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inherits_from: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -34,19 +54,96 @@ impl McpServer {
             description: None,
             command,
             args,
+            args_mode: ArgsMode::Replace,
             env: std::collections::HashMap::new(),
+            transport: TransportType::Stdio,
+            url: None,
+            headers: std::collections::HashMap::new(),
             tags: Vec::new(),
             source: Some(ServerSource {
                 source_type: SourceType::Manual,
                 url: None,
             }),
             parent_id: None,
+            inherits_from: Vec::new(),
             created_at: now,
             updated_at: now,
         }
     }
 }
 
+/// Transport a client uses to talk to an MCP server
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransportType {
+    /// Local process speaking JSON-RPC over stdin/stdout
+    #[default]
+    Stdio,
+    /// Remote server using the HTTP+SSE transport
+    Sse,
+    /// Remote server using the streamable HTTP transport
+    StreamableHttp,
+}
+
+impl TransportType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportType::Stdio => "stdio",
+            TransportType::Sse => "sse",
+            TransportType::StreamableHttp => "streamable-http",
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        *self != TransportType::Stdio
+    }
+}
+
+impl FromStr for TransportType {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> HubResult<Self> {
+        match s {
+            "stdio" => Ok(TransportType::Stdio),
+            "sse" => Ok(TransportType::Sse),
+            "streamable-http" => Ok(TransportType::StreamableHttp),
+            _ => Err(HubError::Validation(format!("Unknown transport: {}", s))),
+        }
+    }
+}
+
+/// How a server's args combine with those of the server it inherits from
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArgsMode {
+    /// The server's own args, or the parent's if it has none
+    #[default]
+    Replace,
+    /// The parent's args followed by the server's own
+    Append,
+}
+
+impl ArgsMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgsMode::Replace => "replace",
+            ArgsMode::Append => "append",
+        }
+    }
+}
+
+impl FromStr for ArgsMode {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> HubResult<Self> {
+        match s {
+            "replace" => Ok(ArgsMode::Replace),
+            "append" => Ok(ArgsMode::Append),
+            _ => Err(HubError::Validation(format!("Unknown args mode: {}", s))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerSource {
@@ -152,50 +249,6 @@ impl ClientType {
         }
     }
 
-    pub fn from_str(s: &str) -> Option<ClientType> {
-        match s {
-            "claude-desktop" => Some(ClientType::ClaudeDesktop),
-            "claude-code" => Some(ClientType::ClaudeCode),
-            "cursor" => Some(ClientType::Cursor),
-            "windsurf" => Some(ClientType::Windsurf),
-            "vscode" => Some(ClientType::Vscode),
-            "vscode-insiders" => Some(ClientType::VscodeInsiders),
-            "zed" => Some(ClientType::Zed),
-            "continue" => Some(ClientType::Continue),
-            "cody" => Some(ClientType::Cody),
-            "cline" => Some(ClientType::Cline),
-            "roo-code" => Some(ClientType::RooCode),
-            "kilo-code" => Some(ClientType::KiloCode),
-            "amp" => Some(ClientType::Amp),
-            "augment" => Some(ClientType::Augment),
-            "antigravity" => Some(ClientType::Antigravity),
-            "jetbrains" => Some(ClientType::Jetbrains),
-            "gemini-cli" => Some(ClientType::GeminiCli),
-            "qwen-coder" => Some(ClientType::QwenCoder),
-            "opencode" => Some(ClientType::Opencode),
-            "openai-codex" => Some(ClientType::OpenaiCodex),
-            "kiro" => Some(ClientType::Kiro),
-            "trae" => Some(ClientType::Trae),
-            "lm-studio" => Some(ClientType::LmStudio),
-            "visual-studio" => Some(ClientType::VisualStudio),
-            "crush" => Some(ClientType::Crush),
-            "boltai" => Some(ClientType::Boltai),
-            "rovo-dev" => Some(ClientType::RovoDev),
-            "zencoder" => Some(ClientType::Zencoder),
-            "qodo-gen" => Some(ClientType::QodoGen),
-            "perplexity" => Some(ClientType::Perplexity),
-            "factory" => Some(ClientType::Factory),
-            "emdash" => Some(ClientType::Emdash),
-            "amazon-q" => Some(ClientType::AmazonQ),
-            "warp" => Some(ClientType::Warp),
-            "copilot-agent" => Some(ClientType::CopilotAgent),
-            "copilot-cli" => Some(ClientType::CopilotCli),
-            "smithery" => Some(ClientType::Smithery),
-            "custom" => Some(ClientType::Custom),
-            _ => None,
-        }
-    }
-
     pub fn as_str(&self) -> &'static str {
         match self {
             ClientType::ClaudeDesktop => "claude-desktop",
@@ -238,6 +291,71 @@ impl ClientType {
             ClientType::Custom => "custom",
         }
     }
+
+    /// Whether the client is started from a terminal, so the servers it
+    /// launches inherit the shell's PATH rather than the desktop session's
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ClientType::ClaudeCode
+                | ClientType::GeminiCli
+                | ClientType::QwenCoder
+                | ClientType::Opencode
+                | ClientType::OpenaiCodex
+                | ClientType::Crush
+                | ClientType::RovoDev
+                | ClientType::Factory
+                | ClientType::CopilotCli
+        )
+    }
+}
+
+impl FromStr for ClientType {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> HubResult<Self> {
+        match s {
+            "claude-desktop" => Ok(ClientType::ClaudeDesktop),
+            "claude-code" => Ok(ClientType::ClaudeCode),
+            "cursor" => Ok(ClientType::Cursor),
+            "windsurf" => Ok(ClientType::Windsurf),
+            "vscode" => Ok(ClientType::Vscode),
+            "vscode-insiders" => Ok(ClientType::VscodeInsiders),
+            "zed" => Ok(ClientType::Zed),
+            "continue" => Ok(ClientType::Continue),
+            "cody" => Ok(ClientType::Cody),
+            "cline" => Ok(ClientType::Cline),
+            "roo-code" => Ok(ClientType::RooCode),
+            "kilo-code" => Ok(ClientType::KiloCode),
+            "amp" => Ok(ClientType::Amp),
+            "augment" => Ok(ClientType::Augment),
+            "antigravity" => Ok(ClientType::Antigravity),
+            "jetbrains" => Ok(ClientType::Jetbrains),
+            "gemini-cli" => Ok(ClientType::GeminiCli),
+            "qwen-coder" => Ok(ClientType::QwenCoder),
+            "opencode" => Ok(ClientType::Opencode),
+            "openai-codex" => Ok(ClientType::OpenaiCodex),
+            "kiro" => Ok(ClientType::Kiro),
+            "trae" => Ok(ClientType::Trae),
+            "lm-studio" => Ok(ClientType::LmStudio),
+            "visual-studio" => Ok(ClientType::VisualStudio),
+            "crush" => Ok(ClientType::Crush),
+            "boltai" => Ok(ClientType::Boltai),
+            "rovo-dev" => Ok(ClientType::RovoDev),
+            "zencoder" => Ok(ClientType::Zencoder),
+            "qodo-gen" => Ok(ClientType::QodoGen),
+            "perplexity" => Ok(ClientType::Perplexity),
+            "factory" => Ok(ClientType::Factory),
+            "emdash" => Ok(ClientType::Emdash),
+            "amazon-q" => Ok(ClientType::AmazonQ),
+            "warp" => Ok(ClientType::Warp),
+            "copilot-agent" => Ok(ClientType::CopilotAgent),
+            "copilot-cli" => Ok(ClientType::CopilotCli),
+            "smithery" => Ok(ClientType::Smithery),
+            "custom" => Ok(ClientType::Custom),
+            _ => Err(HubError::Validation(format!("Unknown client type: {}", s))),
+        }
+    }
 }
 
 /// Represents a client instance (profile) for an MCP client application
@@ -249,14 +367,84 @@ pub struct ClientInstance {
     pub client_type: ClientType,
     pub config_path: String,
     pub enabled_servers: Vec<String>,
+    /// Tool filters by server ID, for servers that have one
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub tool_filters: std::collections::HashMap<String, ToolFilter>,
+    /// Overrides by server ID, for servers that have one
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub server_overrides: std::collections::HashMap<String, ServerOverride>,
+    /// Write server commands as absolute paths, for clients that are started
+    /// without the shell's PATH
+    #[serde(default)]
+    pub resolve_commands: bool,
+    /// Pass the login shell's PATH to servers, so programs they start
+    /// themselves (e.g. node, started by npx) are found too
+    #[serde(default)]
+    pub inject_path: bool,
     pub is_default: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_synced: Option<DateTime<Utc>>,
+    /// Last change to what this instance should sync (its servers, filters
+    /// or overrides), so a later `last_synced` means nothing is pending.
+    /// Edits to the config file itself are detected from the [`SyncSnapshot`]
+    /// taken at sync, which records the file's mtime and content hash.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_modified: Option<DateTime<Utc>>,
+    /// State of the config file as last seen by the watcher
+    #[serde(default)]
+    pub status: InstanceStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_message: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+/// State of an instance's config file
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstanceStatus {
+    /// Not checked yet
+    #[default]
+    Unknown,
+    /// Parses and matches the last sync (or was never synced)
+    Ok,
+    /// Edited outside MCP Hub since the last sync
+    Drifted,
+    /// The config file does not exist
+    Missing,
+    /// The config file cannot be parsed
+    Invalid,
+}
+
+impl InstanceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstanceStatus::Unknown => "unknown",
+            InstanceStatus::Ok => "ok",
+            InstanceStatus::Drifted => "drifted",
+            InstanceStatus::Missing => "missing",
+            InstanceStatus::Invalid => "invalid",
+        }
+    }
+}
+
+impl FromStr for InstanceStatus {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> HubResult<Self> {
+        match s {
+            "unknown" => Ok(InstanceStatus::Unknown),
+            "ok" => Ok(InstanceStatus::Ok),
+            "drifted" => Ok(InstanceStatus::Drifted),
+            "missing" => Ok(InstanceStatus::Missing),
+            "invalid" => Ok(InstanceStatus::Invalid),
+            _ => Err(HubError::Validation(format!(
+                "Unknown instance status: {}",
+                s
+            ))),
+        }
+    }
+}
+
 impl ClientInstance {
     #[allow(dead_code)]
     pub fn new(name: String, client_type: ClientType, config_path: String) -> Self {
@@ -266,9 +454,15 @@ impl ClientInstance {
             client_type,
             config_path,
             enabled_servers: Vec::new(),
+            tool_filters: std::collections::HashMap::new(),
+            server_overrides: std::collections::HashMap::new(),
+            resolve_commands: false,
+            inject_path: false,
             is_default: false,
             last_synced: None,
             last_modified: None,
+            status: InstanceStatus::Unknown,
+            status_message: None,
             created_at: Utc::now(),
         }
     }
@@ -284,6 +478,71 @@ pub struct InstanceServerMapping {
     pub enabled: bool,
 }
 
+/// Which of a server's tools an instance may use, as glob patterns (`*`, `?`).
+///
+/// A tool is allowed if it matches an `allow` pattern (or `allow` is empty)
+/// and matches no `deny` pattern.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolFilter {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl ToolFilter {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// How an instance runs a server differently from the other instances it
+/// is enabled on, applied when syncing the instance
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerOverride {
+    /// Args used instead of the server's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    /// Env vars set on top of the server's
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub env: std::collections::HashMap<String, String>,
+    /// Config entry key used instead of the server's name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+impl ServerOverride {
+    pub fn is_empty(&self) -> bool {
+        self.args.is_none() && self.env.is_empty() && self.alias.is_none()
+    }
+
+    /// Apply the override's args and env to a resolved server
+    pub fn apply(&self, server: &mut McpServer) {
+        if let Some(ref args) = self.args {
+            server.args = args.clone();
+        }
+        server.env.extend(self.env.clone());
+    }
+}
+
+/// A tool filter spelled out as the per-tool fields some clients support
+/// natively (Cline's `alwaysAllow`/`disabled`, Roo Code's `disabledTools`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAccess {
+    /// Tools the client may use without asking; `None` leaves the client's own list alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<Vec<String>>,
+    /// Tools hidden from the client
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied: Vec<String>,
+    /// Whether the filter leaves no tool at all, so the server is disabled
+    #[serde(default)]
+    pub disabled: bool,
+}
+
 /// Configuration backup record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -305,6 +564,110 @@ impl ConfigBackup {
     }
 }
 
+/// Payload of the event emitted when a watched config file changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFileEvent {
+    pub instance_id: String,
+    pub config_path: String,
+    pub status: InstanceStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// An instance's config file as MCP Hub last wrote it, the base for drift
+/// detection and three-way merges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSnapshot {
+    pub instance_id: String,
+    pub content: String,
+    pub content_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_mtime: Option<DateTime<Utc>>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Whether a config file changed outside MCP Hub since the last sync
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DriftStatus {
+    InSync,
+    Modified,
+    Missing,
+    NeverSynced,
+}
+
+/// Out-of-band changes to an instance's config file since the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReport {
+    pub instance_id: String,
+    pub status: DriftStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_modified_at: Option<DateTime<Utc>>,
+    /// Server entries added, changed or removed in the file since the last sync
+    pub added: Vec<EntryChange>,
+    pub changed: Vec<EntryChange>,
+    pub removed: Vec<EntryChange>,
+    /// Settings outside the server entries were edited
+    pub other_settings_changed: bool,
+}
+
+/// How to reconcile a config file that drifted from the registry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReconcileAction {
+    /// Take the file's version of managed entries into the registry
+    Import,
+    /// Rewrite managed entries from the registry
+    Overwrite,
+    /// Three-way merge of the last sync, the file and the registry
+    Merge,
+}
+
+/// A field changed both in the file and in the registry since the last sync
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    pub key: String,
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<serde_json::Value>,
+}
+
+/// What a reconcile changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileResult {
+    pub instance_id: String,
+    pub action: ReconcileAction,
+    /// Servers updated in the registry from the file
+    pub updated_servers: Vec<String>,
+    /// Servers disabled for the instance because they were removed from the file
+    pub disabled_servers: Vec<String>,
+    /// Conflicting edits; the registry's value was kept
+    pub conflicts: Vec<MergeConflict>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<String>,
+}
+
+/// A backup's content compared with the current config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupPreview {
+    pub backup: ConfigBackup,
+    pub content: String,
+    /// Current content of the instance's config file (empty if it does not exist)
+    pub current_content: String,
+    /// Unified diff from the current file to the backup
+    pub diff: String,
+}
+
 /// MCP configuration format for client config files
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -313,12 +676,78 @@ pub struct McpConfigFile {
     pub mcp_servers: std::collections::HashMap<String, McpServerEntry>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A single server entry in a client config file, independent of the client's format
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct McpServerEntry {
+    #[serde(default)]
+    pub transport: TransportType,
+    #[serde(default)]
     pub command: String,
+    #[serde(default)]
     pub args: Vec<String>,
     #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
     pub env: std::collections::HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub headers: std::collections::HashMap<String, String>,
+    /// Per-tool settings, for formats that have fields for them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolAccess>,
+}
+
+/// A single field that differs between the current and the synced server entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// Field name; env variables and headers are reported as `env.NAME` / `headers.NAME`
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<serde_json::Value>,
+}
+
+/// A server entry that a sync would add, change or remove
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryChange {
+    /// Entry key in the client config file
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    pub changes: Vec<FieldChange>,
+}
+
+/// What syncing an instance would do to its config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPlan {
+    pub instance_id: String,
+    pub instance_name: String,
+    pub config_path: String,
+    pub added: Vec<EntryChange>,
+    pub changed: Vec<EntryChange>,
+    pub removed: Vec<EntryChange>,
+    /// Keys of managed entries that are already up to date
+    pub unchanged: Vec<String>,
+    /// The config file does not exist yet and would be created
+    pub creates_file: bool,
+    /// Settings outside the client's server entries would be modified
+    pub touches_other_settings: bool,
+    /// Identifies the file content and planned output, so a confirmed plan
+    /// is only applied if nothing changed since the preview
+    pub fingerprint: String,
+}
+
+impl SyncPlan {
+    pub fn has_changes(&self) -> bool {
+        self.creates_file
+            || self.touches_other_settings
+            || !self.added.is_empty()
+            || !self.changed.is_empty()
+            || !self.removed.is_empty()
+    }
 }
 
 /// Application settings
@@ -328,10 +757,46 @@ pub struct AppSettings {
     pub theme: Theme,
     pub auto_start: bool,
     pub create_backups: bool,
+    /// Backups older than this are pruned (0 keeps them regardless of age)
     pub backup_retention_days: u32,
+    /// Backups kept per instance, newest first (0 keeps any number)
+    #[serde(default = "default_backup_retention_count")]
+    pub backup_retention_count: u32,
+    /// How long a server health check may take
+    #[serde(default = "default_health_check_timeout_secs")]
+    pub health_check_timeout_secs: u64,
+    /// Check servers used by an instance in the background
+    #[serde(default)]
+    pub health_monitor_enabled: bool,
+    /// Minutes between background health checks
+    #[serde(default = "default_health_check_interval_mins")]
+    pub health_check_interval_mins: u32,
+    /// Health checks older than this are pruned (0 keeps them regardless of age)
+    #[serde(default = "default_health_history_days")]
+    pub health_history_days: u32,
     /// Discovery settings
     #[serde(default)]
     pub discovery: DiscoverySettings,
+    /// Where secrets referred to from server env are kept
+    #[serde(default)]
+    pub credential_backend: CredentialBackend,
+}
+
+fn default_backup_retention_count() -> u32 {
+    20
+}
+
+fn default_health_check_timeout_secs() -> u64 {
+    // npx and uvx may download the server on first run
+    30
+}
+
+fn default_health_check_interval_mins() -> u32 {
+    15
+}
+
+fn default_health_history_days() -> u32 {
+    14
 }
 
 impl Default for AppSettings {
@@ -341,11 +806,50 @@ impl Default for AppSettings {
             auto_start: false,
             create_backups: true,
             backup_retention_days: 30,
+            backup_retention_count: default_backup_retention_count(),
+            health_check_timeout_secs: default_health_check_timeout_secs(),
+            health_monitor_enabled: false,
+            health_check_interval_mins: default_health_check_interval_mins(),
+            health_history_days: default_health_history_days(),
             discovery: DiscoverySettings::default(),
+            credential_backend: CredentialBackend::default(),
         }
     }
 }
 
+/// Where secrets are kept
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CredentialBackend {
+    /// Keychain, Credential Manager or the Secret Service
+    #[default]
+    Keyring,
+    /// A file in the app data dir, encrypted with a passphrase
+    Vault,
+}
+
+impl CredentialBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CredentialBackend::Keyring => "keyring",
+            CredentialBackend::Vault => "vault",
+        }
+    }
+}
+
+/// Whether secrets can currently be stored and read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialStatus {
+    pub backend: CredentialBackend,
+    pub available: bool,
+    /// Why secrets can't be stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub vault_exists: bool,
+    pub vault_unlocked: bool,
+}
+
 /// MCP Discovery settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -377,7 +881,7 @@ pub enum Theme {
 }
 
 /// Server health status
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum HealthStatus {
     Healthy,
@@ -385,6 +889,32 @@ pub enum HealthStatus {
     Unknown,
 }
 
+impl HealthStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthStatus::Healthy => "healthy",
+            HealthStatus::Error => "error",
+            HealthStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl FromStr for HealthStatus {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> HubResult<Self> {
+        match s {
+            "healthy" => Ok(HealthStatus::Healthy),
+            "error" => Ok(HealthStatus::Error),
+            "unknown" => Ok(HealthStatus::Unknown),
+            _ => Err(HubError::Validation(format!(
+                "Unknown health status: {}",
+                s
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerHealth {
@@ -393,4 +923,395 @@ pub struct ServerHealth {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
     pub last_checked: DateTime<Utc>,
+    /// Time from spawning the server to its `initialize` response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Protocol revision the server agreed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<McpImplementation>,
+    /// Capabilities the server announced in `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<serde_json::Value>,
+    /// Number of tools returned by `tools/list`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_count: Option<usize>,
+    /// Output the server wrote to stderr during the check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+}
+
+impl ServerHealth {
+    pub fn new(server_id: String, status: HealthStatus, error_message: Option<String>) -> Self {
+        Self {
+            server_id,
+            status,
+            error_message,
+            last_checked: Utc::now(),
+            latency_ms: None,
+            protocol_version: None,
+            server_info: None,
+            capabilities: None,
+            tool_count: None,
+            stderr: None,
+        }
+    }
+}
+
+/// A stored health check result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub server_id: String,
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// How reliable a server has been over its stored health checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStats {
+    pub server_id: String,
+    /// Checks that reached a verdict (healthy or error)
+    pub checks: u32,
+    /// Share of those checks that were healthy, from 0 to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime: Option<f64>,
+    /// Share of consecutive checks whose status differed, from 0 (steady)
+    /// to 1 (alternating every check)
+    pub flakiness: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<HealthStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_checked: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Payload of the event emitted when a server's health status changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthChangeEvent {
+    pub server_id: String,
+    pub server_name: String,
+    pub previous: HealthStatus,
+    pub current: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Name and version an MCP server or client reports about itself
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpImplementation {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// A tool offered by an MCP server, as returned by `tools/list`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTool {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub input_schema: serde_json::Value,
+}
+
+/// A resource offered by an MCP server, as returned by `resources/list`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerResource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// A prompt offered by an MCP server, as returned by `prompts/list`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerPrompt {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CapabilityKind {
+    Tool,
+    Resource,
+    Prompt,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CapabilityChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A tool, resource or prompt that differs from the previous probe
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityChange {
+    pub kind: CapabilityKind,
+    /// Tool or prompt name, or resource URI
+    pub name: String,
+    pub change: CapabilityChangeKind,
+}
+
+/// Everything a server offered when it was last probed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityInventory {
+    pub server_id: String,
+    pub probed_at: DateTime<Utc>,
+    pub tools: Vec<ServerTool>,
+    pub resources: Vec<ServerResource>,
+    pub prompts: Vec<ServerPrompt>,
+    /// Differences from the probe before this one
+    pub changes: Vec<CapabilityChange>,
+}
+
+/// A capability found by searching across the registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityMatch {
+    pub server_id: String,
+    pub server_name: String,
+    pub kind: CapabilityKind,
+    /// Tool or prompt name, or resource URI
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+/// A problem found by the doctor, with what to do about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorIssue {
+    pub server_id: String,
+    pub severity: IssueSeverity,
+    pub message: String,
+    pub fix: String,
+}
+
+/// Tool that installs runtimes per user and puts them on PATH itself
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionManager {
+    Nvm,
+    Fnm,
+    Volta,
+    Asdf,
+    Mise,
+    Pyenv,
+}
+
+impl VersionManager {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionManager::Nvm => "nvm",
+            VersionManager::Fnm => "fnm",
+            VersionManager::Volta => "volta",
+            VersionManager::Asdf => "asdf",
+            VersionManager::Mise => "mise",
+            VersionManager::Pyenv => "pyenv",
+        }
+    }
+}
+
+/// A runtime that a server's command needs, e.g. node for `npx`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeCheck {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_version: Option<String>,
+    /// Found, and at least the minimum version where that could be read
+    pub ok: bool,
+}
+
+/// Whether a stdio server's command can be started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerDiagnosis {
+    pub server_id: String,
+    pub server_name: String,
+    pub command: String,
+    /// Where the command resolves on the login shell's or MCP Hub's PATH
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_path: Option<String>,
+    /// Where the command resolves on the PATH a client started from the
+    /// desktop gets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gui_path: Option<String>,
+    /// Found in a common install location that is on neither PATH
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub found_elsewhere: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_manager: Option<VersionManager>,
+    pub runtimes: Vec<RuntimeCheck>,
+    /// Problems regardless of which client starts the server
+    pub issues: Vec<DoctorIssue>,
+}
+
+/// Problems with the servers enabled on an instance, as its client would
+/// start them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDiagnosis {
+    pub instance_id: String,
+    pub instance_name: String,
+    pub client_type: ClientType,
+    pub issues: Vec<DoctorIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    pub checked_at: DateTime<Utc>,
+    pub servers: Vec<ServerDiagnosis>,
+    pub instances: Vec<InstanceDiagnosis>,
+}
+
+/// Why an env value was taken for a secret
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretReason {
+    /// The env var is named like a key, secret, token or password
+    Name,
+    /// The value starts like a known kind of API key, e.g. `ghp_`
+    Prefix,
+    /// The value looks random
+    Entropy,
+}
+
+impl SecretReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecretReason::Name => "name",
+            SecretReason::Prefix => "prefix",
+            SecretReason::Entropy => "entropy",
+        }
+    }
+}
+
+/// An env value found to be a secret while importing a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedSecret {
+    pub server_id: String,
+    pub server_name: String,
+    pub env_var: String,
+    pub reason: SecretReason,
+    /// Why the value could not be moved into the keyring, in which case it
+    /// was imported as it is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Servers added by an import, and the secrets taken out of their env
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub servers: Vec<McpServer>,
+    pub secrets: Vec<ExtractedSecret>,
+}
+
+/// How rows whose foreign key points at a missing row are repaired
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrphanRepair {
+    Delete,
+    /// Set the key to NULL, for keys declared `ON DELETE SET NULL`
+    ClearReference,
+}
+
+/// Rows of a table whose foreign key points at a row that no longer exists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedRows {
+    pub table: String,
+    pub column: String,
+    pub parent_table: String,
+    pub count: usize,
+    pub repair: OrphanRepair,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    /// Problems SQLite found in the database file itself
+    pub corruption: Vec<String>,
+    pub orphans: Vec<OrphanedRows>,
+    /// Whether the orphaned rows were repaired
+    pub repaired: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_enums_round_trip() {
+        for status in [
+            InstanceStatus::Unknown,
+            InstanceStatus::Ok,
+            InstanceStatus::Drifted,
+            InstanceStatus::Missing,
+            InstanceStatus::Invalid,
+        ] {
+            assert_eq!(status.as_str().parse::<InstanceStatus>().unwrap(), status);
+        }
+        for status in [
+            HealthStatus::Healthy,
+            HealthStatus::Error,
+            HealthStatus::Unknown,
+        ] {
+            assert_eq!(status.as_str().parse::<HealthStatus>().unwrap(), status);
+        }
+        for transport in [
+            TransportType::Stdio,
+            TransportType::Sse,
+            TransportType::StreamableHttp,
+        ] {
+            assert_eq!(
+                transport.as_str().parse::<TransportType>().unwrap(),
+                transport
+            );
+        }
+        for mode in [ArgsMode::Replace, ArgsMode::Append] {
+            assert_eq!(mode.as_str().parse::<ArgsMode>().unwrap(), mode);
+        }
+        assert!("stale".parse::<InstanceStatus>().is_err());
+    }
 }
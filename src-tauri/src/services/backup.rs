@@ -0,0 +1,183 @@
+//! Config file backups: restore, preview and retention.
+
+use chrono::{DateTime, Duration, Utc};
+use similar::TextDiff;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::db::Database;
+use crate::error::{HubError, HubResult};
+use crate::models::{AppSettings, BackupPreview, ClientInstance, ConfigBackup};
+use crate::services::config;
+
+/// Load a backup together with the instance it belongs to
+fn load_backup(db: &Database, backup_id: &str) -> HubResult<(ConfigBackup, ClientInstance)> {
+    let backup = db
+        .get_backup(backup_id)?
+        .ok_or_else(|| HubError::not_found("Backup"))?;
+
+    let instance = db
+        .get_instance(&backup.instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+
+    Ok((backup, instance))
+}
+
+fn read_backup_content(backup: &ConfigBackup) -> HubResult<String> {
+    fs::read_to_string(&backup.backup_path)
+        .map_err(|e| HubError::io(format!("Failed to read backup {}", backup.backup_path), e))
+}
+
+/// Compare a backup with the current config file of its instance
+pub fn preview_backup(db: &Database, backup_id: &str) -> HubResult<BackupPreview> {
+    let (backup, instance) = load_backup(db, backup_id)?;
+
+    let content = read_backup_content(&backup)?;
+    let current_content = config::read_config_content(&PathBuf::from(&instance.config_path))?;
+    let diff = TextDiff::from_lines(&current_content, &content)
+        .unified_diff()
+        .header(&instance.config_path, &backup.backup_path)
+        .to_string();
+
+    Ok(BackupPreview {
+        backup,
+        content,
+        current_content,
+        diff,
+    })
+}
+
+/// Restore a backup over its instance's config file.
+///
+/// The current file is backed up first, so a restore can itself be undone.
+/// Returns the record of that pre-restore backup, if the file existed.
+pub fn restore_backup(db: &Database, backup_id: &str) -> HubResult<Option<ConfigBackup>> {
+    let (backup, instance) = load_backup(db, backup_id)?;
+
+    // Read first so a missing backup file fails before anything is touched
+    let content = read_backup_content(&backup)?;
+    let config_path = PathBuf::from(&instance.config_path);
+
+    let mut pre_restore = None;
+    if config::config_exists(&config_path) {
+        let backup_dir =
+            config::get_backup_dir().ok_or_else(|| HubError::not_found("Backup directory"))?;
+        let path = config::backup_config_file(&config_path, &backup_dir)?;
+        let record = ConfigBackup::new(instance.id.clone(), path.to_string_lossy().to_string());
+        db.create_backup(&record)?;
+        pre_restore = Some(record);
+    }
+
+    config::write_config_content(&config_path, &content)?;
+    log::info!(
+        "Restored {} from backup {}",
+        config_path.display(),
+        backup.backup_path
+    );
+
+    Ok(pre_restore)
+}
+
+/// Backups that fall outside the retention policy: older than
+/// `retention_days`, or beyond the newest `keep_count` of their instance.
+/// A limit of 0 disables that criterion.
+pub fn backups_to_prune(
+    backups: Vec<ConfigBackup>,
+    now: DateTime<Utc>,
+    retention_days: u32,
+    keep_count: u32,
+) -> Vec<ConfigBackup> {
+    let cutoff = now - Duration::days(i64::from(retention_days));
+
+    let mut by_instance: HashMap<String, Vec<ConfigBackup>> = HashMap::new();
+    for backup in backups {
+        by_instance
+            .entry(backup.instance_id.clone())
+            .or_default()
+            .push(backup);
+    }
+
+    let mut expired = Vec::new();
+    for (_, mut backups) in by_instance {
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+        for (index, backup) in backups.into_iter().enumerate() {
+            let too_old = retention_days > 0 && backup.created_at < cutoff;
+            let too_many = keep_count > 0 && index >= keep_count as usize;
+            if too_old || too_many {
+                expired.push(backup);
+            }
+        }
+    }
+
+    expired
+}
+
+/// Delete backups outside the retention policy in `settings`, both the files
+/// on disk and their records. Returns the number of backups removed.
+pub fn prune_backups(db: &Database, settings: &AppSettings) -> HubResult<usize> {
+    let backups = db.get_all_backups()?;
+    let expired = backups_to_prune(
+        backups,
+        Utc::now(),
+        settings.backup_retention_days,
+        settings.backup_retention_count,
+    );
+
+    let mut removed = 0;
+    for backup in expired {
+        match fs::remove_file(&backup.backup_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => {
+                log::warn!("Failed to delete backup {}: {}", backup.backup_path, e);
+                continue;
+            }
+        }
+        db.delete_backup(&backup.id)?;
+        removed += 1;
+    }
+
+    if removed > 0 {
+        log::info!("Pruned {} expired backups", removed);
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(instance_id: &str, age_days: i64, now: DateTime<Utc>) -> ConfigBackup {
+        let mut backup = ConfigBackup::new(
+            instance_id.to_string(),
+            format!("{}-{}", instance_id, age_days),
+        );
+        backup.created_at = now - Duration::days(age_days);
+        backup
+    }
+
+    #[test]
+    fn test_backups_to_prune_by_age_and_count() {
+        let now = Utc::now();
+        let backups = vec![
+            backup("a", 1, now),
+            backup("a", 2, now),
+            backup("a", 3, now),
+            backup("a", 40, now),
+            backup("b", 1, now),
+        ];
+
+        let mut pruned: Vec<String> = backups_to_prune(backups.clone(), now, 30, 2)
+            .into_iter()
+            .map(|b| b.backup_path)
+            .collect();
+        pruned.sort();
+        assert_eq!(pruned, vec!["a-3", "a-40"]);
+
+        // Limits of 0 disable pruning
+        assert!(backups_to_prune(backups, now, 0, 0).is_empty());
+    }
+}
@@ -1,8 +1,13 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::models::{ClientInstance, ClientType, McpConfigFile, McpServer, McpServerEntry};
+use crate::db::Database;
+use crate::error::{HubError, HubResult};
+use crate::models::{
+    AppSettings, ClientInstance, ClientType, McpConfigFile, McpServer, McpServerEntry,
+};
+use crate::services::formats;
 
 /// Get the default configuration path for a client type on the current platform
 pub fn get_default_config_path(client_type: &ClientType) -> Option<PathBuf> {
@@ -146,27 +151,17 @@ fn get_zed_config_path() -> Option<PathBuf> {
 }
 
 fn get_continue_config_path() -> Option<PathBuf> {
-    // Continue.dev extension config
-    #[cfg(target_os = "macos")]
-    {
-        dirs::home_dir().map(|home| {
-            home.join(".continue/config.json")
-        })
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        dirs::home_dir().map(|home| {
-            home.join(".continue/config.json")
-        })
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        dirs::home_dir().map(|home| {
-            home.join(".continue/config.json")
-        })
-    }
+    // Continue.dev reads ~/.continue/config.yaml; fall back to the legacy
+    // config.json only when that is the only config present
+    dirs::home_dir().map(|home| {
+        let yaml = home.join(".continue/config.yaml");
+        let legacy = home.join(".continue/config.json");
+        if !yaml.exists() && legacy.exists() {
+            legacy
+        } else {
+            yaml
+        }
+    })
 }
 
 fn get_cody_config_path() -> Option<PathBuf> {
@@ -321,8 +316,11 @@ fn get_opencode_config_path() -> Option<PathBuf> {
 }
 
 fn get_openai_codex_config_path() -> Option<PathBuf> {
-    // OpenAI Codex CLI config
-    dirs::home_dir().map(|home| home.join(".codex/mcp.json"))
+    // OpenAI Codex CLI reads [mcp_servers.<name>] tables from $CODEX_HOME/config.toml
+    if let Some(codex_home) = std::env::var_os("CODEX_HOME") {
+        return Some(PathBuf::from(codex_home).join("config.toml"));
+    }
+    dirs::home_dir().map(|home| home.join(".codex/config.toml"))
 }
 
 fn get_kiro_config_path() -> Option<PathBuf> {
@@ -491,91 +489,46 @@ fn get_smithery_config_path() -> Option<PathBuf> {
 }
 
 /// Check if a config file exists
-pub fn config_exists(path: &PathBuf) -> bool {
+pub fn config_exists(path: &Path) -> bool {
     path.exists() && path.is_file()
 }
 
-/// Read and parse an MCP configuration file
-pub fn read_config_file(path: &PathBuf) -> Result<McpConfigFile, String> {
+/// Read the config file content, treating a missing file as empty
+pub fn read_config_content(path: &PathBuf) -> HubResult<String> {
     if !config_exists(path) {
-        return Ok(McpConfigFile {
-            mcp_servers: HashMap::new(),
-        });
-    }
-
-    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {}", e))?;
-
-    // Handle empty files
-    if content.trim().is_empty() {
-        return Ok(McpConfigFile {
-            mcp_servers: HashMap::new(),
-        });
+        return Ok(String::new());
     }
 
-    serde_json::from_str(&content).map_err(|e| format!("Failed to parse config file: {}", e))
+    fs::read_to_string(path).map_err(|e| HubError::io("Failed to read config file", e))
 }
 
-/// Write MCP configuration to a file (overwrites entire file)
-pub fn write_config_file(path: &PathBuf, config: &McpConfigFile) -> Result<(), String> {
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
+/// Read and parse an MCP configuration file, detecting its format from the content
+pub fn read_config_file(path: &PathBuf) -> HubResult<McpConfigFile> {
+    let content = read_config_content(path)?;
+    let format = formats::detect_format(path, &content);
 
-    let content = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-
-    fs::write(path, content).map_err(|e| format!("Failed to write config file: {}", e))
+    Ok(McpConfigFile {
+        mcp_servers: format.read_servers(&content).map_err(HubError::Parse)?,
+    })
 }
 
-/// Write MCP servers to a config file, preserving other fields in the file
-/// This is used for config files like ~/.claude.json that contain other settings
-pub fn write_mcp_servers_preserving_config(
-    path: &PathBuf,
-    mcp_servers: &HashMap<String, McpServerEntry>,
-) -> Result<(), String> {
-    // Ensure parent directory exists
+/// Write config file content, creating the parent directory if needed
+pub fn write_config_content(path: &PathBuf, content: &str) -> HubResult<()> {
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        fs::create_dir_all(parent).map_err(|e| HubError::io("Failed to create directory", e))?;
     }
 
-    // Read existing content or start with empty object
-    let mut existing: serde_json::Value = if path.exists() {
-        let content = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read config file: {}", e))?;
-        if content.trim().is_empty() {
-            serde_json::json!({})
-        } else {
-            serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse config file: {}", e))?
-        }
-    } else {
-        serde_json::json!({})
-    };
-
-    // Ensure we have an object at the root
-    let obj = existing.as_object_mut()
-        .ok_or_else(|| "Config file is not a JSON object".to_string())?;
-
-    // Update only the mcpServers field
-    let servers_value = serde_json::to_value(mcp_servers)
-        .map_err(|e| format!("Failed to serialize MCP servers: {}", e))?;
-    obj.insert("mcpServers".to_string(), servers_value);
-
-    // Write back the merged config
-    let content = serde_json::to_string_pretty(&existing)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-
-    fs::write(path, content).map_err(|e| format!("Failed to write config file: {}", e))
+    fs::write(path, content).map_err(|e| HubError::io("Failed to write config file", e))
 }
 
 /// Create a backup of a config file
-pub fn backup_config_file(path: &PathBuf, backup_dir: &PathBuf) -> Result<PathBuf, String> {
+pub fn backup_config_file(path: &PathBuf, backup_dir: &PathBuf) -> HubResult<PathBuf> {
     if !config_exists(path) {
-        return Err("Config file does not exist".to_string());
+        return Err(HubError::not_found("Config file"));
     }
 
-    fs::create_dir_all(backup_dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    fs::create_dir_all(backup_dir)
+        .map_err(|e| HubError::io("Failed to create backup directory", e))?;
 
     let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
     let filename = path
@@ -585,68 +538,13 @@ pub fn backup_config_file(path: &PathBuf, backup_dir: &PathBuf) -> Result<PathBu
     let backup_filename = format!("{}_{}.backup", filename, timestamp);
     let backup_path = backup_dir.join(backup_filename);
 
-    fs::copy(path, &backup_path).map_err(|e| format!("Failed to create backup: {}", e))?;
-
-    Ok(backup_path)
-}
-
-/// Check if a client type uses a config file that contains other settings
-/// beyond just MCP servers (requiring merge-aware writes)
-fn client_requires_merge_write(client_type: &ClientType) -> bool {
-    matches!(
-        client_type,
-        ClientType::ClaudeCode
-            | ClientType::Zed           // settings.json with other Zed settings
-            | ClientType::Augment       // VS Code settings.json
-            | ClientType::GeminiCli     // settings.json with other Gemini settings
-    )
-}
-
-/// Convert servers to MCP config format and write to instance config file
-pub fn sync_servers_to_instance(
-    instance: &ClientInstance,
-    servers: &[McpServer],
-    backup_dir: Option<&PathBuf>,
-) -> Result<Option<PathBuf>, String> {
-    let config_path = PathBuf::from(&instance.config_path);
-    let mut backup_path = None;
-
-    // Create backup if requested and file exists
-    if let Some(dir) = backup_dir {
-        if config_exists(&config_path) {
-            backup_path = Some(backup_config_file(&config_path, dir)?);
-        }
-    }
-
-    // Build the MCP servers map
-    let mut mcp_servers = HashMap::new();
-
-    for server in servers {
-        if instance.enabled_servers.contains(&server.id) {
-            let entry = McpServerEntry {
-                command: server.command.clone(),
-                args: server.args.clone(),
-                env: server.env.clone(),
-            };
-            // Use server name as the key (sanitized)
-            let key = sanitize_server_name(&server.name);
-            mcp_servers.insert(key, entry);
-        }
-    }
-
-    // Use merge-aware write for clients that have other settings in their config file
-    if client_requires_merge_write(&instance.client_type) {
-        write_mcp_servers_preserving_config(&config_path, &mcp_servers)?;
-    } else {
-        let config = McpConfigFile { mcp_servers };
-        write_config_file(&config_path, &config)?;
-    }
+    fs::copy(path, &backup_path).map_err(|e| HubError::io("Failed to create backup", e))?;
 
     Ok(backup_path)
 }
 
 /// Sanitize server name for use as a config key
-fn sanitize_server_name(name: &str) -> String {
+pub fn sanitize_server_name(name: &str) -> String {
     name.to_lowercase()
         .chars()
         .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
@@ -656,21 +554,109 @@ fn sanitize_server_name(name: &str) -> String {
 }
 
 /// Import servers from an existing config file
-pub fn import_servers_from_config(path: &PathBuf) -> Result<Vec<McpServer>, String> {
+pub fn import_servers_from_config(path: &PathBuf) -> HubResult<Vec<McpServer>> {
     let config = read_config_file(path)?;
-    let mut servers = Vec::new();
+    Ok(entries_to_servers(config.mcp_servers, path))
+}
 
-    for (name, entry) in config.mcp_servers {
-        let mut server = McpServer::new(name.clone(), entry.command, entry.args);
-        server.env = entry.env;
-        server.source = Some(crate::models::ServerSource {
-            source_type: crate::models::SourceType::Imported,
-            url: Some(path.to_string_lossy().to_string()),
-        });
-        servers.push(server);
+/// Read the server entries of a client instance's config file using the client's format
+pub fn read_instance_entries(
+    instance: &ClientInstance,
+) -> HubResult<HashMap<String, McpServerEntry>> {
+    let path = PathBuf::from(&instance.config_path);
+    let format = formats::format_for_client(&instance.client_type, &path);
+    format
+        .read_servers(&read_config_content(&path)?)
+        .map_err(HubError::Parse)
+}
+
+/// Import servers from a client instance's config file using the client's format
+pub fn import_servers_from_instance(instance: &ClientInstance) -> HubResult<Vec<McpServer>> {
+    let entries = read_instance_entries(instance)?;
+    Ok(entries_to_servers(
+        entries,
+        Path::new(&instance.config_path),
+    ))
+}
+
+/// Entries in an instance's config file that MCP Hub did not write
+pub fn read_unmanaged_entries(
+    instance: &ClientInstance,
+    managed_keys: &[String],
+) -> HubResult<HashMap<String, McpServerEntry>> {
+    let mut entries = read_instance_entries(instance)?;
+    entries.retain(|key, _| !managed_keys.contains(key));
+    Ok(entries)
+}
+
+/// Convert unmanaged entries of an instance into registry servers, keyed by
+/// their config entry key. `keys` limits adoption to specific entries.
+///
+/// Syncs write a server under its sanitized name, so adopting fails if that
+/// name is already used by another entry of the config file.
+pub fn adopt_unmanaged_entries(
+    instance: &ClientInstance,
+    managed_keys: &[String],
+    keys: Option<&[String]>,
+) -> HubResult<Vec<(String, McpServer)>> {
+    let path = PathBuf::from(&instance.config_path);
+    let all_entries = read_instance_entries(instance)?;
+    let mut entries: Vec<(&String, &McpServerEntry)> = all_entries
+        .iter()
+        .filter(|(key, _)| !managed_keys.contains(key))
+        .filter(|(key, _)| keys.is_none_or(|keys| keys.contains(key)))
+        .collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    let mut synced_keys: Vec<String> = Vec::new();
+    let mut adopted = Vec::new();
+    for (key, entry) in entries {
+        let synced_key = sanitize_server_name(key);
+        if synced_key.is_empty() {
+            return Err(HubError::Validation(format!(
+                "`{}` can't be adopted: its name has nothing usable as a config key",
+                key
+            )));
+        }
+        let clashes = synced_keys.contains(&synced_key)
+            || (synced_key != *key
+                && (all_entries.contains_key(&synced_key) || managed_keys.contains(&synced_key)));
+        if clashes {
+            return Err(HubError::Conflict(format!(
+                "`{}` can't be adopted: it would be synced as `{}`, which another entry uses",
+                key, synced_key
+            )));
+        }
+        synced_keys.push(synced_key);
+        adopted.push((
+            key.clone(),
+            entry_to_server(key.clone(), entry.clone(), &path),
+        ));
     }
 
-    Ok(servers)
+    Ok(adopted)
+}
+
+/// Convert config file entries into registry servers sourced from `path`
+fn entries_to_servers(entries: HashMap<String, McpServerEntry>, path: &Path) -> Vec<McpServer> {
+    entries
+        .into_iter()
+        .map(|(name, entry)| entry_to_server(name, entry, path))
+        .collect()
+}
+
+/// Convert a single config file entry into a registry server sourced from `path`
+fn entry_to_server(name: String, entry: McpServerEntry, path: &Path) -> McpServer {
+    let mut server = McpServer::new(name, entry.command, entry.args);
+    server.env = entry.env;
+    server.transport = entry.transport;
+    server.url = entry.url;
+    server.headers = entry.headers;
+    server.source = Some(crate::models::ServerSource {
+        source_type: crate::models::SourceType::Imported,
+        url: Some(path.to_string_lossy().to_string()),
+    });
+    server
 }
 
 /// Detect installed MCP clients and return their config paths
@@ -763,6 +749,22 @@ pub fn get_database_path() -> Option<PathBuf> {
     get_app_data_dir().map(|dir| dir.join("mcp-hub.db"))
 }
 
+/// Load the saved app settings, falling back to the defaults
+pub fn load_settings(db: &Database) -> HubResult<AppSettings> {
+    match db.get_setting("app_settings")? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| HubError::Parse(format!("Invalid app settings: {}", e))),
+        None => Ok(AppSettings::default()),
+    }
+}
+
+/// Save the app settings
+pub fn save_settings(db: &Database, settings: &AppSettings) -> HubResult<()> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| HubError::Parse(format!("Invalid app settings: {}", e)))?;
+    db.set_setting("app_settings", &json)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -774,4 +776,32 @@ mod tests {
         assert_eq!(sanitize_server_name("  test  "), "test");
         assert_eq!(sanitize_server_name("hello@world!"), "hello-world");
     }
+
+    #[test]
+    fn test_adopt_refuses_clashing_keys() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("claude_desktop_config.json");
+        fs::write(
+            &path,
+            r#"{"mcpServers":{"GitHub":{"command":"gh"},"github":{"command":"other"},"fs":{"command":"fs"}}}"#,
+        )
+        .unwrap();
+        let instance = ClientInstance::new(
+            "Test".to_string(),
+            ClientType::ClaudeDesktop,
+            path.to_string_lossy().to_string(),
+        );
+
+        let only = |key: &str| Some(vec![key.to_string()]);
+        let adopted = adopt_unmanaged_entries(&instance, &[], only("fs").as_deref()).unwrap();
+        assert_eq!(adopted[0].0, "fs");
+
+        // "GitHub" would be synced as "github", which is another entry
+        let error = adopt_unmanaged_entries(&instance, &[], only("GitHub").as_deref()).unwrap_err();
+        assert_eq!(error.kind(), "conflict");
+        assert!(adopt_unmanaged_entries(&instance, &[], None).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -5,6 +5,7 @@
 //! 2. Local HTTP server - /.well-known/mcp.json endpoint (SEP-1649)
 
 use crate::models::McpServer;
+use crate::services::credentials;
 use axum::{
     http::{header, Method, StatusCode},
     response::{IntoResponse, Json},
@@ -47,10 +48,11 @@ pub struct McpServerCard {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransportConfig {
-    /// Transport type (stdio for local servers)
+    /// Transport type (stdio, sse or streamable-http)
     #[serde(rename = "type")]
     pub transport_type: String,
-    /// Command to run the server
+    /// Command to run the server (stdio only)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
     /// Arguments for the command
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -58,6 +60,9 @@ pub struct TransportConfig {
     /// Environment variables
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub env: HashMap<String, String>,
+    /// Endpoint URL (remote transports only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 /// Discovery index format for /.well-known/mcp.json
@@ -116,7 +121,13 @@ fn generate_server_markdown(server: &McpServer) -> String {
     if let Some(ref desc) = server.description {
         content.push_str(&format!("description: {}\n", desc));
     }
-    content.push_str(&format!("command: {}\n", server.command));
+    content.push_str(&format!("transport: {}\n", server.transport.as_str()));
+    if let Some(ref url) = server.url {
+        content.push_str(&format!("url: {}\n", url));
+    }
+    if !server.transport.is_remote() {
+        content.push_str(&format!("command: {}\n", server.command));
+    }
     if !server.args.is_empty() {
         content.push_str("args:\n");
         for arg in &server.args {
@@ -127,11 +138,7 @@ fn generate_server_markdown(server: &McpServer) -> String {
         content.push_str("env:\n");
         for (key, value) in &server.env {
             // Mask sensitive values
-            let masked = if key.to_lowercase().contains("key")
-                || key.to_lowercase().contains("secret")
-                || key.to_lowercase().contains("token")
-                || key.to_lowercase().contains("password")
-            {
+            let masked = if credentials::is_secret_name(key) {
                 "***REDACTED***".to_string()
             } else {
                 value.clone()
@@ -142,7 +149,7 @@ fn generate_server_markdown(server: &McpServer) -> String {
     if !server.tags.is_empty() {
         content.push_str(&format!("tags: [{}]\n", server.tags.join(", ")));
     }
-    content.push_str(&format!("provider: MCP Hub\n"));
+    content.push_str("provider: MCP Hub\n");
     content.push_str(&format!("updated_at: {}\n", server.updated_at.to_rfc3339()));
     content.push_str("---\n\n");
 
@@ -154,7 +161,13 @@ fn generate_server_markdown(server: &McpServer) -> String {
     }
 
     content.push_str("## Configuration\n\n");
-    content.push_str(&format!("**Command:** `{}`\n\n", server.command));
+    match server.url {
+        Some(ref url) if server.transport.is_remote() => {
+            content.push_str(&format!("**Transport:** {}\n\n", server.transport.as_str()));
+            content.push_str(&format!("**URL:** `{}`\n\n", url));
+        }
+        _ => content.push_str(&format!("**Command:** `{}`\n\n", server.command)),
+    }
 
     if !server.args.is_empty() {
         content.push_str("**Arguments:**\n");
@@ -209,10 +222,11 @@ pub fn write_mcp_directory(servers: &[McpServer]) -> Result<(), String> {
             let path = entry.path();
             if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                 // Only delete files we manage (prefixed with mcp-hub-)
-                if name.starts_with("mcp-hub-") && name.ends_with(".md") {
-                    if !managed_files.contains(&path) {
-                        let _ = fs::remove_file(&path);
-                    }
+                if name.starts_with("mcp-hub-")
+                    && name.ends_with(".md")
+                    && !managed_files.contains(&path)
+                {
+                    let _ = fs::remove_file(&path);
                 }
             }
         }
@@ -255,11 +269,12 @@ fn server_to_card(server: &McpServer) -> McpServerCard {
         homepage: None,
         icon: None,
         transport: TransportConfig {
-            transport_type: "stdio".to_string(),
+            transport_type: server.transport.as_str().to_string(),
             command: server.command.clone(),
             args: server.args.clone(),
-            // Don't expose environment variables in HTTP response for security
+            // Don't expose environment variables or headers in HTTP response for security
             env: HashMap::new(),
+            url: server.url.clone(),
         },
         tags: server.tags.clone(),
     }
@@ -0,0 +1,774 @@
+//! Runtime doctor.
+//!
+//! Most servers are started through `npx`, `uvx`, `docker` or `node`, and
+//! when one fails to start it is usually because that runtime is missing,
+//! too old, or only on the PATH of an interactive shell. The doctor resolves
+//! each stdio server's command on MCP Hub's own PATH and on the PATH a client
+//! started from the desktop gets, checks the versions of the runtimes the
+//! command needs, and suggests a fix for every problem it finds.
+
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::error::{HubError, HubResult};
+use crate::models::{
+    ClientInstance, DoctorIssue, DoctorReport, InstanceDiagnosis, IssueSeverity, McpServer,
+    RuntimeCheck, ServerDiagnosis, VersionManager,
+};
+use crate::services::{inheritance, shell_env};
+
+/// How long a runtime gets to print its version
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A runtime that server commands depend on
+struct Runtime {
+    name: &'static str,
+    /// Executable names to look for, in order of preference
+    executables: &'static [&'static str],
+    minimum_version: Option<&'static str>,
+    install_hint: &'static str,
+}
+
+const NODE: Runtime = Runtime {
+    name: "node",
+    executables: &["node"],
+    minimum_version: Some("18.0.0"),
+    install_hint:
+        "Install Node.js 18 or later from https://nodejs.org or with your package manager",
+};
+
+const PYTHON: Runtime = Runtime {
+    name: "python",
+    executables: &["python3", "python"],
+    minimum_version: Some("3.10.0"),
+    install_hint:
+        "Install Python 3.10 or later from https://www.python.org or with your package manager",
+};
+
+const UV: Runtime = Runtime {
+    name: "uv",
+    executables: &["uv"],
+    // First release with `uvx`
+    minimum_version: Some("0.3.0"),
+    install_hint: "Install uv from https://docs.astral.sh/uv/getting-started/installation/",
+};
+
+const DOCKER: Runtime = Runtime {
+    name: "docker",
+    executables: &["docker"],
+    minimum_version: None,
+    install_hint: "Install Docker Desktop from https://docs.docker.com/get-docker/",
+};
+
+/// Runtimes a command needs, judged by its file name
+fn runtimes_for(command: &str) -> &'static [Runtime] {
+    // Split on both separators, so Windows paths are recognized anywhere
+    let name = command
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(command)
+        .to_lowercase();
+    let name = name
+        .strip_suffix(".exe")
+        .or_else(|| name.strip_suffix(".cmd"))
+        .or_else(|| name.strip_suffix(".bat"))
+        .unwrap_or(&name);
+
+    match name {
+        "node" | "npx" | "npm" | "pnpm" | "yarn" => &[NODE],
+        "uv" | "uvx" => &[UV],
+        "docker" => &[DOCKER],
+        _ if name.starts_with("python") || name == "pipx" => &[PYTHON],
+        _ => &[],
+    }
+}
+
+/// Where server commands are looked up
+pub struct SearchPaths {
+    /// The login shell's PATH followed by MCP Hub's own
+    pub hub: Vec<PathBuf>,
+    /// The PATH clients started from the desktop get
+    pub gui: Vec<PathBuf>,
+    /// Common install locations, for commands that are on neither PATH
+    pub extra: Vec<PathBuf>,
+}
+
+impl SearchPaths {
+    pub fn current() -> Self {
+        let gui = gui_path().unwrap_or_else(|| {
+            std::env::var_os("PATH")
+                .map(|path| std::env::split_paths(&path).collect())
+                .unwrap_or_default()
+        });
+        Self {
+            hub: shell_env::search_dirs(),
+            gui,
+            extra: common_locations(),
+        }
+    }
+}
+
+/// The PATH apps launched from the Dock or an app launcher get. On macOS that
+/// is launchd's default; on Linux desktop sessions it is usually systemd's.
+/// Windows apps get the same PATH as everything else, so there is no
+/// separate one.
+fn gui_path() -> Option<Vec<PathBuf>> {
+    let path = if cfg!(target_os = "macos") {
+        "/usr/bin:/bin:/usr/sbin:/sbin"
+    } else if cfg!(unix) {
+        "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"
+    } else {
+        return None;
+    };
+    Some(std::env::split_paths(path).collect())
+}
+
+/// Places runtimes are commonly installed that are often missing from PATH
+fn common_locations() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let home = dirs::home_dir();
+
+    if cfg!(windows) {
+        if let Some(program_files) = std::env::var_os("ProgramFiles").map(PathBuf::from) {
+            dirs.push(program_files.join("nodejs"));
+            dirs.push(
+                program_files
+                    .join("Docker")
+                    .join("Docker")
+                    .join("resources")
+                    .join("bin"),
+            );
+        }
+        if let Some(app_data) = std::env::var_os("APPDATA").map(PathBuf::from) {
+            dirs.push(app_data.join("npm"));
+        }
+        if let Some(ref home) = home {
+            dirs.push(home.join(".local").join("bin"));
+            dirs.push(home.join(".cargo").join("bin"));
+        }
+        return dirs;
+    }
+
+    dirs.extend(
+        [
+            "/opt/homebrew/bin",
+            "/usr/local/bin",
+            "/opt/local/bin",
+            "/snap/bin",
+        ]
+        .iter()
+        .map(PathBuf::from),
+    );
+    if let Some(ref home) = home {
+        for dir in [
+            ".local/bin",
+            ".cargo/bin",
+            ".volta/bin",
+            ".bun/bin",
+            ".asdf/shims",
+            ".local/share/mise/shims",
+            ".pyenv/shims",
+        ] {
+            dirs.push(home.join(dir));
+        }
+
+        // Node versions installed with nvm, newest first
+        let mut versions: Vec<(Vec<u64>, PathBuf)> =
+            std::fs::read_dir(home.join(".nvm").join("versions").join("node"))
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    (version_parts(name.trim_start_matches('v')), entry.path())
+                })
+                .collect();
+        versions.sort_by(|a, b| b.0.cmp(&a.0));
+        dirs.extend(versions.into_iter().map(|(_, dir)| dir.join("bin")));
+    }
+    dirs
+}
+
+/// Find an executable by name in a list of directories
+pub fn find_executable(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let candidates: Vec<String> = if cfg!(windows) && Path::new(name).extension().is_none() {
+        [".exe", ".cmd", ".bat", ""]
+            .iter()
+            .map(|ext| format!("{}{}", name, ext))
+            .collect()
+    } else {
+        vec![name.to_string()]
+    };
+
+    dirs.iter().find_map(|dir| {
+        candidates
+            .iter()
+            .map(|candidate| dir.join(candidate))
+            .find(|path| is_executable(path))
+    })
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+/// The version manager an executable belongs to, judged by its path
+pub fn version_manager(path: &Path) -> Option<VersionManager> {
+    let path = path.to_string_lossy().replace('\\', "/").to_lowercase();
+    if path.contains("/.nvm/") {
+        Some(VersionManager::Nvm)
+    } else if path.contains("/.fnm/") || path.contains("/fnm_multishells/") {
+        Some(VersionManager::Fnm)
+    } else if path.contains("/.volta/") {
+        Some(VersionManager::Volta)
+    } else if path.contains("/.asdf/") {
+        Some(VersionManager::Asdf)
+    } else if path.contains("/mise/") || path.contains("/.mise/") {
+        Some(VersionManager::Mise)
+    } else if path.contains("/.pyenv/") {
+        Some(VersionManager::Pyenv)
+    } else {
+        None
+    }
+}
+
+/// Pick the version number out of `--version` output such as `v20.11.0`,
+/// `Python 3.12.1` or `Docker version 27.3.1, build ce12230`
+pub fn parse_version(output: &str) -> Option<String> {
+    output
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|token| token.trim_start_matches('v'))
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.'))
+        .map(|token| {
+            token
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.')
+                .collect::<String>()
+                .trim_end_matches('.')
+                .to_string()
+        })
+}
+
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| {
+            part.chars()
+                .take_while(char::is_ascii_digit)
+                .collect::<String>()
+                .parse()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Whether `version` is `minimum` or newer
+pub fn version_at_least(version: &str, minimum: &str) -> bool {
+    let mut version = version_parts(version);
+    let mut minimum = version_parts(minimum);
+    let len = version.len().max(minimum.len());
+    version.resize(len, 0);
+    minimum.resize(len, 0);
+    version >= minimum
+}
+
+/// Results of running runtimes, so each is only run once per report
+#[derive(Default)]
+struct Probes {
+    versions: HashMap<PathBuf, Option<String>>,
+    docker_daemon: Option<bool>,
+}
+
+impl Probes {
+    async fn version(&mut self, path: &Path) -> Option<String> {
+        if let Some(version) = self.versions.get(path) {
+            return version.clone();
+        }
+        let version = run_probe(path, &["--version"])
+            .await
+            .and_then(|output| parse_version(&output));
+        self.versions.insert(path.to_path_buf(), version.clone());
+        version
+    }
+
+    async fn docker_daemon_running(&mut self, docker: &Path) -> bool {
+        if let Some(running) = self.docker_daemon {
+            return running;
+        }
+        let running = run_probe(docker, &["info", "--format", "{{.ServerVersion}}"])
+            .await
+            .is_some();
+        self.docker_daemon = Some(running);
+        running
+    }
+}
+
+/// Run a program and return its output if it succeeds in time
+async fn run_probe(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(PROBE_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Older Pythons print their version to stderr
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push('\n');
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Some(text)
+}
+
+fn issue(server: &McpServer, severity: IssueSeverity, message: String, fix: String) -> DoctorIssue {
+    DoctorIssue {
+        server_id: server.id.clone(),
+        severity,
+        message,
+        fix,
+    }
+}
+
+/// Check every stdio server, then what that means for each instance.
+/// `servers` are the registry's, resolved here so a server whose parents
+/// can't be resolved is reported instead of failing the whole check.
+/// Instances need `enabled_servers` filled in.
+pub async fn run(servers: &[McpServer], instances: &[ClientInstance]) -> HubResult<DoctorReport> {
+    // Capturing the login shell's PATH runs the shell, so keep it off the
+    // async workers
+    let paths = tokio::task::spawn_blocking(SearchPaths::current)
+        .await
+        .map_err(|e| HubError::Other(format!("Failed to look up the search path: {}", e)))?;
+    let mut probes = Probes::default();
+
+    let mut resolved = Vec::new();
+    let mut diagnoses = Vec::new();
+    for server in servers {
+        match inheritance::resolve(server, servers) {
+            Ok(server) => resolved.push(server),
+            Err(e) => diagnoses.push(diagnose_unresolved(server, e)),
+        }
+    }
+    for server in resolved.iter().filter(|s| !s.transport.is_remote()) {
+        diagnoses.push(diagnose_server(server, &paths, &mut probes).await);
+    }
+
+    let instances = instances
+        .iter()
+        .map(|instance| diagnose_instance(instance, &resolved, &diagnoses))
+        .collect();
+
+    Ok(DoctorReport {
+        checked_at: Utc::now(),
+        servers: diagnoses,
+        instances,
+    })
+}
+
+/// A server whose parents can't be resolved, so there's nothing to run
+fn diagnose_unresolved(server: &McpServer, error: HubError) -> ServerDiagnosis {
+    ServerDiagnosis {
+        server_id: server.id.clone(),
+        server_name: server.name.clone(),
+        command: server.command.clone(),
+        resolved_path: None,
+        gui_path: None,
+        found_elsewhere: None,
+        version_manager: None,
+        runtimes: Vec::new(),
+        issues: vec![issue(
+            server,
+            IssueSeverity::Error,
+            error.to_string(),
+            "Choose a parent that doesn't inherit from this server".to_string(),
+        )],
+    }
+}
+
+async fn diagnose_server(
+    server: &McpServer,
+    paths: &SearchPaths,
+    probes: &mut Probes,
+) -> ServerDiagnosis {
+    let command = server.command.trim();
+    let mut diagnosis = ServerDiagnosis {
+        server_id: server.id.clone(),
+        server_name: server.name.clone(),
+        command: command.to_string(),
+        resolved_path: None,
+        gui_path: None,
+        found_elsewhere: None,
+        version_manager: None,
+        runtimes: Vec::new(),
+        issues: Vec::new(),
+    };
+
+    if command.is_empty() {
+        diagnosis.issues.push(issue(
+            server,
+            IssueSeverity::Error,
+            format!("{} has no command", server.name),
+            "Set the command that starts the server".to_string(),
+        ));
+        return diagnosis;
+    }
+
+    let runtimes = runtimes_for(command);
+    let located = if Path::new(command).components().count() > 1 {
+        // A path works the same for every client, wherever it points
+        let path = PathBuf::from(command);
+        if is_executable(&path) {
+            diagnosis.resolved_path = Some(command.to_string());
+            diagnosis.gui_path = Some(command.to_string());
+            Some(path)
+        } else {
+            diagnosis.issues.push(issue(
+                server,
+                IssueSeverity::Error,
+                format!("`{}` does not exist or is not executable", command),
+                "Correct the path, or use just the command's name so it is looked up on PATH"
+                    .to_string(),
+            ));
+            None
+        }
+    } else {
+        let resolved = find_executable(command, &paths.hub);
+        let gui = find_executable(command, &paths.gui);
+        let elsewhere = match (&resolved, &gui) {
+            (None, None) => find_executable(command, &paths.extra),
+            _ => None,
+        };
+        let display =
+            |path: &Option<PathBuf>| path.as_ref().map(|path| path.to_string_lossy().to_string());
+        diagnosis.resolved_path = display(&resolved);
+        diagnosis.gui_path = display(&gui);
+        diagnosis.found_elsewhere = display(&elsewhere);
+
+        let located = resolved.or(gui).or(elsewhere);
+        if located.is_none() {
+            diagnosis.issues.push(issue(
+                server,
+                IssueSeverity::Error,
+                format!(
+                    "`{}` was not found on PATH or in common install locations",
+                    command
+                ),
+                runtimes
+                    .first()
+                    .map(|runtime| runtime.install_hint.to_string())
+                    .unwrap_or_else(|| {
+                        format!(
+                            "Install `{}`, or use its absolute path as the command",
+                            command
+                        )
+                    }),
+            ));
+        }
+        located
+    };
+
+    let Some(located) = located else {
+        return diagnosis;
+    };
+    diagnosis.version_manager = version_manager(&located);
+    if let Some(manager) = diagnosis.version_manager {
+        let runtime = runtimes.first().map(|r| r.name).unwrap_or(command);
+        if let Some(found) = manager_issue(server, command, runtime, &located, manager) {
+            diagnosis.issues.push(found);
+        }
+    }
+
+    for runtime in runtimes {
+        let check = check_runtime(server, runtime, &located, paths, probes, &mut diagnosis).await;
+        diagnosis.runtimes.push(check);
+    }
+
+    diagnosis
+}
+
+/// Version managers put executables in places other clients can't rely on
+fn manager_issue(
+    server: &McpServer,
+    command: &str,
+    runtime: &str,
+    located: &Path,
+    manager: VersionManager,
+) -> Option<DoctorIssue> {
+    let shim = located
+        .to_string_lossy()
+        .replace('\\', "/")
+        .contains("/shims/");
+
+    match manager {
+        // Volta's shims work without a shell and without a project
+        VersionManager::Volta => None,
+        VersionManager::Asdf | VersionManager::Mise | VersionManager::Pyenv if shim => Some(issue(
+            server,
+            IssueSeverity::Warning,
+            format!(
+                "`{}` is a {} shim, which picks a version from the client's working directory and fails where none is set",
+                command,
+                manager.as_str()
+            ),
+            match manager {
+                VersionManager::Asdf => format!(
+                    "Set a global {} version with `asdf set --home` (`asdf global` before 0.16), or use the absolute path of the installed binary",
+                    runtime
+                ),
+                VersionManager::Pyenv => format!(
+                    "Set a global {} version with `pyenv global`, or use the absolute path of the installed binary",
+                    runtime
+                ),
+                _ => format!(
+                    "Set a global {} version with `mise use -g`, or use the absolute path of the installed binary",
+                    runtime
+                ),
+            },
+        )),
+        _ => Some(issue(
+            server,
+            IssueSeverity::Warning,
+            format!(
+                "`{}` comes from {} ({}). Clients started from the desktop don't load {}, and the path breaks once that version is removed",
+                command,
+                manager.as_str(),
+                located.display(),
+                manager.as_str()
+            ),
+            format!(
+                "Use the absolute path as the command, or install {} system-wide",
+                runtime
+            ),
+        )),
+    }
+}
+
+async fn check_runtime(
+    server: &McpServer,
+    runtime: &Runtime,
+    located: &Path,
+    paths: &SearchPaths,
+    probes: &mut Probes,
+    diagnosis: &mut ServerDiagnosis,
+) -> RuntimeCheck {
+    // Prefer the runtime installed next to the command, e.g. the node that
+    // came with npx
+    let beside: Vec<PathBuf> = located
+        .parent()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .collect();
+    let path = [&beside, &paths.hub, &paths.gui, &paths.extra]
+        .into_iter()
+        .find_map(|dirs| {
+            runtime
+                .executables
+                .iter()
+                .find_map(|name| find_executable(name, dirs))
+        });
+
+    let mut check = RuntimeCheck {
+        name: runtime.name.to_string(),
+        path: path.as_ref().map(|path| path.to_string_lossy().to_string()),
+        version: None,
+        minimum_version: runtime.minimum_version.map(String::from),
+        ok: false,
+    };
+
+    let Some(path) = path else {
+        diagnosis.issues.push(issue(
+            server,
+            IssueSeverity::Error,
+            format!(
+                "`{}` needs {}, which was not found",
+                diagnosis.command, runtime.name
+            ),
+            runtime.install_hint.to_string(),
+        ));
+        return check;
+    };
+
+    check.version = probes.version(&path).await;
+    check.ok = true;
+    if let (Some(version), Some(minimum)) = (&check.version, runtime.minimum_version) {
+        if !version_at_least(version, minimum) {
+            check.ok = false;
+            let fix = match version_manager(&path) {
+                Some(manager) => format!(
+                    "Install {} {} or later with {} and make it the default",
+                    runtime.name,
+                    minimum,
+                    manager.as_str()
+                ),
+                None => runtime.install_hint.to_string(),
+            };
+            diagnosis.issues.push(issue(
+                server,
+                IssueSeverity::Error,
+                format!(
+                    "{} {} is older than {}, the oldest version {} supports",
+                    runtime.name, version, minimum, server.name
+                ),
+                fix,
+            ));
+        }
+    }
+
+    if runtime.name == DOCKER.name && !probes.docker_daemon_running(&path).await {
+        check.ok = false;
+        diagnosis.issues.push(issue(
+            server,
+            IssueSeverity::Error,
+            "Docker is installed but its daemon is not running".to_string(),
+            "Start Docker Desktop, or the docker service on Linux (`sudo systemctl start docker`)"
+                .to_string(),
+        ));
+    }
+
+    check
+}
+
+/// Problems with an instance's enabled servers, including whether its
+/// client can find each command on the PATH it runs with
+fn diagnose_instance(
+    instance: &ClientInstance,
+    servers: &[McpServer],
+    diagnoses: &[ServerDiagnosis],
+) -> InstanceDiagnosis {
+    let mut issues = Vec::new();
+
+    for diagnosis in diagnoses
+        .iter()
+        .filter(|d| instance.enabled_servers.contains(&d.server_id))
+    {
+        issues.extend(diagnosis.issues.iter().cloned());
+        let Some(server) = servers.iter().find(|s| s.id == diagnosis.server_id) else {
+            continue;
+        };
+
+        if instance.client_type.is_terminal() {
+            if let (None, None, Some(path)) = (
+                &diagnosis.resolved_path,
+                &diagnosis.gui_path,
+                &diagnosis.found_elsewhere,
+            ) {
+                let dir = Path::new(path)
+                    .parent()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default();
+                issues.push(issue(
+                    server,
+                    IssueSeverity::Warning,
+                    format!("`{}` was only found at {}", diagnosis.command, path),
+                    format!(
+                        "Make sure your shell's PATH includes {}, or use the absolute path as the command",
+                        dir
+                    ),
+                ));
+            }
+        } else if diagnosis.gui_path.is_none() && !instance.resolve_commands {
+            if let Some(path) = diagnosis
+                .resolved_path
+                .as_ref()
+                .or(diagnosis.found_elsewhere.as_ref())
+            {
+                issues.push(issue(
+                    server,
+                    IssueSeverity::Error,
+                    format!(
+                        "`{}` is at {}, which is not on the PATH {} gets when started from the desktop",
+                        diagnosis.command,
+                        path,
+                        instance.client_type.display_name()
+                    ),
+                    format!(
+                        "Turn on absolute command paths for {}, or use `{}` as the command of {}",
+                        instance.name, path, server.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    InstanceDiagnosis {
+        instance_id: instance.id.clone(),
+        instance_name: instance.name.clone(),
+        client_type: instance.client_type.clone(),
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v20.11.0\n").as_deref(), Some("20.11.0"));
+        assert_eq!(parse_version("Python 3.12.1").as_deref(), Some("3.12.1"));
+        assert_eq!(
+            parse_version("uv 0.4.18 (7b55e9790 2024-10-01)").as_deref(),
+            Some("0.4.18")
+        );
+        assert_eq!(
+            parse_version("Docker version 27.3.1, build ce12230").as_deref(),
+            Some("27.3.1")
+        );
+        assert_eq!(parse_version("no version here"), None);
+
+        assert!(version_at_least("20.11.0", "18.0.0"));
+        assert!(version_at_least("3.10", "3.10.0"));
+        assert!(!version_at_least("3.9.18", "3.10.0"));
+        assert!(!version_at_least("16.20.2", "18.0.0"));
+    }
+
+    #[test]
+    fn test_version_manager() {
+        let manager = |path: &str| version_manager(Path::new(path));
+        assert_eq!(
+            manager("/Users/me/.nvm/versions/node/v20.11.0/bin/npx"),
+            Some(VersionManager::Nvm)
+        );
+        assert_eq!(
+            manager("/home/me/.asdf/shims/node"),
+            Some(VersionManager::Asdf)
+        );
+        assert_eq!(
+            manager("/home/me/.local/share/mise/shims/uvx"),
+            Some(VersionManager::Mise)
+        );
+        assert_eq!(
+            manager("/home/me/.volta/bin/npx"),
+            Some(VersionManager::Volta)
+        );
+        assert_eq!(manager("/usr/local/bin/npx"), None);
+
+        assert_eq!(runtimes_for("npx")[0].name, "node");
+        assert_eq!(
+            runtimes_for("C:\\Program Files\\nodejs\\npx.cmd")[0].name,
+            "node"
+        );
+        assert_eq!(runtimes_for("uvx")[0].name, "uv");
+        assert_eq!(runtimes_for("python3.12")[0].name, "python");
+        assert!(runtimes_for("my-server").is_empty());
+    }
+}
@@ -0,0 +1,719 @@
+//! Detection and reconciliation of edits made to client config files outside
+//! MCP Hub.
+//!
+//! Every sync stores a [`SyncSnapshot`] of the file it wrote. Comparing the
+//! file against that snapshot shows what a client or a person changed since,
+//! and the snapshot serves as the common base for three-way merges between
+//! the file and the registry.
+//!
+//! Secret env values are not stored in snapshots. They are replaced by a hash,
+//! which is enough to tell whether the file or the registry still has the
+//! value that was synced.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::db::Database;
+use crate::error::{HubError, HubResult};
+use crate::models::{
+    ClientInstance, DriftReport, DriftStatus, EntryChange, McpServer, McpServerEntry,
+    MergeConflict, ReconcileAction, ReconcileResult, ServerOverride, SyncSnapshot,
+};
+use crate::services::formats::ClientConfigFormat;
+use crate::services::{config, credentials, formats, inheritance, sync};
+
+/// Starts a secret value's hash in place of the value in a snapshot
+const REDACTED_PREFIX: &str = "redacted:sha256:";
+
+/// Last modification time of a file, if it exists
+pub fn file_mtime(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+}
+
+/// Capture an instance's config file as it is now, with the values of the
+/// `secrets` env vars of each entry redacted
+pub fn snapshot_config(
+    instance: &ClientInstance,
+    format: &dyn ClientConfigFormat,
+    secrets: &HashMap<String, BTreeSet<String>>,
+) -> HubResult<SyncSnapshot> {
+    let path = PathBuf::from(&instance.config_path);
+    let content = config::read_config_content(&path)?;
+
+    let mut redacted = HashMap::new();
+    for (key, mut entry) in format.read_servers(&content).map_err(HubError::Parse)? {
+        let Some(names) = secrets.get(&key) else {
+            continue;
+        };
+        for (name, value) in entry.env.iter_mut() {
+            if names.contains(name) {
+                *value = redact(value);
+            }
+        }
+        redacted.insert(key, entry);
+    }
+
+    Ok(SyncSnapshot {
+        instance_id: instance.id.clone(),
+        content_hash: sync::content_hash(&content),
+        content: if redacted.is_empty() {
+            content
+        } else {
+            format
+                .merge(&content, &redacted, &[])
+                .map_err(HubError::Parse)?
+        },
+        file_mtime: file_mtime(&path),
+        synced_at: Utc::now(),
+    })
+}
+
+fn redact(value: &str) -> String {
+    format!("{}{}", REDACTED_PREFIX, sync::content_hash(value))
+}
+
+/// Names of the env vars with redacted values, by entry key
+fn redacted_env(entries: &HashMap<String, McpServerEntry>) -> HashMap<String, BTreeSet<String>> {
+    let mut redacted: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for (key, entry) in entries {
+        for (name, value) in &entry.env {
+            if value.starts_with(REDACTED_PREFIX) {
+                redacted
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(name.clone());
+            }
+        }
+    }
+    redacted
+}
+
+/// Put the secret values back into the snapshot's entries where one of
+/// `sides` still has the value that was synced
+fn restore_redacted(
+    base: &mut HashMap<String, McpServerEntry>,
+    sides: &[&HashMap<String, McpServerEntry>],
+) {
+    for (key, entry) in base.iter_mut() {
+        for (name, value) in entry.env.iter_mut() {
+            if !value.starts_with(REDACTED_PREFIX) {
+                continue;
+            }
+            let synced = sides
+                .iter()
+                .filter_map(|side| side.get(key)?.env.get(name))
+                .find(|candidate| redact(candidate) == *value);
+            if let Some(synced) = synced {
+                *value = synced.clone();
+            }
+        }
+    }
+}
+
+/// Compare an instance's config file with what the last sync wrote
+pub fn check_drift(db: &Database, instance_id: &str) -> HubResult<DriftReport> {
+    let instance = db
+        .get_instance(instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+    let snapshot = db.get_sync_snapshot(instance_id)?;
+    let path = PathBuf::from(&instance.config_path);
+
+    let mut report = DriftReport {
+        instance_id: instance.id.clone(),
+        status: DriftStatus::NeverSynced,
+        synced_at: snapshot.as_ref().map(|s| s.synced_at),
+        file_modified_at: file_mtime(&path),
+        added: Vec::new(),
+        changed: Vec::new(),
+        removed: Vec::new(),
+        other_settings_changed: false,
+    };
+
+    let Some(snapshot) = snapshot else {
+        return Ok(report);
+    };
+
+    let exists = config::config_exists(&path);
+    if exists {
+        // Unchanged mtime means the file was not written since the sync
+        if report.file_modified_at.is_some() && report.file_modified_at == snapshot.file_mtime {
+            report.status = DriftStatus::InSync;
+            return Ok(report);
+        }
+    }
+
+    let content = config::read_config_content(&path)?;
+    if exists && sync::content_hash(&content) == snapshot.content_hash {
+        report.status = DriftStatus::InSync;
+        return Ok(report);
+    }
+
+    let format = formats::format_for_client(&instance.client_type, &path);
+    let mut base = format
+        .read_servers(&snapshot.content)
+        .map_err(HubError::Parse)?;
+    let current = format.read_servers(&content).map_err(HubError::Parse)?;
+    let secrets = redacted_env(&base);
+    restore_redacted(&mut base, &[&current]);
+    let no_secrets = BTreeSet::new();
+
+    let keys: BTreeSet<&String> = base.keys().chain(current.keys()).collect();
+    for key in keys {
+        let before = base.get(key);
+        let after = current.get(key);
+        if before == after {
+            continue;
+        }
+        let change = EntryChange {
+            key: key.clone(),
+            server_id: None,
+            changes: sync::mask_secrets(
+                sync::diff_entries(before, after),
+                secrets.get(key).unwrap_or(&no_secrets),
+            ),
+        };
+        match (before, after) {
+            (None, _) => report.added.push(change),
+            (_, None) => report.removed.push(change),
+            _ => report.changed.push(change),
+        }
+    }
+
+    report.other_settings_changed = exists
+        && !snapshot.content.trim().is_empty()
+        && sync::strip_servers(format, &snapshot.content)?
+            != sync::strip_servers(format, &content)?;
+    report.status = if exists {
+        DriftStatus::Modified
+    } else {
+        DriftStatus::Missing
+    };
+
+    Ok(report)
+}
+
+/// Reconcile an instance's config file with the registry, then sync it.
+///
+/// `Import` takes the file's version of every managed entry into the
+/// registry, `Overwrite` rewrites managed entries from the registry, and
+/// `Merge` combines changes from both sides since the last sync, keeping the
+/// registry's value where both changed the same field.
+pub fn reconcile(
+    db: &Database,
+    instance_id: &str,
+    action: ReconcileAction,
+) -> HubResult<ReconcileResult> {
+    let mut instance = db
+        .get_instance(instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+    instance.enabled_servers = db.get_enabled_servers_for_instance(instance_id)?;
+    instance.tool_filters = db.get_tool_filters(instance_id)?;
+    instance.server_overrides = db.get_server_overrides(instance_id)?;
+
+    let mut result = ReconcileResult {
+        instance_id: instance.id.clone(),
+        action,
+        updated_servers: Vec::new(),
+        disabled_servers: Vec::new(),
+        conflicts: Vec::new(),
+        backup_path: None,
+    };
+
+    if action != ReconcileAction::Overwrite {
+        let servers = db.get_all_servers()?;
+        let known_tools = db.get_tool_names()?;
+        let built = sync::build_entries(&instance, &servers, &known_tools)?;
+        let ours = built.entries;
+
+        // Servers behind managed keys, including ones disabled since the last sync
+        let mut key_owners = db.get_managed_key_owners(instance_id)?;
+        key_owners.extend(built.owners);
+
+        let path = PathBuf::from(&instance.config_path);
+        let format = formats::format_for_client(&instance.client_type, &path);
+        let theirs = format
+            .read_servers(&config::read_config_content(&path)?)
+            .map_err(HubError::Parse)?;
+
+        let snapshot = db.get_sync_snapshot(instance_id)?;
+        if snapshot.is_none() && action == ReconcileAction::Merge {
+            return Err(HubError::Validation(
+                "Instance has no sync snapshot to merge against; import or overwrite instead"
+                    .to_string(),
+            ));
+        }
+        let mut base = match snapshot {
+            Some(snapshot) => format
+                .read_servers(&snapshot.content)
+                .map_err(HubError::Parse)?,
+            None => HashMap::new(),
+        };
+        let mut secrets = redacted_env(&base);
+        for (key, names) in built.secrets {
+            secrets.entry(key).or_default().extend(names);
+        }
+        restore_redacted(&mut base, &[&theirs, &ours]);
+
+        let mut keys: Vec<&String> = key_owners.keys().collect();
+        keys.sort();
+        for key in keys {
+            let server_id = &key_owners[key];
+            let ours = ours.get(key);
+            let merged = match action {
+                ReconcileAction::Merge => merge_entries(
+                    key,
+                    base.get(key),
+                    theirs.get(key),
+                    ours,
+                    &mut result.conflicts,
+                ),
+                _ => theirs.get(key).cloned(),
+            };
+            apply_to_registry(db, &instance, server_id, ours, merged, &mut result)?;
+        }
+
+        for conflict in &mut result.conflicts {
+            let is_secret = conflict.field.strip_prefix("env.").is_some_and(|name| {
+                secrets
+                    .get(&conflict.key)
+                    .is_some_and(|names| names.contains(name))
+            });
+            if is_secret {
+                for value in [&mut conflict.file, &mut conflict.registry]
+                    .into_iter()
+                    .flatten()
+                {
+                    *value = Value::from(sync::SECRET_MASK);
+                }
+            }
+        }
+    }
+
+    // Write the reconciled registry state back to the file
+    let backup_path = sync::sync_instance(db, instance_id, None)?;
+    result.backup_path = backup_path.map(|p| p.to_string_lossy().to_string());
+
+    Ok(result)
+}
+
+/// Bring a registry server in line with the reconciled entry for it
+fn apply_to_registry(
+    db: &Database,
+    instance: &ClientInstance,
+    server_id: &str,
+    ours: Option<&McpServerEntry>,
+    merged: Option<McpServerEntry>,
+    result: &mut ReconcileResult,
+) -> HubResult<()> {
+    let Some(mut entry) = merged else {
+        if ours.is_some() {
+            db.set_server_enabled_for_instance(&instance.id, server_id, false)?;
+            result.disabled_servers.push(server_id.to_string());
+        }
+        return Ok(());
+    };
+
+    // Tool settings come from the instance's filters, not the server
+    entry.tools = ours.and_then(|e| e.tools.clone());
+    if ours == Some(&entry) {
+        return Ok(());
+    }
+
+    let servers = db.get_all_servers()?;
+    let Some(mut server) = servers.iter().find(|s| s.id == server_id).cloned() else {
+        return Ok(());
+    };
+    // The entry is the server with what it inherits from its parents and
+    // the instance's override applied, so compare against that. What the
+    // override sets stays with the override, and of the rest the server
+    // keeps only what differs from its parent.
+    let base = inheritance::resolve(&server, &servers)?;
+    let server_override = instance.server_overrides.get(server_id);
+    let mut resolved = base.clone();
+    if let Some(server_override) = server_override {
+        server_override.apply(&mut resolved);
+    }
+    let parent = servers
+        .iter()
+        .find(|s| Some(&s.id) == server.parent_id.as_ref())
+        .map(|parent| inheritance::resolve(parent, &servers))
+        .transpose()?;
+
+    // A resolved command or injected PATH is how this instance gets the
+    // server's command, not part of the server itself
+    if let Some(ours) = ours {
+        if entry.command == ours.command {
+            entry.command = resolved.command.clone();
+        }
+        if instance.inject_path && entry.env.get("PATH") == ours.env.get("PATH") {
+            match resolved.env.get("PATH") {
+                Some(path) => entry.env.insert("PATH".to_string(), path.clone()),
+                None => entry.env.remove("PATH"),
+            };
+        }
+    }
+    keep_secret_references(&resolved, ours, &mut entry)?;
+    let mut wanted = McpServer {
+        transport: entry.transport,
+        command: entry.command,
+        args: entry.args,
+        env: entry.env,
+        url: entry.url,
+        headers: entry.headers,
+        ..resolved
+    };
+    if let Some(server_override) = server_override {
+        let mut updated = server_override.clone();
+        take_overridden(&mut updated, &mut wanted, &base);
+        if &updated != server_override {
+            db.set_server_override(&instance.id, server_id, &updated)?;
+        }
+    }
+
+    let before = server.clone();
+    inheritance::set_resolved(&mut server, parent.as_ref(), &wanted);
+    let changed = server.transport != before.transport
+        || server.command != before.command
+        || server.args != before.args
+        || server.args_mode != before.args_mode
+        || server.env != before.env
+        || server.url != before.url
+        || server.headers != before.headers;
+    if changed {
+        server.updated_at = Utc::now();
+        db.update_server(&server)?;
+    }
+
+    if ours.is_none() {
+        db.set_server_enabled_for_instance(&instance.id, server_id, true)?;
+    }
+    result.updated_servers.push(server_id.to_string());
+
+    Ok(())
+}
+
+/// Move what an instance's override sets from `wanted` into the override,
+/// leaving the values of `base`, the server without the override, in their
+/// place. Env vars the file no longer has are dropped from the override.
+fn take_overridden(server_override: &mut ServerOverride, wanted: &mut McpServer, base: &McpServer) {
+    if let Some(args) = server_override.args.as_mut() {
+        *args = std::mem::replace(&mut wanted.args, base.args.clone());
+    }
+    let keys: Vec<String> = server_override.env.keys().cloned().collect();
+    for key in keys {
+        match wanted.env.remove(&key) {
+            Some(value) => server_override.env.insert(key.clone(), value),
+            None => server_override.env.remove(&key),
+        };
+        if let Some(value) = base.env.get(&key) {
+            wanted.env.insert(key, value.clone());
+        }
+    }
+}
+
+/// Keep secrets out of the registry: values the file still has from the
+/// keyring stay references, and secrets edited in the file are stored in the
+/// keyring in place of the old ones. An edited secret the server inherits is
+/// stored as the server's own.
+fn keep_secret_references(
+    server: &McpServer,
+    ours: Option<&McpServerEntry>,
+    entry: &mut McpServerEntry,
+) -> HubResult<()> {
+    for (key, value) in &server.env {
+        let Some(name) = credentials::parse_reference(value) else {
+            continue;
+        };
+        let Some(theirs) = entry.env.get(key) else {
+            continue;
+        };
+        if ours.and_then(|ours| ours.env.get(key)) != Some(theirs) {
+            credentials::store_credential(
+                &credentials::get_server_env_key(&server.id, name),
+                theirs,
+            )?;
+        }
+        entry.env.insert(key.clone(), value.clone());
+    }
+    Ok(())
+}
+
+/// Three-way merge of one entry between the last sync (`base`), the file
+/// (`theirs`) and the registry (`ours`). `None` means the entry is absent.
+fn merge_entries(
+    key: &str,
+    base: Option<&McpServerEntry>,
+    theirs: Option<&McpServerEntry>,
+    ours: Option<&McpServerEntry>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<McpServerEntry> {
+    if theirs == base || theirs == ours {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+
+    match (theirs, ours) {
+        (Some(theirs), Some(ours)) => {
+            let base = base.cloned().unwrap_or_default();
+            Some(McpServerEntry {
+                transport: merge_value(
+                    key,
+                    "transport",
+                    &base.transport,
+                    &theirs.transport,
+                    &ours.transport,
+                    conflicts,
+                ),
+                command: merge_value(
+                    key,
+                    "command",
+                    &base.command,
+                    &theirs.command,
+                    &ours.command,
+                    conflicts,
+                ),
+                args: merge_value(key, "args", &base.args, &theirs.args, &ours.args, conflicts),
+                url: merge_value(key, "url", &base.url, &theirs.url, &ours.url, conflicts),
+                env: merge_map(key, "env", &base.env, &theirs.env, &ours.env, conflicts),
+                headers: merge_map(
+                    key,
+                    "headers",
+                    &base.headers,
+                    &theirs.headers,
+                    &ours.headers,
+                    conflicts,
+                ),
+                tools: ours.tools.clone(),
+            })
+        }
+        // Removed on one side, edited on the other
+        _ => {
+            conflicts.push(MergeConflict {
+                key: key.to_string(),
+                field: "entry".to_string(),
+                file: to_value(&theirs),
+                registry: to_value(&ours),
+            });
+            ours.cloned()
+        }
+    }
+}
+
+fn merge_value<T: Clone + PartialEq + Serialize>(
+    key: &str,
+    field: &str,
+    base: &T,
+    theirs: &T,
+    ours: &T,
+    conflicts: &mut Vec<MergeConflict>,
+) -> T {
+    if theirs == base || theirs == ours {
+        ours.clone()
+    } else if ours == base {
+        theirs.clone()
+    } else {
+        conflicts.push(MergeConflict {
+            key: key.to_string(),
+            field: field.to_string(),
+            file: to_value(theirs),
+            registry: to_value(ours),
+        });
+        ours.clone()
+    }
+}
+
+fn merge_map(
+    key: &str,
+    prefix: &str,
+    base: &HashMap<String, String>,
+    theirs: &HashMap<String, String>,
+    ours: &HashMap<String, String>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> HashMap<String, String> {
+    let names: BTreeSet<&String> = base
+        .keys()
+        .chain(theirs.keys())
+        .chain(ours.keys())
+        .collect();
+
+    let mut merged = HashMap::new();
+    for name in names {
+        let value = merge_value(
+            key,
+            &format!("{}.{}", prefix, name),
+            &base.get(name),
+            &theirs.get(name),
+            &ours.get(name),
+            conflicts,
+        );
+        if let Some(value) = value {
+            merged.insert(name.clone(), value.clone());
+        }
+    }
+
+    merged
+}
+
+fn to_value<T: Serialize>(value: &T) -> Option<Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Null) | Err(_) => None,
+        Ok(value) => Some(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str, env: &[(&str, &str)]) -> McpServerEntry {
+        McpServerEntry {
+            command: command.to_string(),
+            env: env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_entries_combines_both_sides() {
+        let base = entry("npx", &[("A", "1"), ("B", "1")]);
+        // The file changed A and the command, the registry changed B and the command
+        let theirs = entry("bunx", &[("A", "2"), ("B", "1")]);
+        let ours = entry("pnpx", &[("A", "1"), ("B", "2")]);
+
+        let mut conflicts = Vec::new();
+        let merged = merge_entries(
+            "srv",
+            Some(&base),
+            Some(&theirs),
+            Some(&ours),
+            &mut conflicts,
+        )
+        .unwrap();
+
+        assert_eq!(merged, entry("pnpx", &[("A", "2"), ("B", "2")]));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "command");
+        assert_eq!(conflicts[0].file, Some(Value::from("bunx")));
+    }
+
+    #[test]
+    fn test_merge_entries_removals() {
+        let base = entry("npx", &[]);
+        let mut conflicts = Vec::new();
+
+        // Removed from the file, untouched in the registry
+        assert_eq!(
+            merge_entries("srv", Some(&base), None, Some(&base), &mut conflicts),
+            None
+        );
+        // Removed from the file but edited in the registry: the registry wins
+        let ours = entry("uvx", &[]);
+        assert_eq!(
+            merge_entries("srv", Some(&base), None, Some(&ours), &mut conflicts),
+            Some(ours)
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "entry");
+    }
+
+    #[test]
+    fn test_take_overridden() {
+        let mut base = McpServer::new("fs".to_string(), "npx".to_string(), vec!["~".to_string()]);
+        base.env.insert("LOG".to_string(), "info".to_string());
+        let mut server_override = ServerOverride {
+            args: Some(vec!["~/work".to_string()]),
+            env: HashMap::from([
+                ("LOG".to_string(), "debug".to_string()),
+                ("ROOT".to_string(), "~/work".to_string()),
+            ]),
+            alias: None,
+        };
+
+        // The file changed the overridden args and LOG, dropped ROOT and added a var
+        let mut wanted = base.clone();
+        wanted.args = vec!["~/code".to_string()];
+        wanted.env.insert("LOG".to_string(), "trace".to_string());
+        wanted.env.insert("COLOR".to_string(), "1".to_string());
+        take_overridden(&mut server_override, &mut wanted, &base);
+
+        assert_eq!(server_override.args, Some(vec!["~/code".to_string()]));
+        assert_eq!(
+            server_override.env,
+            HashMap::from([("LOG".to_string(), "trace".to_string())])
+        );
+        assert_eq!(wanted.args, base.args);
+        assert_eq!(wanted.env["LOG"], "info");
+        assert_eq!(wanted.env["COLOR"], "1");
+        assert!(!wanted.env.contains_key("ROOT"));
+    }
+
+    #[test]
+    fn test_check_drift() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-test-{}", uuid::Uuid::new_v4()));
+        let db = Database::new(dir.join("mcp-hub.db")).unwrap();
+        let path = dir.join("claude_desktop_config.json");
+        let synced =
+            r#"{"mcpServers":{"a":{"command":"a-cmd","args":[],"env":{"TOKEN":"s3cret"}}}}"#;
+        fs::write(&path, synced).unwrap();
+        let instance = ClientInstance::new(
+            "Test".to_string(),
+            crate::models::ClientType::ClaudeDesktop,
+            path.to_string_lossy().to_string(),
+        );
+        db.create_instance(&instance).unwrap();
+
+        let status = |db: &Database| check_drift(db, &instance.id).unwrap().status;
+        assert_eq!(status(&db), DriftStatus::NeverSynced);
+        let error = check_drift(&db, "missing").unwrap_err();
+        assert_eq!(error.kind(), "not-found");
+
+        let format = formats::format_for_client(&instance.client_type, &path);
+        let secrets = HashMap::from([("a".to_string(), BTreeSet::from(["TOKEN".to_string()]))]);
+        let snapshot = snapshot_config(&instance, format, &secrets).unwrap();
+        assert!(!snapshot.content.contains("s3cret"));
+        db.set_sync_snapshot(&snapshot).unwrap();
+        assert_eq!(status(&db), DriftStatus::InSync);
+
+        // Rewriting the same content changes the mtime but not the hash
+        fs::write(&path, synced).unwrap();
+        assert_eq!(status(&db), DriftStatus::InSync);
+
+        // The secret is unchanged, so only the command shows up
+        fs::write(&path, synced.replace("a-cmd", "b-cmd")).unwrap();
+        let report = check_drift(&db, &instance.id).unwrap();
+        assert_eq!(report.status, DriftStatus::Modified);
+        let fields: Vec<&str> = report.changed[0]
+            .changes
+            .iter()
+            .map(|c| c.field.as_str())
+            .collect();
+        assert_eq!(fields, ["command"]);
+
+        // A changed secret shows up without its values
+        fs::write(&path, synced.replace("s3cret", "n3w")).unwrap();
+        let report = check_drift(&db, &instance.id).unwrap();
+        let change = &report.changed[0].changes[0];
+        assert_eq!(change.field, "env.TOKEN");
+        assert_eq!(change.after, Some(Value::from(sync::SECRET_MASK)));
+
+        fs::write(&path, "{").unwrap();
+        let error = check_drift(&db, &instance.id).unwrap_err();
+        assert_eq!(error.kind(), "parse");
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(status(&db), DriftStatus::Missing);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
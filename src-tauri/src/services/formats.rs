@@ -0,0 +1,1453 @@
+//! Client config file formats
+//!
+//! MCP clients disagree on where servers live in their config file and on the
+//! shape of each entry (Zed uses `context_servers`, VS Code uses `servers` with a
+//! `type` tag, opencode uses `mcp`, Continue uses an `mcpServers` list, Codex
+//! uses TOML `[mcp_servers.<name>]` tables, ...). Remote servers are spelled
+//! differently again (`url`, `serverUrl`, `httpUrl`, typed `url`, ...).
+//! Each client family gets a `ClientConfigFormat` implementation that knows how
+//! to read its entries, render a fresh file and merge servers into an existing one.
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::models::{ClientType, McpServerEntry, ToolAccess, TransportType};
+
+/// Reads and writes the MCP server section of one family of client config files
+pub trait ClientConfigFormat: Send + Sync {
+    /// Short identifier for the format, used in logs and error messages
+    fn name(&self) -> &'static str;
+
+    /// Parse the MCP server entries out of a config file's content
+    fn read_servers(&self, content: &str) -> Result<HashMap<String, McpServerEntry>, String>;
+
+    /// Render a complete config file containing only the given servers
+    fn render(&self, servers: &HashMap<String, McpServerEntry>) -> Result<String, String> {
+        self.merge("", servers, &[])
+    }
+
+    /// Merge servers into existing file content.
+    ///
+    /// Entries in `servers` are added or updated, entries named in `remove` are
+    /// deleted, and everything else in the file (other settings, other servers,
+    /// client-specific fields on updated entries) is left untouched.
+    fn merge(
+        &self,
+        existing: &str,
+        servers: &HashMap<String, McpServerEntry>,
+        remove: &[String],
+    ) -> Result<String, String>;
+}
+
+// ==================== Format selection ====================
+
+/// Plain `mcpServers` object (Claude Desktop, Cursor, Kiro, Amazon Q, ...)
+static MCP_SERVERS_JSON: ObjectFormat = ObjectFormat {
+    name: "mcp-servers-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::Url,
+    tool_fields: ToolFields::None,
+};
+
+/// Claude Code: `mcpServers` with `"type": "http" | "sse"` on remote entries
+static CLAUDE_CODE_JSON: ObjectFormat = ObjectFormat {
+    name: "claude-code-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::Typed {
+        streamable_http: "http",
+        sse: "sse",
+    },
+    tool_fields: ToolFields::None,
+};
+
+/// Windsurf: `mcpServers` with `serverUrl` on remote entries
+static WINDSURF_JSON: ObjectFormat = ObjectFormat {
+    name: "windsurf-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::ServerUrl,
+    tool_fields: ToolFields::None,
+};
+
+/// Gemini CLI and its forks: `httpUrl` for streamable HTTP, `url` for SSE
+static GEMINI_JSON: ObjectFormat = ObjectFormat {
+    name: "gemini-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::GeminiUrl,
+    tool_fields: ToolFields::None,
+};
+
+/// Cline: `mcpServers` with `"type": "streamableHttp" | "sse"` on remote entries
+static CLINE_JSON: ObjectFormat = ObjectFormat {
+    name: "cline-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::Typed {
+        streamable_http: "streamableHttp",
+        sse: "sse",
+    },
+    tool_fields: ToolFields::AlwaysAllow,
+};
+
+/// Roo Code and Kilo Code: like Cline but spelling it `streamable-http`, and
+/// with `disabledTools` for hiding individual tools
+static ROO_CODE_JSON: ObjectFormat = ObjectFormat {
+    name: "roo-code-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::Typed {
+        streamable_http: "streamable-http",
+        sse: "sse",
+    },
+    tool_fields: ToolFields::DisabledTools,
+};
+
+/// `mcpServers` object in a YAML file
+static MCP_SERVERS_YAML: ObjectFormat = ObjectFormat {
+    name: "mcp-servers-yaml",
+    syntax: Syntax::Yaml,
+    key_path: &["mcpServers"],
+    stdio_type: None,
+    remote: RemoteStyle::Url,
+    tool_fields: ToolFields::None,
+};
+
+/// VS Code family: `servers` object with an explicit `type`
+static VSCODE_JSON: ObjectFormat = ObjectFormat {
+    name: "vscode-json",
+    syntax: Syntax::Json,
+    key_path: &["servers"],
+    stdio_type: Some("stdio"),
+    remote: RemoteStyle::Typed {
+        streamable_http: "http",
+        sse: "sse",
+    },
+    tool_fields: ToolFields::None,
+};
+
+/// Crush: `mcp` object with an explicit `type`
+static CRUSH_JSON: ObjectFormat = ObjectFormat {
+    name: "crush-json",
+    syntax: Syntax::Json,
+    key_path: &["mcp"],
+    stdio_type: Some("stdio"),
+    remote: RemoteStyle::Typed {
+        streamable_http: "http",
+        sse: "sse",
+    },
+    tool_fields: ToolFields::None,
+};
+
+/// Continue `config.yaml`: `mcpServers` as a list of named entries
+static CONTINUE_YAML: NamedListFormat = NamedListFormat {
+    name: "continue-yaml",
+    syntax: Syntax::Yaml,
+    key_path: &["mcpServers"],
+    remote: CONTINUE_REMOTE,
+};
+
+/// Continue's legacy `config.json` with the same list shape
+static CONTINUE_JSON: NamedListFormat = NamedListFormat {
+    name: "continue-json",
+    syntax: Syntax::Json,
+    key_path: &["mcpServers"],
+    remote: CONTINUE_REMOTE,
+};
+
+const CONTINUE_REMOTE: RemoteStyle = RemoteStyle::Typed {
+    streamable_http: "streamable-http",
+    sse: "sse",
+};
+
+/// Augment: named list nested in VS Code's `augment.advanced` setting
+static AUGMENT_JSON: NamedListFormat = NamedListFormat {
+    name: "augment-json",
+    syntax: Syntax::Json,
+    key_path: &["augment.advanced", "mcpServers"],
+    remote: RemoteStyle::Url,
+};
+
+static ZED_JSON: ZedFormat = ZedFormat;
+
+static OPENCODE_JSON: OpencodeFormat = OpencodeFormat;
+
+static CODEX_TOML: CodexTomlFormat = CodexTomlFormat;
+
+/// Get the config format used by a client type for the config file at `path`
+pub fn format_for_client(client_type: &ClientType, path: &Path) -> &'static dyn ClientConfigFormat {
+    match client_type {
+        ClientType::Continue if Syntax::from_path(path) == Syntax::Json => &CONTINUE_JSON,
+        ClientType::Continue => &CONTINUE_YAML,
+        ClientType::Vscode | ClientType::VscodeInsiders | ClientType::VisualStudio => &VSCODE_JSON,
+        ClientType::Zed => &ZED_JSON,
+        ClientType::Opencode => &OPENCODE_JSON,
+        ClientType::Augment => &AUGMENT_JSON,
+        ClientType::Crush => &CRUSH_JSON,
+        ClientType::OpenaiCodex => &CODEX_TOML,
+        ClientType::ClaudeCode => &CLAUDE_CODE_JSON,
+        ClientType::Windsurf => &WINDSURF_JSON,
+        ClientType::GeminiCli | ClientType::QwenCoder => &GEMINI_JSON,
+        ClientType::Cline => &CLINE_JSON,
+        ClientType::RooCode | ClientType::KiloCode => &ROO_CODE_JSON,
+        _ => &MCP_SERVERS_JSON,
+    }
+}
+
+/// Guess the format of a config file that isn't tied to a known client
+/// from its extension and the top-level keys it contains
+pub fn detect_format(path: &Path, content: &str) -> &'static dyn ClientConfigFormat {
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        return &CODEX_TOML;
+    }
+
+    if Syntax::from_path(path) == Syntax::Yaml {
+        let is_list = Syntax::Yaml
+            .parse(content)
+            .ok()
+            .and_then(|root| root.get("mcpServers").map(Value::is_array))
+            .unwrap_or(true);
+        return if is_list {
+            &CONTINUE_YAML
+        } else {
+            &MCP_SERVERS_YAML
+        };
+    }
+
+    let root: Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(_) => return &MCP_SERVERS_JSON,
+    };
+
+    if let Some(servers) = root.get("mcpServers") {
+        return if servers.is_array() {
+            &CONTINUE_JSON
+        } else {
+            &MCP_SERVERS_JSON
+        };
+    }
+    if root.get("context_servers").is_some() {
+        return &ZED_JSON;
+    }
+    if root.get("servers").is_some() {
+        return &VSCODE_JSON;
+    }
+    if root.get("augment.advanced").is_some() {
+        return &AUGMENT_JSON;
+    }
+    if let Some(mcp) = root.get("mcp").and_then(|v| v.as_object()) {
+        // opencode tags local servers "local" and takes the command as an array
+        let is_opencode = mcp
+            .values()
+            .any(|entry| entry.get("type").and_then(|t| t.as_str()) == Some("local"));
+        return if is_opencode {
+            &OPENCODE_JSON
+        } else {
+            &CRUSH_JSON
+        };
+    }
+
+    &MCP_SERVERS_JSON
+}
+
+// ==================== Document helpers ====================
+
+/// Serialization syntax of a config file.
+///
+/// JSON and YAML files are both edited as a `serde_json::Value` tree, so key
+/// order and unrelated settings survive a write. In YAML files only the
+/// servers block is rewritten, so comments elsewhere in the file survive too.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Syntax {
+    Json,
+    Yaml,
+}
+
+impl Syntax {
+    fn from_path(path: &Path) -> Syntax {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => Syntax::Yaml,
+            _ => Syntax::Json,
+        }
+    }
+
+    /// Parse file content, treating an empty file as an empty object
+    fn parse(self, content: &str) -> Result<Value, String> {
+        if content.trim().is_empty() {
+            return Ok(json!({}));
+        }
+
+        let root: Value = match self {
+            Syntax::Json => serde_json::from_str(content)
+                .map_err(|e| format!("Failed to parse config file: {}", e))?,
+            Syntax::Yaml => serde_yaml_ng::from_str(content)
+                .map_err(|e| format!("Failed to parse config file: {}", e))?,
+        };
+
+        if !root.is_object() {
+            return Err("Config file is not an object at the top level".to_string());
+        }
+
+        Ok(root)
+    }
+
+    fn serialize(self, root: &Value) -> Result<String, String> {
+        match self {
+            Syntax::Json => serde_json::to_string_pretty(root)
+                .map_err(|e| format!("Failed to serialize config: {}", e)),
+            Syntax::Yaml => serde_yaml_ng::to_string(root)
+                .map_err(|e| format!("Failed to serialize config: {}", e)),
+        }
+    }
+
+    /// Serialize a document edited under `key_path` that was parsed from
+    /// `existing`
+    fn write(self, existing: &str, root: &Value, key_path: &[&str]) -> Result<String, String> {
+        match self {
+            Syntax::Yaml => match splice_yaml_block(existing, root, key_path)? {
+                Some(content) => Ok(content),
+                None => self.serialize(root),
+            },
+            Syntax::Json => self.serialize(root),
+        }
+    }
+}
+
+/// Replace the top-level block of `key_path` in a YAML file with its value
+/// in `root`, keeping every other line as it was. Returns None if the block
+/// can't be told apart from the rest of the file, e.g. for nested key paths.
+fn splice_yaml_block(
+    existing: &str,
+    root: &Value,
+    key_path: &[&str],
+) -> Result<Option<String>, String> {
+    let ([key], Some(value)) = (key_path, get_path(root, key_path)) else {
+        return Ok(None);
+    };
+    let mut block = Map::new();
+    block.insert(key.to_string(), value.clone());
+    let block = Syntax::Yaml.serialize(&Value::Object(block))?;
+
+    let lines: Vec<&str> = existing.split_inclusive('\n').collect();
+    let starts_block = |line: &&str| {
+        [
+            format!("{}:", key),
+            format!("\"{}\":", key),
+            format!("'{}':", key),
+        ]
+        .iter()
+        .any(|prefix| line.starts_with(prefix.as_str()))
+    };
+    let content = match lines.iter().position(starts_block) {
+        Some(start) => {
+            // The block runs on over indented lines, list items and blank
+            // lines; blank lines and comments at its end belong to what follows
+            let continues =
+                |line: &str| line.starts_with([' ', '\t', '-']) || line.trim().is_empty();
+            let trails = |line: &str| line.trim().is_empty() || line.trim().starts_with('#');
+            let mut end = start
+                + 1
+                + lines[start + 1..]
+                    .iter()
+                    .take_while(|l| continues(l))
+                    .count();
+            while end > start + 1 && trails(lines[end - 1]) {
+                end -= 1;
+            }
+            lines[..start].concat() + &block + &lines[end..].concat()
+        }
+        None if existing.trim().is_empty() => block,
+        None if existing.ends_with('\n') => format!("{}{}", existing, block),
+        None => format!("{}\n{}", existing, block),
+    };
+
+    // Fall back to writing the whole document if the splice changed anything
+    // but the block
+    match Syntax::Yaml.parse(&content) {
+        Ok(written) if written == *root => Ok(Some(content)),
+        _ => Ok(None),
+    }
+}
+
+/// Follow a key path, returning None if any segment is missing
+fn get_path<'a>(root: &'a Value, key_path: &[&str]) -> Option<&'a Value> {
+    key_path.iter().try_fold(root, |value, key| value.get(*key))
+}
+
+/// Follow a key path, creating `default` at the end (and objects along the way)
+fn get_path_mut<'a>(
+    root: &'a mut Value,
+    key_path: &[&str],
+    default: Value,
+) -> Result<&'a mut Value, String> {
+    let mut current = root;
+    for (i, key) in key_path.iter().enumerate() {
+        let obj = current
+            .as_object_mut()
+            .ok_or_else(|| format!("Expected `{}` to be an object", key_path[..i].join(".")))?;
+        let fallback = if i + 1 == key_path.len() {
+            default.clone()
+        } else {
+            json!({})
+        };
+        current = obj.entry(key.to_string()).or_insert(fallback);
+    }
+    Ok(current)
+}
+
+/// Update an existing entry in place: fields the format renders are replaced,
+/// fields the format doesn't know about (e.g. Cline's `alwaysAllow`) are kept
+fn merge_entry(existing: Option<&Value>, rendered: Value, owned_fields: &[&str]) -> Value {
+    match (existing.and_then(|v| v.as_object()), rendered) {
+        (Some(existing), Value::Object(rendered)) => {
+            let mut merged = existing.clone();
+            for field in owned_fields {
+                merged.remove(*field);
+            }
+            merged.extend(rendered);
+            Value::Object(merged)
+        }
+        (_, rendered) => rendered,
+    }
+}
+
+fn string_array(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn string_map(value: Option<&Value>) -> HashMap<String, String> {
+    value
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// How a format spells a remote (SSE / streamable HTTP) server entry
+#[derive(Debug, Clone, Copy)]
+enum RemoteStyle {
+    /// `url` + `headers`, with the client inferring the transport
+    Url,
+    /// `serverUrl` + `headers` (Windsurf)
+    ServerUrl,
+    /// `httpUrl` for streamable HTTP and `url` for SSE (Gemini CLI)
+    GeminiUrl,
+    /// `type` + `url` + `headers`, using the client's names for each transport
+    Typed {
+        streamable_http: &'static str,
+        sse: &'static str,
+    },
+}
+
+/// Per-tool fields a format's entries carry
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ToolFields {
+    /// None; tool filters are only enforced by the proxy
+    None,
+    /// `alwaysAllow` and `disabled` (Cline)
+    AlwaysAllow,
+    /// `alwaysAllow`, `disabledTools` and `disabled` (Roo Code, Kilo Code)
+    DisabledTools,
+}
+
+impl ToolFields {
+    /// Read the per-tool fields of an entry, if it has any
+    fn read(self, value: &Value) -> Option<ToolAccess> {
+        if self == ToolFields::None {
+            return None;
+        }
+        let always_allow = value.get("alwaysAllow");
+        let disabled_tools = value
+            .get("disabledTools")
+            .filter(|_| self == ToolFields::DisabledTools);
+        let disabled = value.get("disabled");
+        if always_allow.is_none() && disabled_tools.is_none() && disabled.is_none() {
+            return None;
+        }
+
+        Some(ToolAccess {
+            allowed: always_allow.map(|v| string_array(Some(v))),
+            denied: string_array(disabled_tools),
+            disabled: disabled.and_then(Value::as_bool).unwrap_or(false),
+        })
+    }
+
+    /// Render per-tool fields into an entry. An `alwaysAllow` list is only
+    /// written for filters with an allowlist, so the client's own
+    /// auto-approvals survive a deny-only filter.
+    fn render(self, access: &ToolAccess, obj: &mut Map<String, Value>) {
+        if self == ToolFields::None {
+            return;
+        }
+        if let Some(ref allowed) = access.allowed {
+            obj.insert("alwaysAllow".to_string(), json!(allowed));
+        }
+        if self == ToolFields::DisabledTools && !access.denied.is_empty() {
+            obj.insert("disabledTools".to_string(), json!(access.denied));
+        }
+        if access.disabled {
+            obj.insert("disabled".to_string(), json!(true));
+        }
+    }
+
+    /// Fields only tool filters write, removed on update so they don't
+    /// outlive the filter that wrote them. `alwaysAllow` is not among them,
+    /// since it also holds the client's own auto-approvals.
+    fn owned_fields(self) -> &'static [&'static str] {
+        match self {
+            ToolFields::None => &[],
+            ToolFields::AlwaysAllow => &["disabled"],
+            ToolFields::DisabledTools => &["disabled", "disabledTools"],
+        }
+    }
+}
+
+/// Every field a stdio or remote entry may be rendered with. These are replaced
+/// as a unit on update, so switching a server's transport leaves no stale fields.
+const ENTRY_FIELDS: &[&str] = &[
+    "type",
+    "command",
+    "args",
+    "env",
+    "url",
+    "serverUrl",
+    "httpUrl",
+    "headers",
+];
+
+/// Work out a remote entry's transport from its `type` tag, falling back to the URL
+fn remote_transport(type_tag: Option<&str>, url: &str) -> TransportType {
+    match type_tag {
+        Some("sse") => TransportType::Sse,
+        Some("http")
+        | Some("streamable-http")
+        | Some("streamableHttp")
+        | Some("streamable_http") => TransportType::StreamableHttp,
+        _ if url.trim_end_matches('/').ends_with("/sse") => TransportType::Sse,
+        _ => TransportType::StreamableHttp,
+    }
+}
+
+/// Read a stdio (`command`/`args`/`env`) or remote (`url`/`headers`) entry object
+fn read_entry_fields(value: &Value, remote: RemoteStyle) -> Option<McpServerEntry> {
+    if let Some(command) = value.get("command").and_then(|c| c.as_str()) {
+        return Some(McpServerEntry {
+            command: command.to_string(),
+            args: string_array(value.get("args")),
+            env: string_map(value.get("env")),
+            ..Default::default()
+        });
+    }
+
+    let (url, transport) = match value.get("httpUrl").and_then(|u| u.as_str()) {
+        Some(url) => (url, TransportType::StreamableHttp),
+        None => {
+            let url = value
+                .get("url")
+                .or_else(|| value.get("serverUrl"))
+                .and_then(|u| u.as_str())?;
+            let type_tag = match remote {
+                // Gemini CLI reserves `url` for SSE
+                RemoteStyle::GeminiUrl => Some("sse"),
+                _ => value.get("type").and_then(|t| t.as_str()),
+            };
+            (url, remote_transport(type_tag, url))
+        }
+    };
+
+    Some(McpServerEntry {
+        transport,
+        url: Some(url.to_string()),
+        headers: string_map(value.get("headers")),
+        ..Default::default()
+    })
+}
+
+/// Render an entry's stdio or remote fields in the given remote style
+fn render_entry_fields(entry: &McpServerEntry, remote: RemoteStyle) -> Map<String, Value> {
+    let mut obj = Map::new();
+
+    if !entry.transport.is_remote() {
+        obj.insert("command".to_string(), json!(entry.command));
+        obj.insert("args".to_string(), json!(entry.args));
+        if !entry.env.is_empty() {
+            obj.insert("env".to_string(), json!(entry.env));
+        }
+        return obj;
+    }
+
+    let url = json!(entry.url.as_deref().unwrap_or_default());
+    match remote {
+        RemoteStyle::Url => {
+            obj.insert("url".to_string(), url);
+        }
+        RemoteStyle::ServerUrl => {
+            obj.insert("serverUrl".to_string(), url);
+        }
+        RemoteStyle::GeminiUrl => {
+            let key = match entry.transport {
+                TransportType::Sse => "url",
+                _ => "httpUrl",
+            };
+            obj.insert(key.to_string(), url);
+        }
+        RemoteStyle::Typed {
+            streamable_http,
+            sse,
+        } => {
+            let type_tag = match entry.transport {
+                TransportType::Sse => sse,
+                _ => streamable_http,
+            };
+            obj.insert("type".to_string(), json!(type_tag));
+            obj.insert("url".to_string(), url);
+        }
+    }
+    if !entry.headers.is_empty() {
+        obj.insert("headers".to_string(), json!(entry.headers));
+    }
+
+    obj
+}
+
+/// Shared read logic for formats that store servers as an object keyed by name
+fn read_object_entries(
+    syntax: Syntax,
+    content: &str,
+    key_path: &[&str],
+    read_entry: impl Fn(&Value) -> Option<McpServerEntry>,
+) -> Result<HashMap<String, McpServerEntry>, String> {
+    let root = syntax.parse(content)?;
+    let mut servers = HashMap::new();
+
+    if let Some(container) = get_path(&root, key_path).and_then(|v| v.as_object()) {
+        for (name, value) in container {
+            match read_entry(value) {
+                Some(entry) => {
+                    servers.insert(name.clone(), entry);
+                }
+                None => log::warn!("Skipping unsupported MCP server entry `{}`", name),
+            }
+        }
+    }
+
+    Ok(servers)
+}
+
+/// Shared merge logic for formats that store servers as an object keyed by name
+fn merge_object_entries(
+    syntax: Syntax,
+    existing: &str,
+    key_path: &[&str],
+    servers: &HashMap<String, McpServerEntry>,
+    remove: &[String],
+    owned_fields: &[&str],
+    render_entry: impl Fn(&McpServerEntry) -> Value,
+) -> Result<String, String> {
+    let mut root = syntax.parse(existing)?;
+
+    let container = get_path_mut(&mut root, key_path, json!({}))?
+        .as_object_mut()
+        .ok_or_else(|| format!("Expected `{}` to be an object", key_path.join(".")))?;
+
+    for key in remove {
+        container.remove(key);
+    }
+
+    let mut names: Vec<&String> = servers.keys().collect();
+    names.sort();
+    for name in names {
+        let merged = merge_entry(
+            container.get(name),
+            render_entry(&servers[name]),
+            owned_fields,
+        );
+        container.insert(name.clone(), merged);
+    }
+
+    syntax.write(existing, &root, key_path)
+}
+
+// ==================== Format implementations ====================
+
+/// Servers stored as an object keyed by name, each entry holding either
+/// `command`/`args`/`env` or a remote URL, optionally with a `type` tag
+pub struct ObjectFormat {
+    name: &'static str,
+    syntax: Syntax,
+    key_path: &'static [&'static str],
+    stdio_type: Option<&'static str>,
+    remote: RemoteStyle,
+    tool_fields: ToolFields,
+}
+
+impl ObjectFormat {
+    fn render_entry(&self, entry: &McpServerEntry) -> Value {
+        let mut obj = Map::new();
+        if let (Some(stdio_type), false) = (self.stdio_type, entry.transport.is_remote()) {
+            obj.insert("type".to_string(), json!(stdio_type));
+        }
+        obj.extend(render_entry_fields(entry, self.remote));
+        if let Some(ref tools) = entry.tools {
+            self.tool_fields.render(tools, &mut obj);
+        }
+        Value::Object(obj)
+    }
+}
+
+impl ClientConfigFormat for ObjectFormat {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn read_servers(&self, content: &str) -> Result<HashMap<String, McpServerEntry>, String> {
+        read_object_entries(self.syntax, content, self.key_path, |value| {
+            let mut entry = read_entry_fields(value, self.remote)?;
+            entry.tools = self.tool_fields.read(value);
+            Some(entry)
+        })
+    }
+
+    fn merge(
+        &self,
+        existing: &str,
+        servers: &HashMap<String, McpServerEntry>,
+        remove: &[String],
+    ) -> Result<String, String> {
+        let owned_fields: Vec<&str> = ENTRY_FIELDS
+            .iter()
+            .chain(self.tool_fields.owned_fields())
+            .copied()
+            .collect();
+        merge_object_entries(
+            self.syntax,
+            existing,
+            self.key_path,
+            servers,
+            remove,
+            &owned_fields,
+            |entry| self.render_entry(entry),
+        )
+    }
+}
+
+/// Servers stored as a list of entries that carry their own `name`
+pub struct NamedListFormat {
+    name: &'static str,
+    syntax: Syntax,
+    key_path: &'static [&'static str],
+    remote: RemoteStyle,
+}
+
+impl NamedListFormat {
+    fn entry_name(value: &Value) -> Option<&str> {
+        value.get("name").and_then(|n| n.as_str())
+    }
+}
+
+impl ClientConfigFormat for NamedListFormat {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn read_servers(&self, content: &str) -> Result<HashMap<String, McpServerEntry>, String> {
+        let root = self.syntax.parse(content)?;
+        let mut servers = HashMap::new();
+
+        if let Some(list) = get_path(&root, self.key_path).and_then(|v| v.as_array()) {
+            for value in list {
+                match (
+                    Self::entry_name(value),
+                    read_entry_fields(value, self.remote),
+                ) {
+                    (Some(name), Some(entry)) => {
+                        servers.insert(name.to_string(), entry);
+                    }
+                    _ => log::warn!("Skipping unsupported MCP server entry in {}", self.name),
+                }
+            }
+        }
+
+        Ok(servers)
+    }
+
+    fn merge(
+        &self,
+        existing: &str,
+        servers: &HashMap<String, McpServerEntry>,
+        remove: &[String],
+    ) -> Result<String, String> {
+        let mut root = self.syntax.parse(existing)?;
+
+        let list = get_path_mut(&mut root, self.key_path, json!([]))?
+            .as_array_mut()
+            .ok_or_else(|| format!("Expected `{}` to be a list", self.key_path.join(".")))?;
+
+        list.retain(|value| {
+            Self::entry_name(value).is_none_or(|name| !remove.iter().any(|r| r == name))
+        });
+
+        let mut names: Vec<&String> = servers.keys().collect();
+        names.sort();
+        for name in names {
+            let mut rendered = Map::new();
+            rendered.insert("name".to_string(), json!(name));
+            rendered.extend(render_entry_fields(&servers[name], self.remote));
+
+            match list
+                .iter_mut()
+                .find(|value| Self::entry_name(value) == Some(name.as_str()))
+            {
+                Some(slot) => {
+                    *slot = merge_entry(Some(slot), Value::Object(rendered), ENTRY_FIELDS)
+                }
+                None => list.push(Value::Object(rendered)),
+            }
+        }
+
+        self.syntax.write(existing, &root, self.key_path)
+    }
+}
+
+/// Zed `settings.json`: servers under `context_servers`, local ones tagged
+/// `"source": "custom"` and remote ones given as a bare `url`
+pub struct ZedFormat;
+
+impl ZedFormat {
+    const KEY_PATH: &'static [&'static str] = &["context_servers"];
+    const OWNED_FIELDS: &'static [&'static str] =
+        &["source", "command", "args", "env", "url", "headers"];
+
+    fn read_entry(value: &Value) -> Option<McpServerEntry> {
+        // Older Zed releases nest the command: {"command": {"path", "args", "env"}}
+        match value.get("command") {
+            Some(Value::Object(command)) => Some(McpServerEntry {
+                command: command.get("path")?.as_str()?.to_string(),
+                args: string_array(command.get("args")),
+                env: string_map(command.get("env")),
+                ..Default::default()
+            }),
+            _ => read_entry_fields(value, RemoteStyle::Url),
+        }
+    }
+
+    fn render_entry(entry: &McpServerEntry) -> Value {
+        let mut obj = Map::new();
+        if !entry.transport.is_remote() {
+            obj.insert("source".to_string(), json!("custom"));
+        }
+        obj.extend(render_entry_fields(entry, RemoteStyle::Url));
+        Value::Object(obj)
+    }
+}
+
+impl ClientConfigFormat for ZedFormat {
+    fn name(&self) -> &'static str {
+        "zed-json"
+    }
+
+    fn read_servers(&self, content: &str) -> Result<HashMap<String, McpServerEntry>, String> {
+        read_object_entries(Syntax::Json, content, Self::KEY_PATH, Self::read_entry)
+    }
+
+    fn merge(
+        &self,
+        existing: &str,
+        servers: &HashMap<String, McpServerEntry>,
+        remove: &[String],
+    ) -> Result<String, String> {
+        merge_object_entries(
+            Syntax::Json,
+            existing,
+            Self::KEY_PATH,
+            servers,
+            remove,
+            Self::OWNED_FIELDS,
+            Self::render_entry,
+        )
+    }
+}
+
+/// opencode: servers under `mcp`, either `"type": "local"` with the command and
+/// its args as one array, or `"type": "remote"` with a `url`
+pub struct OpencodeFormat;
+
+impl OpencodeFormat {
+    const KEY_PATH: &'static [&'static str] = &["mcp"];
+    const OWNED_FIELDS: &'static [&'static str] =
+        &["type", "command", "environment", "url", "headers"];
+
+    fn read_entry(value: &Value) -> Option<McpServerEntry> {
+        if value.get("type").and_then(|t| t.as_str()) == Some("remote") {
+            let url = value.get("url")?.as_str()?;
+            return Some(McpServerEntry {
+                transport: remote_transport(None, url),
+                url: Some(url.to_string()),
+                headers: string_map(value.get("headers")),
+                ..Default::default()
+            });
+        }
+
+        let mut command = string_array(value.get("command")).into_iter();
+        Some(McpServerEntry {
+            command: command.next()?,
+            args: command.collect(),
+            env: string_map(value.get("environment")),
+            ..Default::default()
+        })
+    }
+
+    fn render_entry(entry: &McpServerEntry) -> Value {
+        let mut obj = Map::new();
+
+        if entry.transport.is_remote() {
+            obj.insert("type".to_string(), json!("remote"));
+            obj.insert(
+                "url".to_string(),
+                json!(entry.url.as_deref().unwrap_or_default()),
+            );
+            if !entry.headers.is_empty() {
+                obj.insert("headers".to_string(), json!(entry.headers));
+            }
+        } else {
+            let mut command = vec![entry.command.clone()];
+            command.extend(entry.args.iter().cloned());
+
+            obj.insert("type".to_string(), json!("local"));
+            obj.insert("command".to_string(), json!(command));
+            if !entry.env.is_empty() {
+                obj.insert("environment".to_string(), json!(entry.env));
+            }
+        }
+        obj.insert("enabled".to_string(), json!(true));
+        Value::Object(obj)
+    }
+}
+
+impl ClientConfigFormat for OpencodeFormat {
+    fn name(&self) -> &'static str {
+        "opencode-json"
+    }
+
+    fn read_servers(&self, content: &str) -> Result<HashMap<String, McpServerEntry>, String> {
+        read_object_entries(Syntax::Json, content, Self::KEY_PATH, Self::read_entry)
+    }
+
+    fn merge(
+        &self,
+        existing: &str,
+        servers: &HashMap<String, McpServerEntry>,
+        remove: &[String],
+    ) -> Result<String, String> {
+        merge_object_entries(
+            Syntax::Json,
+            existing,
+            Self::KEY_PATH,
+            servers,
+            remove,
+            Self::OWNED_FIELDS,
+            Self::render_entry,
+        )
+    }
+}
+
+// ==================== TOML formats ====================
+
+/// Parse TOML file content, preserving comments and formatting for write-back
+fn parse_toml_document(content: &str) -> Result<toml_edit::DocumentMut, String> {
+    content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| format!("Failed to parse config file: {}", e))
+}
+
+/// Read a string-to-string TOML table (inline or a sub-table)
+fn toml_string_map(item: Option<&toml_edit::Item>) -> HashMap<String, String> {
+    item.and_then(|item| item.as_table_like())
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.to_string(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build an inline TOML table with sorted keys
+fn toml_inline_table(map: &HashMap<String, String>) -> toml_edit::InlineTable {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|k| (k.as_str(), map[k].as_str()))
+        .collect()
+}
+
+/// OpenAI Codex `config.toml`: one `[mcp_servers.<name>]` table per server,
+/// holding `command`/`args`/`env` or a streamable HTTP `url`/`http_headers`
+pub struct CodexTomlFormat;
+
+impl CodexTomlFormat {
+    const KEY: &'static str = "mcp_servers";
+
+    fn read_entry(table: &dyn toml_edit::TableLike) -> Option<McpServerEntry> {
+        let command = match table.get("command").and_then(|item| item.as_str()) {
+            Some(command) => command.to_string(),
+            None => {
+                let url = table.get("url")?.as_str()?;
+                return Some(McpServerEntry {
+                    transport: TransportType::StreamableHttp,
+                    url: Some(url.to_string()),
+                    headers: toml_string_map(table.get("http_headers")),
+                    ..Default::default()
+                });
+            }
+        };
+
+        let args = table
+            .get("args")
+            .and_then(|item| item.as_array())
+            .map(|array| {
+                array
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // `env` may be an inline table or a `[mcp_servers.<name>.env]` sub-table
+        let env = toml_string_map(table.get("env"));
+
+        Some(McpServerEntry {
+            command,
+            args,
+            env,
+            ..Default::default()
+        })
+    }
+
+    /// Write the entry's fields into a server table, keeping any other keys
+    /// (e.g. `startup_timeout_ms`) and their comments
+    fn write_entry(table: &mut dyn toml_edit::TableLike, entry: &McpServerEntry) {
+        if entry.transport.is_remote() {
+            for key in ["command", "args", "env"] {
+                table.remove(key);
+            }
+            table.insert(
+                "url",
+                toml_edit::value(entry.url.as_deref().unwrap_or_default()),
+            );
+            if entry.headers.is_empty() {
+                table.remove("http_headers");
+            } else {
+                table.insert(
+                    "http_headers",
+                    toml_edit::value(toml_inline_table(&entry.headers)),
+                );
+            }
+            return;
+        }
+
+        table.remove("url");
+        table.remove("http_headers");
+        table.insert("command", toml_edit::value(entry.command.as_str()));
+        table.insert(
+            "args",
+            toml_edit::value(
+                entry
+                    .args
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<toml_edit::Array>(),
+            ),
+        );
+
+        if entry.env.is_empty() {
+            table.remove("env");
+        } else {
+            table.insert("env", toml_edit::value(toml_inline_table(&entry.env)));
+        }
+    }
+}
+
+impl ClientConfigFormat for CodexTomlFormat {
+    fn name(&self) -> &'static str {
+        "codex-toml"
+    }
+
+    fn read_servers(&self, content: &str) -> Result<HashMap<String, McpServerEntry>, String> {
+        let doc = parse_toml_document(content)?;
+        let mut servers = HashMap::new();
+
+        if let Some(container) = doc.get(Self::KEY).and_then(|item| item.as_table_like()) {
+            for (name, item) in container.iter() {
+                match item.as_table_like().and_then(Self::read_entry) {
+                    Some(entry) => {
+                        servers.insert(name.to_string(), entry);
+                    }
+                    None => log::warn!("Skipping unsupported MCP server entry `{}`", name),
+                }
+            }
+        }
+
+        Ok(servers)
+    }
+
+    fn merge(
+        &self,
+        existing: &str,
+        servers: &HashMap<String, McpServerEntry>,
+        remove: &[String],
+    ) -> Result<String, String> {
+        let mut doc = parse_toml_document(existing)?;
+
+        let container = doc
+            .entry(Self::KEY)
+            .or_insert_with(|| {
+                // Only emit the `[mcp_servers.<name>]` headers, not a bare `[mcp_servers]`
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(true);
+                toml_edit::Item::Table(table)
+            })
+            .as_table_like_mut()
+            .ok_or_else(|| format!("Expected `{}` to be a table", Self::KEY))?;
+
+        for key in remove {
+            container.remove(key);
+        }
+
+        let mut names: Vec<&String> = servers.keys().collect();
+        names.sort();
+        for name in names {
+            let entry = &servers[name];
+            match container
+                .get_mut(name)
+                .and_then(|item| item.as_table_like_mut())
+            {
+                Some(table) => Self::write_entry(table, entry),
+                None => {
+                    let mut table = toml_edit::Table::new();
+                    Self::write_entry(&mut table, entry);
+                    container.insert(name, toml_edit::Item::Table(table));
+                }
+            }
+        }
+
+        Ok(doc.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str, args: &[&str]) -> McpServerEntry {
+        McpServerEntry {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn remote(transport: TransportType, url: &str) -> McpServerEntry {
+        McpServerEntry {
+            transport,
+            url: Some(url.to_string()),
+            headers: HashMap::from([("Authorization".to_string(), "Bearer t".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_preserves_other_settings() {
+        let existing = r#"{"theme": "dark", "mcpServers": {"old": {"command": "x", "args": []}}}"#;
+        let servers = HashMap::from([("fs".to_string(), entry("npx", &["-y", "fs"]))]);
+
+        let merged = MCP_SERVERS_JSON
+            .merge(existing, &servers, &["old".to_string()])
+            .unwrap();
+        let value: Value = serde_json::from_str(&merged).unwrap();
+
+        assert_eq!(value["theme"], "dark");
+        assert!(value["mcpServers"].get("old").is_none());
+        assert_eq!(value["mcpServers"]["fs"]["command"], "npx");
+    }
+
+    #[test]
+    fn test_merge_keeps_client_specific_fields() {
+        let existing =
+            r#"{"mcpServers": {"fs": {"command": "old", "args": [], "alwaysAllow": ["read"]}}}"#;
+        let servers = HashMap::from([("fs".to_string(), entry("npx", &[]))]);
+
+        let merged = MCP_SERVERS_JSON.merge(existing, &servers, &[]).unwrap();
+        let value: Value = serde_json::from_str(&merged).unwrap();
+
+        assert_eq!(value["mcpServers"]["fs"]["command"], "npx");
+        assert_eq!(value["mcpServers"]["fs"]["alwaysAllow"][0], "read");
+    }
+
+    #[test]
+    fn test_tool_access_rendered_into_native_fields() {
+        let existing = r#"{"mcpServers": {"fs": {"command": "old", "args": [], "alwaysAllow": ["read"], "timeout": 60}}}"#;
+        let mut fs = entry("npx", &[]);
+        fs.tools = Some(ToolAccess {
+            allowed: None,
+            denied: vec!["delete_file".to_string()],
+            disabled: false,
+        });
+        let servers = HashMap::from([("fs".to_string(), fs)]);
+
+        let merged = ROO_CODE_JSON.merge(existing, &servers, &[]).unwrap();
+        let value: Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(value["mcpServers"]["fs"]["disabledTools"][0], "delete_file");
+        assert_eq!(value["mcpServers"]["fs"]["alwaysAllow"][0], "read");
+        assert_eq!(value["mcpServers"]["fs"]["timeout"], 60);
+        let read = ROO_CODE_JSON.read_servers(&merged).unwrap();
+        assert_eq!(
+            read["fs"].tools.as_ref().unwrap().denied,
+            vec!["delete_file"]
+        );
+        assert!(!merged.contains("disabled\""));
+
+        // Once the filter is gone, so are the fields it wrote
+        let mut disabled = entry("npx", &[]);
+        disabled.tools = Some(ToolAccess {
+            allowed: Some(Vec::new()),
+            denied: Vec::new(),
+            disabled: true,
+        });
+        let servers = HashMap::from([("fs".to_string(), disabled)]);
+        let merged = ROO_CODE_JSON.merge(&merged, &servers, &[]).unwrap();
+        let value: Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(value["mcpServers"]["fs"]["disabled"], true);
+        let servers = HashMap::from([("fs".to_string(), entry("npx", &[]))]);
+        let merged = ROO_CODE_JSON.merge(&merged, &servers, &[]).unwrap();
+        assert!(!merged.contains("disabled"));
+
+        // Cline has no per-tool denial, and other clients no tool fields at all
+        let merged = CLINE_JSON.merge(existing, &servers, &[]).unwrap();
+        assert!(!merged.contains("disabledTools"));
+        let merged = MCP_SERVERS_JSON.merge(existing, &servers, &[]).unwrap();
+        assert!(!merged.contains("disabled"));
+    }
+
+    #[test]
+    fn test_client_shapes_round_trip() {
+        let servers = HashMap::from([("fs".to_string(), entry("npx", &["-y", "fs"]))]);
+
+        for client_type in [
+            ClientType::Vscode,
+            ClientType::Zed,
+            ClientType::Opencode,
+            ClientType::Continue,
+            ClientType::Augment,
+            ClientType::Crush,
+        ] {
+            let path = Path::new("mcp.json");
+            let format = format_for_client(&client_type, path);
+            let rendered = format.render(&servers).unwrap();
+            let read = format.read_servers(&rendered).unwrap();
+            assert_eq!(read["fs"].command, "npx", "{}", format.name());
+            assert_eq!(read["fs"].args, vec!["-y", "fs"], "{}", format.name());
+            assert_eq!(detect_format(path, &rendered).name(), format.name());
+        }
+    }
+
+    #[test]
+    fn test_read_legacy_zed_entry() {
+        let content =
+            r#"{"context_servers": {"fs": {"command": {"path": "npx", "args": ["fs"]}}}}"#;
+        let servers = ZED_JSON.read_servers(content).unwrap();
+        assert_eq!(servers["fs"].command, "npx");
+        assert_eq!(servers["fs"].args, vec!["fs"]);
+    }
+
+    #[test]
+    fn test_codex_toml_merge_preserves_comments() {
+        let existing = r#"# Codex settings
+model = "o3" # default model
+
+[mcp_servers.old]
+command = "old"
+args = []
+
+[mcp_servers.fs]
+command = "node"
+args = ["old.js"]
+startup_timeout_ms = 20000
+"#;
+        let mut fs_entry = entry("npx", &["-y", "fs"]);
+        fs_entry.env.insert("TOKEN".to_string(), "abc".to_string());
+        let servers = HashMap::from([("fs".to_string(), fs_entry)]);
+
+        let merged = CODEX_TOML
+            .merge(existing, &servers, &["old".to_string()])
+            .unwrap();
+
+        assert!(merged.contains("# Codex settings"));
+        assert!(merged.contains("model = \"o3\" # default model"));
+        assert!(merged.contains("startup_timeout_ms = 20000"));
+        assert!(!merged.contains("[mcp_servers.old]"));
+
+        let read = CODEX_TOML.read_servers(&merged).unwrap();
+        assert_eq!(read["fs"].command, "npx");
+        assert_eq!(read["fs"].args, vec!["-y", "fs"]);
+        assert_eq!(read["fs"].env["TOKEN"], "abc");
+    }
+
+    #[test]
+    fn test_codex_toml_render_and_env_subtable() {
+        let servers = HashMap::from([("fs".to_string(), entry("npx", &["fs"]))]);
+        let rendered = CODEX_TOML.render(&servers).unwrap();
+        assert!(rendered.contains("[mcp_servers.fs]"));
+        assert!(!rendered.contains("[mcp_servers]\n"));
+
+        let content = "[mcp_servers.gh]\ncommand = \"gh-mcp\"\n\n[mcp_servers.gh.env]\nGITHUB_TOKEN = \"t\"\n";
+        let read = CODEX_TOML.read_servers(content).unwrap();
+        assert_eq!(read["gh"].env["GITHUB_TOKEN"], "t");
+        assert!(read["gh"].args.is_empty());
+    }
+
+    #[test]
+    fn test_continue_yaml_merge_round_trips_other_settings() {
+        let existing = r#"# Local config
+name: Local Assistant
+version: 1.0.0
+schema: v1
+models:
+  - name: Claude # the default model
+    provider: anthropic
+
+# Servers managed by MCP Hub
+mcpServers:
+  - name: sqlite
+    command: uvx
+    args:
+      - mcp-server-sqlite
+    connectionTimeout: 5000
+"#;
+        let path = Path::new("config.yaml");
+        let format = format_for_client(&ClientType::Continue, path);
+        assert_eq!(format.name(), "continue-yaml");
+        assert_eq!(detect_format(path, existing).name(), "continue-yaml");
+
+        let read = format.read_servers(existing).unwrap();
+        assert_eq!(read["sqlite"].command, "uvx");
+
+        let servers = HashMap::from([
+            (
+                "sqlite".to_string(),
+                entry("uvx", &["mcp-server-sqlite", "--db", "x.db"]),
+            ),
+            ("fs".to_string(), entry("npx", &["fs"])),
+        ]);
+        let merged = format.merge(existing, &servers, &[]).unwrap();
+        let value: Value = serde_yaml_ng::from_str(&merged).unwrap();
+
+        assert_eq!(value["name"], "Local Assistant");
+        assert_eq!(value["models"][0]["provider"], "anthropic");
+        assert_eq!(value["mcpServers"][0]["name"], "sqlite");
+        assert_eq!(value["mcpServers"][0]["connectionTimeout"], 5000);
+        assert_eq!(value["mcpServers"][0]["args"][2], "x.db");
+        assert_eq!(value["mcpServers"][1]["name"], "fs");
+
+        // Comments outside the servers block survive
+        assert!(merged.starts_with("# Local config\nname: Local Assistant\n"));
+        assert!(merged.contains("  - name: Claude # the default model\n"));
+        assert!(merged.contains("\n# Servers managed by MCP Hub\nmcpServers:\n"));
+    }
+
+    #[test]
+    fn test_remote_entries_round_trip_per_client() {
+        let servers = HashMap::from([
+            (
+                "api".to_string(),
+                remote(TransportType::StreamableHttp, "https://mcp.example.com/mcp"),
+            ),
+            (
+                "events".to_string(),
+                remote(TransportType::Sse, "https://mcp.example.com/sse"),
+            ),
+        ]);
+
+        for client_type in [
+            ClientType::ClaudeDesktop,
+            ClientType::ClaudeCode,
+            ClientType::Windsurf,
+            ClientType::GeminiCli,
+            ClientType::Cline,
+            ClientType::RooCode,
+            ClientType::Vscode,
+            ClientType::Zed,
+            ClientType::Opencode,
+            ClientType::Continue,
+            ClientType::OpenaiCodex,
+        ] {
+            let path = Path::new("config");
+            let format = format_for_client(&client_type, path);
+            let read = format
+                .read_servers(&format.render(&servers).unwrap())
+                .unwrap();
+
+            assert_eq!(read["api"], servers["api"], "{}", format.name());
+            // Codex only speaks streamable HTTP to remote servers
+            if client_type != ClientType::OpenaiCodex {
+                assert_eq!(read["events"], servers["events"], "{}", format.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_remote_spellings() {
+        let content = r#"{"mcpServers": {
+            "a": {"serverUrl": "https://a.example.com/sse"},
+            "b": {"httpUrl": "https://b.example.com/mcp"},
+            "c": {"type": "http", "url": "https://c.example.com/mcp"}
+        }}"#;
+        let servers = MCP_SERVERS_JSON.read_servers(content).unwrap();
+        assert_eq!(servers["a"].transport, TransportType::Sse);
+        assert_eq!(servers["b"].transport, TransportType::StreamableHttp);
+        assert_eq!(
+            servers["c"].url.as_deref(),
+            Some("https://c.example.com/mcp")
+        );
+    }
+
+    #[test]
+    fn test_switching_transport_drops_stale_fields() {
+        let existing =
+            r#"{"mcpServers": {"api": {"command": "npx", "args": ["api"], "disabled": false}}}"#;
+        let servers = HashMap::from([(
+            "api".to_string(),
+            remote(TransportType::StreamableHttp, "https://mcp.example.com/mcp"),
+        )]);
+
+        let merged = WINDSURF_JSON.merge(existing, &servers, &[]).unwrap();
+        let value: Value = serde_json::from_str(&merged).unwrap();
+
+        assert!(value["mcpServers"]["api"].get("command").is_none());
+        assert_eq!(
+            value["mcpServers"]["api"]["serverUrl"],
+            "https://mcp.example.com/mcp"
+        );
+        assert_eq!(value["mcpServers"]["api"]["disabled"], false);
+    }
+}
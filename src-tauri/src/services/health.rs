@@ -0,0 +1,204 @@
+//! Server health checks over the MCP protocol.
+//!
+//! A check starts the server, performs the `initialize` handshake and lists
+//! its tools, so it proves the server actually speaks MCP rather than merely
+//! that its command exists. Remote servers are only checked for reachability:
+//! the host of their URL has to accept a connection.
+
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use crate::models::{HealthStatus, McpServer, ServerHealth};
+use crate::services::mcp_client::StdioClient;
+
+/// Check a server by performing the MCP handshake, giving up after `timeout`.
+/// The server process is always shut down before this returns.
+pub async fn check_server_health(server: &McpServer, timeout: Duration) -> ServerHealth {
+    if server.transport.is_remote() {
+        return check_reachable(server, timeout).await;
+    }
+
+    let started = Instant::now();
+    let client = match StdioClient::spawn(server) {
+        Ok(client) => client,
+        Err(e) => return ServerHealth::new(server.id.clone(), HealthStatus::Error, Some(e)),
+    };
+
+    let mut health = ServerHealth::new(server.id.clone(), HealthStatus::Healthy, None);
+    let result = tokio::time::timeout(timeout, probe(&client, started, &mut health)).await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            health.status = HealthStatus::Error;
+            health.error_message = Some(e);
+        }
+        Err(_) => {
+            health.status = HealthStatus::Error;
+            health.error_message = Some(match health.latency_ms {
+                Some(_) => format!("Server did not list its tools within {:?}", timeout),
+                None => format!("Server did not complete the handshake within {:?}", timeout),
+            });
+        }
+    }
+
+    let stderr = client.shutdown().await;
+    if !stderr.is_empty() {
+        health.stderr = Some(stderr);
+    }
+
+    health.last_checked = chrono::Utc::now();
+    health
+}
+
+/// Check that the host of a remote server's URL accepts a connection
+async fn check_reachable(server: &McpServer, timeout: Duration) -> ServerHealth {
+    let url = server.url.as_deref().unwrap_or_default();
+    let Some(address) = host_and_port(url) else {
+        return ServerHealth::new(
+            server.id.clone(),
+            HealthStatus::Error,
+            Some(format!("Invalid server URL: {}", url)),
+        );
+    };
+
+    let started = Instant::now();
+    match tokio::time::timeout(timeout, TcpStream::connect(&address)).await {
+        Ok(Ok(_)) => {
+            let mut health = ServerHealth::new(server.id.clone(), HealthStatus::Healthy, None);
+            health.latency_ms = Some(started.elapsed().as_millis() as u64);
+            health
+        }
+        Ok(Err(e)) => ServerHealth::new(
+            server.id.clone(),
+            HealthStatus::Error,
+            Some(format!("Failed to connect to {}: {}", address, e)),
+        ),
+        Err(_) => ServerHealth::new(
+            server.id.clone(),
+            HealthStatus::Error,
+            Some(format!(
+                "{} did not accept a connection within {:?}",
+                address, timeout
+            )),
+        ),
+    }
+}
+
+/// The `host:port` an http(s) URL connects to
+fn host_and_port(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let default_port = match scheme.to_ascii_lowercase().as_str() {
+        "http" => 80,
+        "https" => 443,
+        _ => return None,
+    };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    if host_port.is_empty() {
+        return None;
+    }
+
+    // A port follows the last colon, unless that colon is inside an IPv6 address
+    match host_port.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => {
+            port.parse::<u16>().ok()?;
+            Some(format!("{}:{}", host, port))
+        }
+        _ => Some(format!("{}:{}", host_port, default_port)),
+    }
+}
+
+/// Run the handshake, recording what the server reports in `health` as it
+/// arrives so a timeout still keeps the earlier results
+async fn probe(
+    client: &StdioClient,
+    started: Instant,
+    health: &mut ServerHealth,
+) -> Result<(), String> {
+    let init = client.initialize().await?;
+    health.latency_ms = Some(started.elapsed().as_millis() as u64);
+    health.protocol_version = Some(init.protocol_version);
+    health.server_info = init.server_info;
+
+    let has_tools = init.capabilities.get("tools").is_some();
+    health.capabilities = Some(init.capabilities);
+
+    if has_tools {
+        let tools = client.list_all("tools/list", "tools").await?;
+        health.tool_count = Some(tools.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// A shell script that answers `initialize` and `tools/list`
+    fn fake_server() -> McpServer {
+        let script = r#"
+            read line
+            echo 'booting' >&2
+            echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"1.0.0"}}}'
+            read line
+            read line
+            echo '{"jsonrpc":"2.0","method":"notifications/message","params":{}}'
+            echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"a"},{"name":"b"}]}}'
+            read line
+        "#;
+        McpServer::new(
+            "fake".to_string(),
+            "sh".to_string(),
+            vec!["-c".to_string(), script.to_string()],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_check_server_health_handshake() {
+        let health = check_server_health(&fake_server(), Duration::from_secs(10)).await;
+
+        assert_eq!(
+            health.status,
+            HealthStatus::Healthy,
+            "{:?}",
+            health.error_message
+        );
+        assert_eq!(health.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(health.server_info.unwrap().name, "fake");
+        assert_eq!(health.tool_count, Some(2));
+        assert!(health.latency_ms.is_some());
+        assert_eq!(health.stderr.as_deref(), Some("booting"));
+    }
+
+    #[test]
+    fn test_host_and_port() {
+        assert_eq!(
+            host_and_port("https://mcp.example.com/sse").as_deref(),
+            Some("mcp.example.com:443")
+        );
+        assert_eq!(
+            host_and_port("http://user:pw@localhost:8080?x=1").as_deref(),
+            Some("localhost:8080")
+        );
+        assert_eq!(
+            host_and_port("http://[::1]/mcp").as_deref(),
+            Some("[::1]:80")
+        );
+        assert_eq!(host_and_port("ws://localhost"), None);
+        assert_eq!(host_and_port("https:///mcp"), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_server_health_times_out() {
+        let server = McpServer::new(
+            "silent".to_string(),
+            "sleep".to_string(),
+            vec!["30".to_string()],
+        );
+        let health = check_server_health(&server, Duration::from_millis(200)).await;
+
+        assert_eq!(health.status, HealthStatus::Error);
+        assert!(health.error_message.unwrap().contains("handshake"));
+    }
+}
@@ -0,0 +1,266 @@
+//! Background health monitoring.
+//!
+//! Every few minutes each server enabled on at least one instance is checked
+//! (stdio servers with an MCP handshake, remote ones for reachability) and
+//! the result is stored as history. Servers that need secrets are skipped
+//! while the vault is locked, since they could only fail. When a
+//! server's status changes, [`HEALTH_CHANGED_EVENT`] is emitted to the
+//! frontend, and a desktop notification is shown when a healthy server starts
+//! failing. History older than the retention setting is pruned.
+
+use chrono::{Duration as ChronoDuration, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db::Database;
+use crate::error::{HubError, HubResult};
+use crate::models::{
+    AppSettings, HealthChangeEvent, HealthCheck, HealthStats, HealthStatus, McpServer, ServerHealth,
+};
+use crate::services::{config, credentials, health, inheritance};
+
+/// Event emitted when a server's health status changes
+pub const HEALTH_CHANGED_EVENT: &str = "server-health-changed";
+
+/// Wait before the first pass, so checks don't slow down app startup
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// How often the settings are re-read while the monitor is turned off
+const SETTINGS_POLL: Duration = Duration::from_secs(60);
+
+/// Check servers in the background for as long as the app runs
+pub fn start(app: AppHandle, db: Database) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let settings = match config::load_settings(&db) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    log::error!("Failed to load settings for health monitor: {}", e);
+                    AppSettings::default()
+                }
+            };
+
+            let wait = if settings.health_monitor_enabled {
+                run_checks(&app, &db, &settings).await;
+                Duration::from_secs(u64::from(settings.health_check_interval_mins.max(1)) * 60)
+            } else {
+                SETTINGS_POLL
+            };
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+/// Check every server in use, one at a time, then prune old history
+async fn run_checks(app: &AppHandle, db: &Database, settings: &AppSettings) {
+    let servers = db.get_servers_in_use().and_then(|in_use| {
+        let all = db.get_all_servers()?;
+        Ok(in_use
+            .iter()
+            .filter_map(|server| match inheritance::resolve(server, &all) {
+                Ok(server) => Some(server),
+                Err(e) => {
+                    log::warn!("Not checking {}: {}", server.name, e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>())
+    });
+    let servers = match servers {
+        Ok(servers) => servers,
+        Err(e) => {
+            log::error!("Failed to load servers for health monitor: {}", e);
+            return;
+        }
+    };
+
+    let timeout = Duration::from_secs(settings.health_check_timeout_secs);
+    let secrets_available = credentials::secrets_available();
+    for server in &servers {
+        if !secrets_available && !credentials::referenced_secrets(&server.env).is_empty() {
+            log::debug!("Not checking {} while the vault is locked", server.name);
+            continue;
+        }
+        let result = health::check_server_health(server, timeout).await;
+        if let Err(e) = report(app, db, server, &result) {
+            log::error!("Failed to record health of {}: {}", server.name, e);
+        }
+    }
+
+    if let Err(e) = prune_history(db, settings) {
+        log::error!("Failed to prune health history: {}", e);
+    }
+}
+
+/// Store a health check and announce it if the server's status changed.
+/// Checks without a verdict are not stored.
+pub fn report(
+    app: &AppHandle,
+    db: &Database,
+    server: &McpServer,
+    result: &ServerHealth,
+) -> HubResult<()> {
+    if result.status == HealthStatus::Unknown {
+        return Ok(());
+    }
+
+    let previous = db
+        .get_health_history(&server.id, 1)?
+        .into_iter()
+        .next()
+        .map(|check| check.status)
+        .unwrap_or(HealthStatus::Unknown);
+
+    db.record_health_check(&HealthCheck {
+        server_id: server.id.clone(),
+        status: result.status,
+        error_message: result.error_message.clone(),
+        latency_ms: result.latency_ms,
+        checked_at: result.last_checked,
+    })?;
+
+    if previous == result.status {
+        return Ok(());
+    }
+
+    log::info!(
+        "{} is now {} (was {})",
+        server.name,
+        result.status.as_str(),
+        previous.as_str()
+    );
+    let event = HealthChangeEvent {
+        server_id: server.id.clone(),
+        server_name: server.name.clone(),
+        previous,
+        current: result.status,
+        error_message: result.error_message.clone(),
+        checked_at: result.last_checked,
+    };
+    app.emit(HEALTH_CHANGED_EVENT, event)
+        .map_err(|e| HubError::Other(e.to_string()))?;
+
+    if previous == HealthStatus::Healthy && result.status == HealthStatus::Error {
+        let shown = app
+            .notification()
+            .builder()
+            .title(format!("{} stopped working", server.name))
+            .body(
+                result
+                    .error_message
+                    .as_deref()
+                    .unwrap_or("The server failed its health check"),
+            )
+            .show();
+        if let Err(e) = shown {
+            log::warn!("Failed to show notification: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Delete health checks older than the retention period
+pub fn prune_history(db: &Database, settings: &AppSettings) -> HubResult<usize> {
+    if settings.health_history_days == 0 {
+        return Ok(0);
+    }
+    let cutoff = Utc::now() - ChronoDuration::days(i64::from(settings.health_history_days));
+    db.prune_health_checks(cutoff)
+}
+
+/// Uptime and flakiness of every server with stored health checks
+pub fn health_stats(db: &Database) -> HubResult<Vec<HealthStats>> {
+    let mut by_server: BTreeMap<String, Vec<HealthCheck>> = BTreeMap::new();
+    for check in db.get_all_health_checks()? {
+        by_server
+            .entry(check.server_id.clone())
+            .or_default()
+            .push(check);
+    }
+
+    Ok(by_server
+        .into_iter()
+        .map(|(server_id, checks)| compute_stats(server_id, &checks))
+        .collect())
+}
+
+/// Summarize a server's health checks, given oldest first
+pub fn compute_stats(server_id: String, checks: &[HealthCheck]) -> HealthStats {
+    let checks: Vec<&HealthCheck> = checks
+        .iter()
+        .filter(|check| check.status != HealthStatus::Unknown)
+        .collect();
+
+    let healthy = checks
+        .iter()
+        .filter(|check| check.status == HealthStatus::Healthy)
+        .count();
+    let transitions = checks
+        .windows(2)
+        .filter(|pair| pair[0].status != pair[1].status)
+        .count();
+    let latencies: Vec<u64> = checks.iter().filter_map(|check| check.latency_ms).collect();
+    let last = checks.last();
+
+    HealthStats {
+        server_id,
+        checks: checks.len() as u32,
+        uptime: (!checks.is_empty()).then(|| healthy as f64 / checks.len() as f64),
+        flakiness: if checks.len() > 1 {
+            transitions as f64 / (checks.len() - 1) as f64
+        } else {
+            0.0
+        },
+        average_latency_ms: (!latencies.is_empty())
+            .then(|| latencies.iter().sum::<u64>() / latencies.len() as u64),
+        last_status: last.map(|check| check.status),
+        last_checked: last.map(|check| check.checked_at),
+        last_error: checks
+            .iter()
+            .rev()
+            .find_map(|check| check.error_message.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(status: HealthStatus, latency_ms: Option<u64>) -> HealthCheck {
+        HealthCheck {
+            server_id: "s".to_string(),
+            status,
+            error_message: (status == HealthStatus::Error).then(|| "boom".to_string()),
+            latency_ms,
+            checked_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_compute_stats() {
+        let checks = vec![
+            check(HealthStatus::Healthy, Some(100)),
+            check(HealthStatus::Error, None),
+            check(HealthStatus::Unknown, None),
+            check(HealthStatus::Healthy, Some(300)),
+            check(HealthStatus::Healthy, Some(200)),
+        ];
+
+        let stats = compute_stats("s".to_string(), &checks);
+        assert_eq!(stats.checks, 4);
+        assert_eq!(stats.uptime, Some(0.75));
+        // healthy -> error -> healthy -> healthy: 2 of 3 steps changed
+        assert!((stats.flakiness - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.average_latency_ms, Some(200));
+        assert_eq!(stats.last_status, Some(HealthStatus::Healthy));
+        assert_eq!(stats.last_error.as_deref(), Some("boom"));
+
+        let empty = compute_stats("s".to_string(), &[]);
+        assert_eq!(empty.uptime, None);
+        assert_eq!(empty.flakiness, 0.0);
+    }
+}
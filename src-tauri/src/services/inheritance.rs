@@ -0,0 +1,302 @@
+//! Servers that inherit from another server through `parent_id`.
+//!
+//! A server with a parent is resolved into the server clients actually run
+//! by resolving its parent first and then applying the server's own fields:
+//!
+//! - A server that sets neither a command nor a URL runs its parent's: it
+//!   takes the parent's transport, command and URL.
+//! - With [`ArgsMode::Replace`] the server's own args are used, or the
+//!   parent's if it has none. With [`ArgsMode::Append`] the server's args
+//!   follow the parent's.
+//! - Env and headers are the parent's, with the server's own values on top.
+//! - Tags are the parent's followed by the server's own.
+//! - Everything else, like the name and description, is the server's own.
+//!
+//! A chain of parents that leads back to a server it started from is an
+//! error. A parent that no longer exists ends the chain.
+
+use std::collections::HashMap;
+
+use crate::error::{HubError, HubResult};
+use crate::models::{ArgsMode, McpServer};
+
+/// The servers `server` inherits from, nearest first
+pub fn ancestors<'a>(
+    server: &'a McpServer,
+    servers: &'a [McpServer],
+) -> HubResult<Vec<&'a McpServer>> {
+    let mut chain: Vec<&McpServer> = Vec::new();
+    let mut next = server.parent_id.as_deref();
+    while let Some(parent_id) = next {
+        if parent_id == server.id || chain.iter().any(|s| s.id == parent_id) {
+            let mut path: Vec<&str> = std::iter::once(server)
+                .chain(chain.iter().copied())
+                .map(|s| s.name.as_str())
+                .collect();
+            let repeated = std::iter::once(server)
+                .chain(chain.iter().copied())
+                .find(|s| s.id == parent_id);
+            path.extend(repeated.map(|s| s.name.as_str()));
+            return Err(HubError::Validation(format!(
+                "{} inherits from itself: {}",
+                server.name,
+                path.join(" → ")
+            )));
+        }
+        let Some(parent) = servers.iter().find(|s| s.id == parent_id) else {
+            break;
+        };
+        chain.push(parent);
+        next = parent.parent_id.as_deref();
+    }
+    Ok(chain)
+}
+
+/// IDs of the servers that inherit from the server with `id`, directly or
+/// through other parents
+pub fn heirs(id: &str, servers: &[McpServer]) -> Vec<String> {
+    servers
+        .iter()
+        .filter(|server| {
+            ancestors(server, servers).is_ok_and(|chain| chain.iter().any(|s| s.id == id))
+        })
+        .map(|server| server.id.clone())
+        .collect()
+}
+
+/// The server clients run for `server`, with what it inherits from its
+/// parents filled in. `inherits_from` lists the parents, and `updated_at`
+/// is the latest change to the server or any of them.
+pub fn resolve(server: &McpServer, servers: &[McpServer]) -> HubResult<McpServer> {
+    let ancestors = ancestors(server, servers)?;
+
+    let mut resolved = match ancestors.split_last() {
+        Some((root, parents)) => parents
+            .iter()
+            .rev()
+            .chain(std::iter::once(&server))
+            .fold((*root).clone(), |parent, child| inherit(&parent, child)),
+        None => server.clone(),
+    };
+    resolved.inherits_from = ancestors.iter().map(|s| s.id.clone()).collect();
+    resolved.updated_at = ancestors
+        .iter()
+        .map(|s| s.updated_at)
+        .fold(server.updated_at, |latest, t| latest.max(t));
+    Ok(resolved)
+}
+
+/// Resolve every server of the registry. A server that can't be resolved is
+/// left out with a warning, so it doesn't take the others down with it.
+pub fn resolve_all(servers: &[McpServer]) -> Vec<McpServer> {
+    servers
+        .iter()
+        .filter_map(|server| match resolve(server, servers) {
+            Ok(resolved) => Some(resolved),
+            Err(e) => {
+                log::warn!("Skipping {}: {}", server.name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// `child` applied on top of its already resolved `parent`
+fn inherit(parent: &McpServer, child: &McpServer) -> McpServer {
+    let mut merged = child.clone();
+
+    if child.command.is_empty() && child.url.is_none() {
+        merged.transport = parent.transport;
+        merged.command = parent.command.clone();
+        merged.url = parent.url.clone();
+    }
+
+    merged.args = match child.args_mode {
+        ArgsMode::Replace if child.args.is_empty() => parent.args.clone(),
+        ArgsMode::Replace => child.args.clone(),
+        ArgsMode::Append => parent.args.iter().chain(&child.args).cloned().collect(),
+    };
+    merged.env = overlay(&parent.env, &child.env);
+    merged.headers = overlay(&parent.headers, &child.headers);
+
+    merged.tags = parent.tags.clone();
+    for tag in &child.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+
+    merged
+}
+
+/// Change `server` so it resolves to the transport, command, URL, args, env
+/// and headers of `wanted`. With a resolved `parent`, only what differs from
+/// the parent becomes the server's own, so the rest stays inherited. Env
+/// vars and headers the parent sets can't be taken away by the server.
+pub fn set_resolved(server: &mut McpServer, parent: Option<&McpServer>, wanted: &McpServer) {
+    let Some(parent) = parent else {
+        server.transport = wanted.transport;
+        server.command = wanted.command.clone();
+        server.args = wanted.args.clone();
+        server.env = wanted.env.clone();
+        server.url = wanted.url.clone();
+        server.headers = wanted.headers.clone();
+        return;
+    };
+
+    server.transport = wanted.transport;
+    if wanted.command == parent.command && wanted.url == parent.url {
+        server.command = String::new();
+        server.url = None;
+    } else {
+        server.command = wanted.command.clone();
+        server.url = wanted.url.clone();
+    }
+
+    match server.args_mode {
+        ArgsMode::Append if wanted.args.starts_with(&parent.args) => {
+            server.args = wanted.args[parent.args.len()..].to_vec();
+        }
+        _ if wanted.args == parent.args => server.args = Vec::new(),
+        _ => {
+            server.args_mode = ArgsMode::Replace;
+            server.args = wanted.args.clone();
+        }
+    }
+    server.env = difference(&wanted.env, &parent.env);
+    server.headers = difference(&wanted.headers, &parent.headers);
+}
+
+/// The entries of `map` that `base` doesn't have with the same value
+fn difference(
+    map: &HashMap<String, String>,
+    base: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    map.iter()
+        .filter(|(k, v)| base.get(*k) != Some(*v))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+fn overlay(
+    base: &HashMap<String, String>,
+    top: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut merged = base.clone();
+    merged.extend(top.iter().map(|(k, v)| (k.clone(), v.clone())));
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &str, command: &str, args: &[&str]) -> McpServer {
+        let args = args.iter().map(|a| a.to_string()).collect();
+        McpServer::new(name.to_string(), command.to_string(), args)
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut parent = server(
+            "GitHub",
+            "npx",
+            &["-y", "@modelcontextprotocol/server-github"],
+        );
+        parent
+            .env
+            .insert("GITHUB_TOKEN".into(), "keyring:GITHUB_TOKEN".into());
+        parent.env.insert("LOG_LEVEL".into(), "info".into());
+        parent.tags = vec!["git".into()];
+
+        let mut work = server("GitHub (work)", "", &[]);
+        work.parent_id = Some(parent.id.clone());
+        work.env.insert("LOG_LEVEL".into(), "debug".into());
+        work.tags = vec!["work".into(), "git".into()];
+
+        let mut verbose = server("GitHub (verbose)", "", &["--verbose"]);
+        verbose.parent_id = Some(work.id.clone());
+        verbose.args_mode = ArgsMode::Append;
+
+        let servers = vec![parent.clone(), work.clone(), verbose.clone()];
+
+        let resolved = resolve(&work, &servers).unwrap();
+        assert_eq!(resolved.command, "npx");
+        assert_eq!(resolved.args, parent.args);
+        assert_eq!(resolved.env["LOG_LEVEL"], "debug");
+        assert_eq!(resolved.env["GITHUB_TOKEN"], "keyring:GITHUB_TOKEN");
+        assert_eq!(resolved.tags, ["git", "work"]);
+        assert_eq!(resolved.inherits_from, [parent.id.clone()]);
+
+        let resolved = resolve(&verbose, &servers).unwrap();
+        assert_eq!(resolved.name, "GitHub (verbose)");
+        assert_eq!(
+            resolved.args,
+            ["-y", "@modelcontextprotocol/server-github", "--verbose"]
+        );
+        assert_eq!(resolved.env["LOG_LEVEL"], "debug");
+        assert_eq!(resolved.inherits_from, [work.id.clone(), parent.id.clone()]);
+        assert_eq!(
+            heirs(&parent.id, &servers),
+            [work.id.clone(), verbose.id.clone()]
+        );
+        assert_eq!(heirs(&verbose.id, &servers), Vec::<String>::new());
+
+        // A command of its own replaces the parent's, and so do its args
+        let mut local = server("GitHub (local)", "github-mcp", &["stdio"]);
+        local.parent_id = Some(parent.id.clone());
+        let resolved = resolve(&local, &servers).unwrap();
+        assert_eq!(resolved.command, "github-mcp");
+        assert_eq!(resolved.args, ["stdio"]);
+    }
+
+    #[test]
+    fn test_set_resolved() {
+        let mut parent = server("GitHub", "npx", &["-y", "server-github"]);
+        parent
+            .env
+            .insert("GITHUB_TOKEN".into(), "keyring:GITHUB_TOKEN".into());
+        let mut child = server("GitHub (verbose)", "", &["--verbose"]);
+        child.parent_id = Some(parent.id.clone());
+        child.args_mode = ArgsMode::Append;
+        let servers = vec![parent.clone(), child.clone()];
+
+        let mut wanted = resolve(&child, &servers).unwrap();
+        wanted.args.push("--debug".into());
+        wanted.env.insert("LOG_LEVEL".into(), "debug".into());
+        set_resolved(&mut child, Some(&parent), &wanted);
+
+        // Only what differs from the parent is the child's own
+        assert_eq!(child.command, "");
+        assert_eq!(child.args, ["--verbose", "--debug"]);
+        assert_eq!(child.env.len(), 1);
+        assert_eq!(child.env["LOG_LEVEL"], "debug");
+        let resolved = resolve(&child, &[parent.clone(), child.clone()]).unwrap();
+        assert_eq!(resolved.args, wanted.args);
+        assert_eq!(resolved.env, wanted.env);
+
+        // Args that no longer follow the parent's replace them
+        wanted.args = vec!["--stdio".into()];
+        set_resolved(&mut child, Some(&parent), &wanted);
+        assert_eq!(child.args_mode, ArgsMode::Replace);
+        assert_eq!(child.args, ["--stdio"]);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut a = server("A", "a", &[]);
+        let mut b = server("B", "b", &[]);
+        a.parent_id = Some(b.id.clone());
+        b.parent_id = Some(a.id.clone());
+        let servers = vec![a.clone(), b];
+
+        let error = resolve(&a, &servers).unwrap_err();
+        assert_eq!(error.kind(), "validation");
+        assert_eq!(error.to_string(), "A inherits from itself: A → B → A");
+
+        // Cycles are also found for a server not yet saved
+        let mut c = server("C", "c", &[]);
+        c.parent_id = Some(c.id.clone());
+        assert!(ancestors(&c, &[]).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Capability inventory: the tools, resources and prompts each server offers.
+//!
+//! A probe starts the server over stdio and lists everything it announces.
+//! Recording a probe compares it with the previous one, so the registry can
+//! show what a server update added, removed or changed.
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::error::{HubError, HubResult};
+use crate::models::{
+    CapabilityChange, CapabilityChangeKind, CapabilityInventory, CapabilityKind, McpServer,
+};
+use crate::services::mcp_client::StdioClient;
+
+/// Start a server and list its tools, resources and prompts, giving up after
+/// `timeout`. The returned inventory has no changes recorded yet.
+pub async fn probe_capabilities(
+    server: &McpServer,
+    timeout: Duration,
+) -> HubResult<CapabilityInventory> {
+    let client = StdioClient::spawn(server).map_err(HubError::Other)?;
+    let result = tokio::time::timeout(timeout, list_capabilities(&client, &server.id)).await;
+    let stderr = client.shutdown().await;
+
+    match result {
+        Ok(Ok(inventory)) => Ok(inventory),
+        Ok(Err(e)) if stderr.is_empty() => Err(HubError::Other(e)),
+        Ok(Err(e)) => Err(HubError::Other(format!("{}\n{}", e, stderr))),
+        Err(_) => Err(HubError::Other(format!(
+            "{} did not answer within {:?}",
+            server.name, timeout
+        ))),
+    }
+}
+
+async fn list_capabilities(
+    client: &StdioClient,
+    server_id: &str,
+) -> Result<CapabilityInventory, String> {
+    let init = client.initialize().await?;
+    let offers = |capability: &str| init.capabilities.get(capability).is_some();
+
+    let mut inventory = CapabilityInventory {
+        server_id: server_id.to_string(),
+        probed_at: Utc::now(),
+        tools: Vec::new(),
+        resources: Vec::new(),
+        prompts: Vec::new(),
+        changes: Vec::new(),
+    };
+    if offers("tools") {
+        inventory.tools = parse_items(client.list_all("tools/list", "tools").await?);
+    }
+    if offers("resources") {
+        inventory.resources = parse_items(client.list_all("resources/list", "resources").await?);
+    }
+    if offers("prompts") {
+        inventory.prompts = parse_items(client.list_all("prompts/list", "prompts").await?);
+    }
+
+    Ok(inventory)
+}
+
+/// Parse listed items, skipping any the server got wrong
+fn parse_items<T: DeserializeOwned>(items: Vec<Value>) -> Vec<T> {
+    items
+        .into_iter()
+        .filter_map(|item| match serde_json::from_value(item) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                log::debug!("Skipping malformed capability: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Store a probe, recording how it differs from the previous one
+pub fn record_inventory(
+    db: &Database,
+    mut inventory: CapabilityInventory,
+) -> HubResult<CapabilityInventory> {
+    let previous = db.get_capability_inventory(&inventory.server_id)?;
+
+    inventory.changes = match previous {
+        Some(ref previous) => diff_capabilities(previous, &inventory),
+        None => Vec::new(),
+    };
+
+    db.set_capability_inventory(&inventory)?;
+    Ok(inventory)
+}
+
+/// What changed between two probes of the same server
+pub fn diff_capabilities(
+    before: &CapabilityInventory,
+    after: &CapabilityInventory,
+) -> Vec<CapabilityChange> {
+    let mut changes = Vec::new();
+    diff_kind(
+        CapabilityKind::Tool,
+        before.tools.iter().map(|t| (t.name.as_str(), t)),
+        after.tools.iter().map(|t| (t.name.as_str(), t)),
+        &mut changes,
+    );
+    diff_kind(
+        CapabilityKind::Resource,
+        before.resources.iter().map(|r| (r.uri.as_str(), r)),
+        after.resources.iter().map(|r| (r.uri.as_str(), r)),
+        &mut changes,
+    );
+    diff_kind(
+        CapabilityKind::Prompt,
+        before.prompts.iter().map(|p| (p.name.as_str(), p)),
+        after.prompts.iter().map(|p| (p.name.as_str(), p)),
+        &mut changes,
+    );
+    changes
+}
+
+fn diff_kind<'a, T: PartialEq + 'a>(
+    kind: CapabilityKind,
+    before: impl Iterator<Item = (&'a str, &'a T)>,
+    after: impl Iterator<Item = (&'a str, &'a T)>,
+    changes: &mut Vec<CapabilityChange>,
+) {
+    let before: BTreeMap<&str, &T> = before.collect();
+    let after: BTreeMap<&str, &T> = after.collect();
+
+    let mut push = |name: &str, change| {
+        changes.push(CapabilityChange {
+            kind,
+            name: name.to_string(),
+            change,
+        })
+    };
+
+    for (name, item) in &after {
+        match before.get(name) {
+            None => push(name, CapabilityChangeKind::Added),
+            Some(old) if old != item => push(name, CapabilityChangeKind::Changed),
+            Some(_) => {}
+        }
+    }
+    for name in before.keys().filter(|name| !after.contains_key(*name)) {
+        push(name, CapabilityChangeKind::Removed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ServerTool;
+
+    fn tool(name: &str, description: &str) -> ServerTool {
+        ServerTool {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            input_schema: serde_json::json!({ "type": "object" }),
+        }
+    }
+
+    fn inventory(tools: Vec<ServerTool>) -> CapabilityInventory {
+        CapabilityInventory {
+            server_id: "s".to_string(),
+            probed_at: Utc::now(),
+            tools,
+            resources: Vec::new(),
+            prompts: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_capabilities() {
+        let before = inventory(vec![
+            tool("create_issue", "Create an issue"),
+            tool("list_issues", "List issues"),
+        ]);
+        let after = inventory(vec![
+            tool("create_issue", "Create a GitHub issue"),
+            tool("search_code", "Search code"),
+        ]);
+
+        let changes: Vec<(String, CapabilityChangeKind)> = diff_capabilities(&before, &after)
+            .into_iter()
+            .map(|c| (c.name, c.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("create_issue".to_string(), CapabilityChangeKind::Changed),
+                ("search_code".to_string(), CapabilityChangeKind::Added),
+                ("list_issues".to_string(), CapabilityChangeKind::Removed),
+            ]
+        );
+    }
+}
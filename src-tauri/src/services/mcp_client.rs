@@ -0,0 +1,349 @@
+//! Minimal MCP client for talking to stdio servers.
+//!
+//! Spawns a server with its args and env and exchanges newline-delimited
+//! JSON-RPC messages over stdin/stdout. A reader task matches responses to
+//! their requests, so several requests can be in flight at once. The child
+//! is killed when the client is dropped, so abandoning a client (e.g. on
+//! timeout) never leaks a process.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::models::{McpImplementation, McpServer};
+use crate::services::credentials;
+
+/// Protocol revision requested in `initialize`
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// How much of the server's stderr is kept
+const STDERR_LIMIT: usize = 16 * 1024;
+
+/// How long a server gets to exit after its stdin is closed
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// How long to wait for the rest of stderr once the server has exited
+const STDERR_DRAIN: Duration = Duration::from_millis(500);
+
+/// Result of the `initialize` request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: Value,
+    pub server_info: Option<McpImplementation>,
+    pub instructions: Option<String>,
+}
+
+type Writer = Arc<tokio::sync::Mutex<Option<ChildStdin>>>;
+/// Requests waiting for a response; `None` once the server closed its output
+type Pending = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Value>>>>>;
+
+/// A running stdio MCP server
+pub struct StdioClient {
+    child: tokio::sync::Mutex<Child>,
+    stdin: Writer,
+    pending: Pending,
+    next_id: AtomicU64,
+    notifications: Mutex<Option<mpsc::UnboundedReceiver<Value>>>,
+    reader_task: JoinHandle<()>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+    stderr_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl StdioClient {
+    /// Start a server process
+    pub fn spawn(server: &McpServer) -> Result<Self, String> {
+        if server.transport.is_remote() {
+            return Err(format!("{} is not a stdio server", server.name));
+        }
+
+        let env = credentials::resolve_env(server).map_err(|e| e.to_string())?;
+        let mut child = Command::new(&server.command)
+            .args(&server.args)
+            .envs(&env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", server.command, e))?;
+
+        let stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+        let mut stderr_pipe = child.stderr.take().ok_or("Failed to open server stderr")?;
+
+        let stdin: Writer = Arc::new(tokio::sync::Mutex::new(Some(stdin)));
+        let pending: Pending = Arc::new(Mutex::new(Some(HashMap::new())));
+        let (notification_sender, notifications) = mpsc::unbounded_channel();
+        let reader_task = tokio::spawn(read_messages(
+            stdout,
+            stdin.clone(),
+            pending.clone(),
+            notification_sender,
+        ));
+
+        // Keep the tail of stderr; servers log startup failures there
+        let stderr = Arc::new(Mutex::new(Vec::new()));
+        let stderr_buffer = stderr.clone();
+        let stderr_task = tokio::spawn(async move {
+            let mut chunk = [0u8; 4096];
+            while let Ok(n) = stderr_pipe.read(&mut chunk).await {
+                if n == 0 {
+                    break;
+                }
+                let mut buffer = stderr_buffer.lock().unwrap();
+                buffer.extend_from_slice(&chunk[..n]);
+                if buffer.len() > STDERR_LIMIT {
+                    let excess = buffer.len() - STDERR_LIMIT;
+                    buffer.drain(..excess);
+                }
+            }
+        });
+
+        Ok(Self {
+            child: tokio::sync::Mutex::new(child),
+            stdin,
+            pending,
+            next_id: AtomicU64::new(1),
+            notifications: Mutex::new(Some(notifications)),
+            reader_task,
+            stderr,
+            stderr_task: Mutex::new(Some(stderr_task)),
+        })
+    }
+
+    /// Perform the `initialize` handshake and send `notifications/initialized`
+    pub async fn initialize(&self) -> Result<InitializeResult, String> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "mcp-hub",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        let result: InitializeResult = serde_json::from_value(result)
+            .map_err(|e| format!("Invalid initialize result: {}", e))?;
+
+        self.notify("notifications/initialized", json!({})).await?;
+        Ok(result)
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let mut response = self.request_message(method, params).await?;
+
+        if let Some(error) = response.get("error") {
+            let text = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Err(format!("{} failed: {}", method, text));
+        }
+        Ok(response
+            .get_mut("result")
+            .map(Value::take)
+            .unwrap_or(Value::Null))
+    }
+
+    /// Send a request and return the whole response message, so a JSON-RPC
+    /// error can be passed on as it is
+    pub async fn request_message(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .as_mut()
+            .ok_or("Server closed its output")?
+            .insert(id, sender);
+
+        let sent = send(
+            &self.stdin,
+            &json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }),
+        )
+        .await;
+        if let Err(e) = sent {
+            if let Some(pending) = self.pending.lock().unwrap().as_mut() {
+                pending.remove(&id);
+            }
+            return Err(e);
+        }
+
+        receiver
+            .await
+            .map_err(|_| "Server closed its output".to_string())
+    }
+
+    /// Send a notification
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        send(
+            &self.stdin,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+            }),
+        )
+        .await
+    }
+
+    /// Whether the server has closed its output, e.g. because it exited
+    pub fn is_closed(&self) -> bool {
+        self.pending.lock().unwrap().is_none()
+    }
+
+    /// Notifications sent by the server. Can only be taken once; until then
+    /// they are buffered.
+    pub fn take_notifications(&self) -> Option<mpsc::UnboundedReceiver<Value>> {
+        self.notifications.lock().unwrap().take()
+    }
+
+    /// Collect every page of a paginated list request such as `tools/list`.
+    /// `field` is the array holding the items, e.g. `tools`.
+    pub async fn list_all(&self, method: &str, field: &str) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match cursor {
+                Some(ref cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let mut result = self.request(method, params).await?;
+
+            if let Some(Value::Array(page)) = result.get_mut(field).map(Value::take) {
+                items.extend(page);
+            }
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(String::from);
+            if cursor.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Close the server's stdin and wait briefly for it to exit, killing it
+    /// if it does not. Returns what the server wrote to stderr.
+    pub async fn shutdown(&self) -> String {
+        self.stdin.lock().await.take();
+        {
+            let mut child = self.child.lock().await;
+            if tokio::time::timeout(SHUTDOWN_GRACE, child.wait())
+                .await
+                .is_err()
+            {
+                let _ = child.kill().await;
+            }
+        }
+        self.reader_task.abort();
+
+        // Let the reader drain what is left; a grandchild may hold stderr open
+        let stderr_task = self.stderr_task.lock().unwrap().take();
+        if let Some(mut stderr_task) = stderr_task {
+            let _ = tokio::time::timeout(STDERR_DRAIN, &mut stderr_task).await;
+            stderr_task.abort();
+        }
+
+        let buffer = self.stderr.lock().unwrap();
+        String::from_utf8_lossy(&buffer).trim().to_string()
+    }
+}
+
+impl Drop for StdioClient {
+    fn drop(&mut self) {
+        self.reader_task.abort();
+    }
+}
+
+async fn send(stdin: &Writer, message: &Value) -> Result<(), String> {
+    let mut stdin = stdin.lock().await;
+    let stdin = stdin.as_mut().ok_or("Server stdin is closed")?;
+    let mut line = message.to_string();
+    line.push('\n');
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write to server: {}", e))?;
+    stdin
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to write to server: {}", e))
+}
+
+/// Dispatch messages from the server until it closes its output: responses
+/// go to the waiting request, notifications to the notification channel, and
+/// requests from the server are answered with "method not found"
+async fn read_messages(
+    stdout: ChildStdout,
+    stdin: Writer,
+    pending: Pending,
+    notifications: mpsc::UnboundedSender<Value>,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            // Some servers log to stdout; that is not a protocol message
+            Err(_) => {
+                log::debug!("Ignoring non-JSON server output: {}", line);
+                continue;
+            }
+        };
+
+        if message.get("method").is_some() {
+            match message.get("id") {
+                Some(request_id) => {
+                    let reply = json!({
+                        "jsonrpc": "2.0",
+                        "id": request_id,
+                        "error": { "code": -32601, "message": "Method not found" },
+                    });
+                    let _ = send(&stdin, &reply).await;
+                }
+                None => {
+                    let _ = notifications.send(message);
+                }
+            }
+            continue;
+        }
+
+        let waiting = message
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|id| pending.lock().unwrap().as_mut()?.remove(&id));
+        if let Some(waiting) = waiting {
+            let _ = waiting.send(message);
+        }
+    }
+
+    // Fail every request still waiting for a response, and any sent later
+    pending.lock().unwrap().take();
+}
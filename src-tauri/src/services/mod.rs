@@ -1,4 +1,19 @@
+pub mod backup;
 pub mod config;
 pub mod credentials;
 pub mod discovery;
+pub mod doctor;
+pub mod drift;
+pub mod formats;
+pub mod health;
+pub mod health_monitor;
+pub mod inheritance;
+pub mod inventory;
+pub mod mcp_client;
+pub mod proxy;
 pub mod registry;
+pub mod shell_env;
+pub mod sync;
+pub mod tool_filter;
+pub mod vault;
+pub mod watcher;
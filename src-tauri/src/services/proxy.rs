@@ -0,0 +1,586 @@
+//! Aggregating MCP proxy: MCP Hub serving an instance's servers as one server.
+//!
+//! The proxy speaks MCP over its own stdin/stdout, starts every stdio server
+//! enabled for the instance as a child, and merges their tools, prompts and
+//! resources under names and URIs prefixed with the server's config key
+//! (`github__create_issue`). Calls and reads are routed back to the child
+//! that owns them. Tools the instance's
+//! tool filters exclude are neither listed nor callable, and the instance's
+//! overrides apply as they do when syncing. The database is polled so
+//! enabling or disabling a server or editing its filter or override in MCP
+//! Hub takes effect right away, and the client is told to re-list.
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Stdout};
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+
+use crate::db::Database;
+use crate::models::{McpServer, ToolFilter};
+use crate::services::mcp_client::{StdioClient, PROTOCOL_VERSION};
+use crate::services::{config, inheritance, tool_filter};
+
+/// Separates the server prefix from a tool or prompt name or a resource URI
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+/// How often the database is checked for enabled servers
+const RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Notifications a child may send that are passed on to the client
+const LIST_CHANGED: [&str; 3] = [
+    "notifications/tools/list_changed",
+    "notifications/resources/list_changed",
+    "notifications/prompts/list_changed",
+];
+
+/// What a child was started from: the server's last edit, and the args and
+/// env, which the instance's override changes without editing the server
+#[derive(Debug, Clone, PartialEq)]
+struct Version {
+    updated_at: DateTime<Utc>,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+}
+
+impl Version {
+    fn of(server: &McpServer) -> Self {
+        Self {
+            updated_at: server.updated_at,
+            args: server.args.clone(),
+            env: server.env.clone(),
+        }
+    }
+}
+
+/// A running child server
+struct Upstream {
+    server_id: String,
+    version: Version,
+    capabilities: Value,
+    client: Arc<StdioClient>,
+    /// Which of the child's tools the instance may use
+    filter: ToolFilter,
+}
+
+impl Upstream {
+    fn offers(&self, capability: &str) -> bool {
+        self.capabilities.get(capability).is_some()
+    }
+}
+
+struct Proxy {
+    instance_id: String,
+    /// Running children by namespace prefix
+    upstreams: RwLock<BTreeMap<String, Upstream>>,
+    /// Servers that failed to start or crashed, with the version that failed,
+    /// so they are not restarted until their definition changes
+    failed: Mutex<HashMap<String, Version>>,
+    output: tokio::sync::Mutex<Stdout>,
+    timeout: Duration,
+}
+
+/// Serve the enabled servers of an instance over stdin/stdout until the
+/// client disconnects. `timeout` bounds starting a child and each request
+/// made to it while listing.
+pub async fn serve(db: Database, instance_id: &str, timeout: Duration) -> Result<(), String> {
+    let instance = db
+        .get_instance(instance_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Instance not found")?;
+
+    let proxy = Arc::new(Proxy {
+        instance_id: instance.id.clone(),
+        upstreams: RwLock::new(BTreeMap::new()),
+        failed: Mutex::new(HashMap::new()),
+        output: tokio::sync::Mutex::new(tokio::io::stdout()),
+        timeout,
+    });
+    proxy.reload(&db).await?;
+
+    let reloader = {
+        let proxy = proxy.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match proxy.reload(&db).await {
+                    Ok(true) => proxy.notify_list_changed().await,
+                    Ok(false) => {}
+                    Err(e) => log::error!("Failed to reload enabled servers: {}", e),
+                }
+            }
+        })
+    };
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| format!("Failed to read from client: {}", e))?
+    {
+        let message: Value = match serde_json::from_str(line.trim()) {
+            Ok(message) => message,
+            Err(e) => {
+                log::warn!("Ignoring invalid message from client: {}", e);
+                continue;
+            }
+        };
+
+        // Requests are answered concurrently; notifications need no reply
+        let (Some(id), Some(method)) = (
+            message.get("id").cloned(),
+            message
+                .get("method")
+                .and_then(Value::as_str)
+                .map(String::from),
+        ) else {
+            continue;
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let proxy = proxy.clone();
+        tokio::spawn(async move {
+            let response = match proxy.handle_request(&method, params).await {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+            };
+            proxy.write(&response).await;
+        });
+    }
+
+    reloader.abort();
+    let upstreams = std::mem::take(&mut *proxy.upstreams.write().await);
+    let mut shutdowns = JoinSet::new();
+    for upstream in upstreams.into_values() {
+        shutdowns.spawn(async move { upstream.client.shutdown().await });
+    }
+    while shutdowns.join_next().await.is_some() {}
+
+    Ok(())
+}
+
+impl Proxy {
+    /// Start children for newly enabled or edited servers and stop those
+    /// that were disabled, edited or crashed. Returns whether the set of
+    /// running children changed.
+    async fn reload(self: &Arc<Self>, db: &Database) -> Result<bool, String> {
+        let enabled = db
+            .get_enabled_servers_for_instance(&self.instance_id)
+            .map_err(|e| e.to_string())?;
+        let mut filters = db
+            .get_tool_filters(&self.instance_id)
+            .map_err(|e| e.to_string())?;
+        let overrides = db
+            .get_server_overrides(&self.instance_id)
+            .map_err(|e| e.to_string())?;
+        let mut wanted: BTreeMap<String, McpServer> = BTreeMap::new();
+        let servers = db.get_all_servers().map_err(|e| e.to_string())?;
+        for server in servers.iter().filter(|s| enabled.contains(&s.id)) {
+            let mut server = match inheritance::resolve(server, &servers) {
+                Ok(server) => server,
+                Err(e) => {
+                    log::warn!("Not proxying {}: {}", server.name, e);
+                    continue;
+                }
+            };
+            if server.transport.is_remote() {
+                log::debug!("Not proxying remote server {}", server.name);
+                continue;
+            }
+            // Run and prefix it as the instance's config does
+            let server_override = overrides.get(&server.id);
+            if let Some(server_override) = server_override {
+                server_override.apply(&mut server);
+            }
+            let name = server_override
+                .and_then(|o| o.alias.as_deref())
+                .unwrap_or(&server.name);
+            wanted
+                .entry(config::sanitize_server_name(name))
+                .or_insert(server);
+        }
+
+        let mut stopped = Vec::new();
+        let mut filters_changed = false;
+        {
+            let mut upstreams = self.upstreams.write().await;
+            let mut failed = self.failed.lock().unwrap();
+            failed.retain(|prefix, version| {
+                wanted.get(prefix).map(Version::of).as_ref() == Some(version)
+            });
+
+            let stale: Vec<String> = upstreams
+                .iter()
+                .filter(|(prefix, upstream)| match wanted.get(*prefix) {
+                    Some(server) => {
+                        server.id != upstream.server_id
+                            || Version::of(server) != upstream.version
+                            || upstream.client.is_closed()
+                    }
+                    None => true,
+                })
+                .map(|(prefix, _)| prefix.clone())
+                .collect();
+            for prefix in stale {
+                if let Some(upstream) = upstreams.remove(&prefix) {
+                    if upstream.client.is_closed() {
+                        log::warn!("Server {} exited", prefix);
+                        failed.insert(prefix.clone(), upstream.version.clone());
+                    }
+                    stopped.push(upstream);
+                }
+            }
+
+            // Filters apply to running children without a restart
+            for upstream in upstreams.values_mut() {
+                let filter = filters.remove(&upstream.server_id).unwrap_or_default();
+                if filter != upstream.filter {
+                    upstream.filter = filter;
+                    filters_changed = true;
+                }
+            }
+
+            wanted.retain(|prefix, _| {
+                !upstreams.contains_key(prefix) && !failed.contains_key(prefix)
+            });
+        }
+        let changed = !stopped.is_empty() || !wanted.is_empty() || filters_changed;
+
+        for upstream in stopped {
+            tokio::spawn(async move { upstream.client.shutdown().await });
+        }
+
+        let mut starting = JoinSet::new();
+        for (prefix, server) in wanted {
+            let timeout = self.timeout;
+            let filter = filters.remove(&server.id).unwrap_or_default();
+            starting.spawn(async move {
+                let result = start_upstream(&server, filter, timeout).await;
+                (prefix, server, result)
+            });
+        }
+        while let Some(Ok((prefix, server, result))) = starting.join_next().await {
+            match result {
+                Ok(upstream) => {
+                    self.forward_notifications(&upstream.client);
+                    self.upstreams.write().await.insert(prefix, upstream);
+                }
+                Err(e) => {
+                    log::error!("Failed to start {}: {}", server.name, e);
+                    self.failed
+                        .lock()
+                        .unwrap()
+                        .insert(prefix, Version::of(&server));
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Pass a child's list-changed notifications on to the client
+    fn forward_notifications(self: &Arc<Self>, client: &StdioClient) {
+        let Some(mut notifications) = client.take_notifications() else {
+            return;
+        };
+        let proxy = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                let method = notification.get("method").and_then(Value::as_str);
+                if !method.is_some_and(|m| LIST_CHANGED.contains(&m)) {
+                    continue;
+                }
+                match proxy.upgrade() {
+                    Some(proxy) => proxy.write(&notification).await,
+                    None => return,
+                }
+            }
+        });
+    }
+
+    async fn notify_list_changed(&self) {
+        for method in LIST_CHANGED {
+            self.write(&json!({ "jsonrpc": "2.0", "method": method }))
+                .await;
+        }
+    }
+
+    async fn write(&self, message: &Value) {
+        let mut line = message.to_string();
+        line.push('\n');
+        let mut output = self.output.lock().await;
+        if let Err(e) = output.write_all(line.as_bytes()).await {
+            log::error!("Failed to write to client: {}", e);
+            return;
+        }
+        let _ = output.flush().await;
+    }
+
+    async fn handle_request(&self, method: &str, params: Value) -> Result<Value, Value> {
+        match method {
+            "initialize" => {
+                let version = params
+                    .get("protocolVersion")
+                    .and_then(Value::as_str)
+                    .unwrap_or(PROTOCOL_VERSION);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "capabilities": {
+                        "tools": { "listChanged": true },
+                        "resources": { "listChanged": true },
+                        "prompts": { "listChanged": true },
+                    },
+                    "serverInfo": {
+                        "name": "mcp-hub",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                let tools = self.gather("tools", "tools/list", "tools").await;
+                Ok(json!({ "tools": tools }))
+            }
+            "prompts/list" => {
+                let prompts = self.gather("prompts", "prompts/list", "prompts").await;
+                Ok(json!({ "prompts": prompts }))
+            }
+            "resources/list" => {
+                let resources = self
+                    .gather("resources", "resources/list", "resources")
+                    .await;
+                Ok(json!({ "resources": resources }))
+            }
+            "resources/templates/list" => {
+                let templates = self
+                    .gather("resources", "resources/templates/list", "resourceTemplates")
+                    .await;
+                Ok(json!({ "resourceTemplates": templates }))
+            }
+            "tools/call" | "prompts/get" => self.call_namespaced(method, params).await,
+            "resources/read" => self.read_resource(params).await,
+            _ => Err(rpc_error(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            )),
+        }
+    }
+
+    /// List items from every child offering `capability`, with their names
+    /// and URIs (or URI templates) prefixed with the child's prefix
+    async fn gather(
+        &self,
+        capability: &str,
+        method: &'static str,
+        field: &'static str,
+    ) -> Vec<Value> {
+        let mut listing = JoinSet::new();
+        for (prefix, upstream) in self.upstreams.read().await.iter() {
+            if !upstream.offers(capability) {
+                continue;
+            }
+            let prefix = prefix.clone();
+            let client = upstream.client.clone();
+            let filter = (capability == "tools").then(|| upstream.filter.clone());
+            let timeout = self.timeout;
+            listing.spawn(async move {
+                let mut items = tokio::time::timeout(timeout, client.list_all(method, field)).await;
+                if let (Some(filter), Ok(Ok(items))) = (filter, items.as_mut()) {
+                    items.retain(|item| {
+                        let name = item.get("name").and_then(Value::as_str);
+                        name.is_some_and(|name| tool_filter::permits(&filter, name))
+                    });
+                }
+                (prefix, items)
+            });
+        }
+
+        let mut by_prefix = BTreeMap::new();
+        while let Some(Ok((prefix, items))) = listing.join_next().await {
+            match items {
+                Ok(Ok(items)) => {
+                    by_prefix.insert(prefix, items);
+                }
+                Ok(Err(e)) => log::warn!("{} from {} failed: {}", method, prefix, e),
+                Err(_) => log::warn!("{} from {} timed out", method, prefix),
+            }
+        }
+
+        let mut merged = Vec::new();
+        for (prefix, items) in by_prefix {
+            for mut item in items {
+                for key in ["name", "uri", "uriTemplate"] {
+                    if let Some(value) = item.get(key).and_then(Value::as_str) {
+                        item[key] = Value::String(namespaced(&prefix, value));
+                    }
+                }
+                merged.push(item);
+            }
+        }
+        merged
+    }
+
+    /// Forward a `tools/call` or `prompts/get` to the child named by the prefix
+    async fn call_namespaced(&self, method: &str, mut params: Value) -> Result<Value, Value> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| rpc_error(INVALID_PARAMS, "Missing name".to_string()))?;
+
+        let (client, inner) = {
+            let upstreams = self.upstreams.read().await;
+            let (prefix, inner) = split_namespaced(name, upstreams.keys().map(String::as_str))
+                .ok_or_else(|| rpc_error(INVALID_PARAMS, format!("Unknown name: {}", name)))?;
+            let upstream = &upstreams[prefix];
+            if method == "tools/call" && !tool_filter::permits(&upstream.filter, inner) {
+                return Err(rpc_error(
+                    INVALID_PARAMS,
+                    format!("Tool {} is not allowed on this instance", name),
+                ));
+            }
+            (upstream.client.clone(), inner.to_string())
+        };
+
+        params["name"] = Value::String(inner);
+        forward(&client, method, params).await
+    }
+
+    /// Forward a `resources/read` to the child named by the URI's prefix. A
+    /// URI without one, e.g. from a resource link in a tool result, is tried
+    /// on each child offering resources until one can read it.
+    async fn read_resource(&self, mut params: Value) -> Result<Value, Value> {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .ok_or_else(|| rpc_error(INVALID_PARAMS, "Missing uri".to_string()))?
+            .to_string();
+
+        let candidates: Vec<Arc<StdioClient>> = {
+            let upstreams = self.upstreams.read().await;
+            match split_namespaced(&uri, upstreams.keys().map(String::as_str)) {
+                Some((prefix, inner)) => {
+                    params["uri"] = Value::String(inner.to_string());
+                    vec![upstreams[prefix].client.clone()]
+                }
+                None => upstreams
+                    .values()
+                    .filter(|upstream| upstream.offers("resources"))
+                    .map(|upstream| upstream.client.clone())
+                    .collect(),
+            }
+        };
+
+        let mut last_error = rpc_error(INVALID_PARAMS, format!("Unknown resource: {}", uri));
+        for client in candidates {
+            match forward(&client, "resources/read", params.clone()).await {
+                Ok(result) => return Ok(result),
+                Err(error) => last_error = error,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Start a child and complete its handshake within `timeout`
+async fn start_upstream(
+    server: &McpServer,
+    filter: ToolFilter,
+    timeout: Duration,
+) -> Result<Upstream, String> {
+    let client = StdioClient::spawn(server)?;
+    match tokio::time::timeout(timeout, client.initialize()).await {
+        Ok(Ok(init)) => Ok(Upstream {
+            server_id: server.id.clone(),
+            version: Version::of(server),
+            capabilities: init.capabilities,
+            client: Arc::new(client),
+            filter,
+        }),
+        Ok(Err(e)) => {
+            let stderr = client.shutdown().await;
+            Err(if stderr.is_empty() {
+                e
+            } else {
+                format!("{}\n{}", e, stderr)
+            })
+        }
+        Err(_) => {
+            client.shutdown().await;
+            Err(format!("No handshake within {:?}", timeout))
+        }
+    }
+}
+
+/// Send a request to a child and pass its result or JSON-RPC error through
+async fn forward(client: &StdioClient, method: &str, params: Value) -> Result<Value, Value> {
+    let mut response = client
+        .request_message(method, params)
+        .await
+        .map_err(|e| rpc_error(INTERNAL_ERROR, e))?;
+
+    match response.get_mut("error").map(Value::take) {
+        Some(error) => Err(error),
+        None => Ok(response
+            .get_mut("result")
+            .map(Value::take)
+            .unwrap_or_else(|| Value::Object(Map::new()))),
+    }
+}
+
+fn rpc_error(code: i64, message: String) -> Value {
+    json!({ "code": code, "message": message })
+}
+
+/// The name or URI a child's tool, prompt or resource is exposed under
+pub fn namespaced(prefix: &str, name: &str) -> String {
+    format!("{}{}{}", prefix, NAMESPACE_SEPARATOR, name)
+}
+
+/// Split a namespaced name or URI into the prefix it belongs to and the
+/// child's own. The longest matching prefix wins, since prefixes may contain the
+/// separator themselves.
+pub fn split_namespaced<'a, 'p>(
+    name: &'a str,
+    prefixes: impl Iterator<Item = &'p str>,
+) -> Option<(&'p str, &'a str)> {
+    prefixes
+        .filter_map(|prefix| {
+            name.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix(NAMESPACE_SEPARATOR))
+                .map(|inner| (prefix, inner))
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_namespaced() {
+        let prefixes = ["github", "github__enterprise", "fs"];
+
+        assert_eq!(
+            split_namespaced("github__create_issue", prefixes.into_iter()),
+            Some(("github", "create_issue"))
+        );
+        assert_eq!(
+            split_namespaced("github__enterprise__create_issue", prefixes.into_iter()),
+            Some(("github__enterprise", "create_issue"))
+        );
+        assert_eq!(
+            split_namespaced("fs__file:///tmp/notes.md", prefixes.into_iter()),
+            Some(("fs", "file:///tmp/notes.md"))
+        );
+        assert_eq!(split_namespaced("fs_read", prefixes.into_iter()), None);
+        assert_eq!(namespaced("fs", "read_file"), "fs__read_file".to_string());
+    }
+}
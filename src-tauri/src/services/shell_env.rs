@@ -0,0 +1,161 @@
+//! The PATH of the user's login shell.
+//!
+//! Apps started from the Dock or an app launcher don't see PATH changes made
+//! in shell profiles, e.g. by Homebrew, nvm or installers that add
+//! `~/.local/bin`. Capturing PATH from a login shell lets sync write commands
+//! that such clients can start.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::models::McpServerEntry;
+use crate::services::doctor;
+
+/// How long the login shell gets to start and print its PATH
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Brackets the PATH in the shell's output, which profiles may add to
+const MARKER: &str = "__MCP_HUB_PATH__";
+
+/// The captured PATH, or `None` if capturing it failed
+static LOGIN_PATH: OnceLock<Option<String>> = OnceLock::new();
+
+/// PATH as set up by the user's login shell. Only available on Linux and
+/// macOS; captured once and then reused, even if the capture failed, so a
+/// broken profile doesn't cost the timeout again on every sync.
+pub fn login_shell_path() -> Option<String> {
+    LOGIN_PATH.get_or_init(capture_login_path).clone()
+}
+
+#[cfg(unix)]
+fn capture_login_path() -> Option<String> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    // Interactive as well as login, since many profiles only set PATH in
+    // their rc file
+    let mut child = Command::new(&shell)
+        .args([
+            "-l",
+            "-i",
+            "-c",
+            &format!("printf '%s%s%s' {0} \"$PATH\" {0}", MARKER),
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| log::warn!("Failed to start login shell {}: {}", shell, e))
+        .ok()?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < CAPTURE_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            _ => {
+                log::warn!("Login shell {} did not print its PATH in time", shell);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let output = child.wait_with_output().ok()?;
+    parse_marked_path(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(unix))]
+fn capture_login_path() -> Option<String> {
+    None
+}
+
+/// Take the PATH from between the markers, ignoring whatever the shell's
+/// profile printed around it
+fn parse_marked_path(output: &str) -> Option<String> {
+    let start = output.find(MARKER)? + MARKER.len();
+    let end = start + output[start..].find(MARKER)?;
+    let path = output[start..end].trim();
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// Directories to resolve commands in: the login shell's PATH, then MCP
+/// Hub's own
+pub fn search_dirs() -> Vec<PathBuf> {
+    ShellPath::current().dirs
+}
+
+/// The login shell's PATH and the directories commands are resolved in,
+/// looked up once for rewriting many entries
+pub struct ShellPath {
+    login_path: Option<String>,
+    dirs: Vec<PathBuf>,
+}
+
+impl ShellPath {
+    pub fn current() -> Self {
+        let login_path = login_shell_path();
+        let mut dirs: Vec<PathBuf> = login_path
+            .as_ref()
+            .map(|path| std::env::split_paths(path).collect())
+            .unwrap_or_default();
+        if let Some(path) = std::env::var_os("PATH") {
+            dirs.extend(std::env::split_paths(&path));
+        }
+        Self { login_path, dirs }
+    }
+
+    /// Rewrite a stdio entry for clients without the shell's PATH: with
+    /// `resolve_command` the command becomes an absolute path, and with
+    /// `inject_path` the login shell's PATH is passed along, unless the
+    /// server sets PATH itself. Commands that can't be found are left as
+    /// they are.
+    pub fn resolve_entry(
+        &self,
+        entry: &mut McpServerEntry,
+        resolve_command: bool,
+        inject_path: bool,
+    ) {
+        if entry.transport.is_remote() {
+            return;
+        }
+
+        if resolve_command && !entry.command.is_empty() {
+            let is_path = std::path::Path::new(&entry.command).components().count() > 1;
+            if !is_path {
+                match doctor::find_executable(&entry.command, &self.dirs) {
+                    Some(path) => entry.command = path.to_string_lossy().to_string(),
+                    None => log::warn!("Could not resolve `{}` to an absolute path", entry.command),
+                }
+            }
+        }
+
+        if inject_path && !entry.env.contains_key("PATH") {
+            if let Some(ref path) = self.login_path {
+                entry.env.insert("PATH".to_string(), path.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_marked_path() {
+        let output = format!(
+            "Welcome back!\n{0}/opt/homebrew/bin:/usr/bin{0}\nbye",
+            MARKER
+        );
+        assert_eq!(
+            parse_marked_path(&output).as_deref(),
+            Some("/opt/homebrew/bin:/usr/bin")
+        );
+        assert_eq!(parse_marked_path("no markers"), None);
+        assert_eq!(parse_marked_path(&format!("{0}{0}", MARKER)), None);
+    }
+}
@@ -0,0 +1,658 @@
+//! Planning and applying syncs of registry servers to client config files.
+//!
+//! A sync is first computed as a [`SyncPlan`] that can be shown to the user,
+//! then applied. Only entries MCP Hub owns are touched: enabled servers are
+//! added or updated, and previously managed keys that are no longer enabled
+//! are removed. Entries added by hand or by the client itself are left alone.
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use chrono::Utc;
+
+use crate::db::Database;
+use crate::error::{HubError, HubResult};
+use crate::models::{
+    ClientInstance, ConfigBackup, EntryChange, FieldChange, McpServer, McpServerEntry, SyncPlan,
+};
+use crate::services::formats::{self, ClientConfigFormat};
+use crate::services::shell_env::ShellPath;
+use crate::services::{config, credentials, drift, inheritance, tool_filter};
+
+/// A computed sync, ready to be previewed or applied
+pub struct PreparedSync {
+    pub plan: SyncPlan,
+    format: &'static dyn ClientConfigFormat,
+    /// Full config file content after the sync
+    content: String,
+    /// Entry keys MCP Hub owns after the sync, mapped to their server IDs
+    managed_keys: HashMap<String, String>,
+    /// Secret env var names by entry key, kept out of the sync snapshot
+    secrets: HashMap<String, BTreeSet<String>>,
+}
+
+/// Result of applying a sync
+#[derive(Debug)]
+pub struct SyncOutcome {
+    pub backup_path: Option<PathBuf>,
+    /// Entry keys MCP Hub now owns in the config, mapped to their server IDs
+    pub managed_keys: HashMap<String, String>,
+}
+
+/// Shown in place of secret values in sync plans and drift reports
+pub const SECRET_MASK: &str = "••••••••";
+
+/// Config entries built for an instance, all keyed by config entry key
+pub struct BuiltEntries {
+    pub entries: HashMap<String, McpServerEntry>,
+    /// The server ID behind each entry
+    pub owners: HashMap<String, String>,
+    /// Names of the env vars whose values are secrets, for entries with any
+    pub secrets: HashMap<String, BTreeSet<String>>,
+}
+
+/// Build the config entries for the servers enabled on an instance.
+///
+/// `known_tools` are the probed tool names per server ID,
+/// used to spell out the instance's tool filters. Servers are resolved
+/// against the others in `servers` for what they inherit from their parents,
+/// then the instance's overrides are applied. Secret references in env are
+/// resolved from the keyring, and commands are resolved to absolute paths
+/// for instances that ask for it.
+pub fn build_entries(
+    instance: &ClientInstance,
+    servers: &[McpServer],
+    known_tools: &HashMap<String, Vec<String>>,
+) -> HubResult<BuiltEntries> {
+    let mut entries = HashMap::new();
+    let mut owners = HashMap::new();
+    let mut secrets = HashMap::new();
+    let shell_path = (instance.resolve_commands || instance.inject_path).then(ShellPath::current);
+
+    for server in servers {
+        if instance.enabled_servers.contains(&server.id) {
+            let mut server = inheritance::resolve(server, servers)?;
+            let server_override = instance.server_overrides.get(&server.id);
+            if let Some(server_override) = server_override {
+                server_override.apply(&mut server);
+            }
+            let mut entry = McpServerEntry {
+                transport: server.transport,
+                command: server.command.clone(),
+                args: server.args.clone(),
+                env: credentials::resolve_env(&server)?,
+                url: server.url.clone(),
+                headers: server.headers.clone(),
+                tools: instance.tool_filters.get(&server.id).map(|filter| {
+                    let known = known_tools.get(&server.id).map(Vec::as_slice);
+                    tool_filter::native_access(filter, known.unwrap_or_default())
+                }),
+            };
+            if let Some(ref shell_path) = shell_path {
+                shell_path.resolve_entry(
+                    &mut entry,
+                    instance.resolve_commands,
+                    instance.inject_path,
+                );
+            }
+            // Use the alias or else the server name as the key (sanitized)
+            let name = server_override
+                .and_then(|o| o.alias.as_deref())
+                .unwrap_or(&server.name);
+            let key = config::sanitize_server_name(name);
+            if let Some(other) = owners
+                .get(&key)
+                .and_then(|id| servers.iter().find(|s| &s.id == id))
+            {
+                return Err(HubError::Conflict(format!(
+                    "{} and {} would both be written as {}; give one of them an alias on {}",
+                    other.name, server.name, key, instance.name
+                )));
+            }
+            let secret_env: BTreeSet<String> = server
+                .env
+                .iter()
+                .filter(|(_, value)| credentials::parse_reference(value).is_some())
+                .map(|(name, _)| name.clone())
+                .collect();
+            if !secret_env.is_empty() {
+                secrets.insert(key.clone(), secret_env);
+            }
+            owners.insert(key.clone(), server.id.clone());
+            entries.insert(key, entry);
+        }
+    }
+
+    Ok(BuiltEntries {
+        entries,
+        owners,
+        secrets,
+    })
+}
+
+/// Compute what syncing `servers` to an instance would change, without writing anything.
+///
+/// `managed_keys` are the entry keys written by the previous sync, and
+/// `known_tools` the probed tool names per server ID.
+pub fn plan_sync(
+    instance: &ClientInstance,
+    servers: &[McpServer],
+    known_tools: &HashMap<String, Vec<String>>,
+    managed_keys: &[String],
+) -> HubResult<PreparedSync> {
+    let config_path = PathBuf::from(&instance.config_path);
+    let format = formats::format_for_client(&instance.client_type, &config_path);
+    let existing_content = config::read_config_content(&config_path)?;
+    let existing = format
+        .read_servers(&existing_content)
+        .map_err(HubError::Parse)?;
+
+    let BuiltEntries {
+        entries,
+        owners,
+        secrets,
+    } = build_entries(instance, servers, known_tools)?;
+    let mut remove: Vec<String> = managed_keys
+        .iter()
+        .filter(|key| !entries.contains_key(*key))
+        .cloned()
+        .collect();
+    remove.sort();
+
+    let is_empty = existing_content.trim().is_empty();
+    let content = if is_empty {
+        format.render(&entries)
+    } else {
+        format.merge(&existing_content, &entries, &remove)
+    }
+    .map_err(HubError::Parse)?;
+
+    let touches_other_settings =
+        !is_empty && strip_servers(format, &existing_content)? != strip_servers(format, &content)?;
+
+    let mut plan = SyncPlan {
+        instance_id: instance.id.clone(),
+        instance_name: instance.name.clone(),
+        config_path: instance.config_path.clone(),
+        added: Vec::new(),
+        changed: Vec::new(),
+        removed: Vec::new(),
+        unchanged: Vec::new(),
+        creates_file: !config::config_exists(&config_path),
+        touches_other_settings,
+        fingerprint: fingerprint(&existing_content, &content),
+    };
+
+    // Compare entries as written, so fields the client keeps for itself
+    // (or that the format cannot express) do not count as changes
+    let written = format.read_servers(&content).map_err(HubError::Parse)?;
+    let no_secrets = BTreeSet::new();
+    let mut keys: Vec<&String> = entries.keys().collect();
+    keys.sort();
+    for key in keys {
+        let after = written.get(key).unwrap_or(&entries[key]);
+        let server_id = owners.get(key).cloned();
+        let secret_env = secrets.get(key).unwrap_or(&no_secrets);
+        match existing.get(key) {
+            None => plan.added.push(EntryChange {
+                key: key.clone(),
+                server_id,
+                changes: mask_secrets(diff_entries(None, Some(after)), secret_env),
+            }),
+            Some(before) if before == after => plan.unchanged.push(key.clone()),
+            Some(before) => plan.changed.push(EntryChange {
+                key: key.clone(),
+                server_id,
+                changes: mask_secrets(diff_entries(Some(before), Some(after)), secret_env),
+            }),
+        }
+    }
+
+    // Removed entries are no longer backed by a server that says which of
+    // their env vars are secrets, so none of their env values are shown
+    for key in remove {
+        if let Some(before) = existing.get(&key) {
+            let env: BTreeSet<String> = before.env.keys().cloned().collect();
+            plan.removed.push(EntryChange {
+                key,
+                server_id: None,
+                changes: mask_secrets(diff_entries(Some(before), None), &env),
+            });
+        }
+    }
+
+    Ok(PreparedSync {
+        plan,
+        format,
+        content,
+        managed_keys: owners,
+        secrets,
+    })
+}
+
+/// Write a prepared sync to the instance's config file, backing up the
+/// current file to `backup_dir` first when given. A plan without changes
+/// leaves the file alone.
+pub fn apply_sync(
+    instance: &ClientInstance,
+    prepared: PreparedSync,
+    backup_dir: Option<&PathBuf>,
+) -> HubResult<SyncOutcome> {
+    let config_path = PathBuf::from(&instance.config_path);
+    let mut backup_path = None;
+
+    // Nothing to write: skip the backup too
+    if !prepared.plan.has_changes() {
+        log::info!("{} is already up to date", config_path.display());
+        return Ok(SyncOutcome {
+            backup_path,
+            managed_keys: prepared.managed_keys,
+        });
+    }
+
+    // Create backup if requested and file exists
+    if let Some(dir) = backup_dir {
+        if config::config_exists(&config_path) {
+            backup_path = Some(config::backup_config_file(&config_path, dir)?);
+        }
+    }
+
+    log::info!(
+        "Writing {} servers to {} ({}): {} added, {} changed, {} removed",
+        prepared.managed_keys.len(),
+        config_path.display(),
+        prepared.format.name(),
+        prepared.plan.added.len(),
+        prepared.plan.changed.len(),
+        prepared.plan.removed.len()
+    );
+    config::write_config_content(&config_path, &prepared.content)?;
+
+    Ok(SyncOutcome {
+        backup_path,
+        managed_keys: prepared.managed_keys,
+    })
+}
+
+/// Load an instance with its enabled servers and compute its sync plan
+pub fn prepare_instance_sync(
+    db: &Database,
+    instance_id: &str,
+) -> HubResult<(ClientInstance, PreparedSync)> {
+    let mut instance = db
+        .get_instance(instance_id)?
+        .ok_or_else(|| HubError::not_found("Instance"))?;
+
+    instance.enabled_servers = db.get_enabled_servers_for_instance(instance_id)?;
+    instance.tool_filters = db.get_tool_filters(instance_id)?;
+    instance.server_overrides = db.get_server_overrides(instance_id)?;
+
+    let servers = db.get_all_servers()?;
+    let known_tools = db.get_tool_names()?;
+
+    // Get the entry keys written by the previous sync
+    let managed_keys = db.get_managed_keys(instance_id)?;
+
+    let prepared = plan_sync(&instance, &servers, &known_tools, &managed_keys)?;
+    Ok((instance, prepared))
+}
+
+/// Sync an instance's config file and record the result.
+///
+/// When `expected_fingerprint` is given, the sync is only applied if it still
+/// matches the previewed plan. Returns the path of the backup taken, if any.
+pub fn sync_instance(
+    db: &Database,
+    instance_id: &str,
+    expected_fingerprint: Option<&str>,
+) -> HubResult<Option<PathBuf>> {
+    let (mut instance, prepared) = prepare_instance_sync(db, instance_id)?;
+
+    if let Some(expected) = expected_fingerprint {
+        if expected != prepared.plan.fingerprint {
+            return Err(HubError::Conflict(format!(
+                "Config for {} changed since the sync was previewed",
+                instance.name
+            )));
+        }
+    }
+
+    let format = prepared.format;
+    let secrets = prepared.secrets.clone();
+    let backup_dir = config::get_backup_dir();
+    let outcome = apply_sync(&instance, prepared, backup_dir.as_ref())?;
+
+    // Record backup if created
+    if let Some(ref path) = outcome.backup_path {
+        let backup = ConfigBackup::new(instance.id.clone(), path.to_string_lossy().to_string());
+        db.create_backup(&backup)?;
+    }
+
+    db.set_managed_keys(&instance.id, &outcome.managed_keys)?;
+
+    // Remember what we wrote so later edits to the file can be detected
+    let snapshot = drift::snapshot_config(&instance, format, &secrets)?;
+    db.set_sync_snapshot(&snapshot)?;
+
+    // Update last synced timestamp
+    instance.last_synced = Some(Utc::now());
+    db.update_instance(&instance)?;
+
+    Ok(outcome.backup_path)
+}
+
+/// Hex-encoded SHA-256 of some content
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Identify a sync by the file content it starts from and the content it writes
+fn fingerprint(before: &str, after: &str) -> String {
+    content_hash(&format!("{}\0{}", before, after))
+}
+
+/// Config content with every server entry removed, for comparing the rest of the file
+pub(crate) fn strip_servers(format: &dyn ClientConfigFormat, content: &str) -> HubResult<String> {
+    let keys: Vec<String> = format
+        .read_servers(content)
+        .map_err(HubError::Parse)?
+        .into_keys()
+        .collect();
+    format
+        .merge(content, &HashMap::new(), &keys)
+        .map_err(HubError::Parse)
+}
+
+/// Field-level differences between two versions of an entry. A missing side
+/// means the entry is being added or removed.
+pub(crate) fn diff_entries(
+    before: Option<&McpServerEntry>,
+    after: Option<&McpServerEntry>,
+) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    push_change(
+        &mut changes,
+        "transport".to_string(),
+        before.map(|e| json!(e.transport.as_str())),
+        after.map(|e| json!(e.transport.as_str())),
+    );
+    push_change(
+        &mut changes,
+        "command".to_string(),
+        before
+            .filter(|e| !e.command.is_empty())
+            .map(|e| json!(e.command)),
+        after
+            .filter(|e| !e.command.is_empty())
+            .map(|e| json!(e.command)),
+    );
+    push_change(
+        &mut changes,
+        "args".to_string(),
+        before.filter(|e| !e.args.is_empty()).map(|e| json!(e.args)),
+        after.filter(|e| !e.args.is_empty()).map(|e| json!(e.args)),
+    );
+    push_change(
+        &mut changes,
+        "url".to_string(),
+        before.and_then(|e| e.url.as_ref()).map(|u| json!(u)),
+        after.and_then(|e| e.url.as_ref()).map(|u| json!(u)),
+    );
+    diff_map(
+        &mut changes,
+        "env",
+        before.map(|e| &e.env),
+        after.map(|e| &e.env),
+    );
+    diff_map(
+        &mut changes,
+        "headers",
+        before.map(|e| &e.headers),
+        after.map(|e| &e.headers),
+    );
+
+    let before_tools = before.and_then(|e| e.tools.as_ref());
+    let after_tools = after.and_then(|e| e.tools.as_ref());
+    push_change(
+        &mut changes,
+        "tools.allowed".to_string(),
+        before_tools
+            .and_then(|t| t.allowed.as_ref())
+            .map(|a| json!(a)),
+        after_tools
+            .and_then(|t| t.allowed.as_ref())
+            .map(|a| json!(a)),
+    );
+    push_change(
+        &mut changes,
+        "tools.denied".to_string(),
+        before_tools
+            .filter(|t| !t.denied.is_empty())
+            .map(|t| json!(t.denied)),
+        after_tools
+            .filter(|t| !t.denied.is_empty())
+            .map(|t| json!(t.denied)),
+    );
+    push_change(
+        &mut changes,
+        "tools.disabled".to_string(),
+        before_tools.filter(|t| t.disabled).map(|_| json!(true)),
+        after_tools.filter(|t| t.disabled).map(|_| json!(true)),
+    );
+
+    changes
+}
+
+/// Per-key differences of a string map, reported as `prefix.KEY`
+fn diff_map(
+    changes: &mut Vec<FieldChange>,
+    prefix: &str,
+    before: Option<&HashMap<String, String>>,
+    after: Option<&HashMap<String, String>>,
+) {
+    let keys: BTreeSet<&String> = before
+        .into_iter()
+        .chain(after)
+        .flat_map(|map| map.keys())
+        .collect();
+
+    for key in keys {
+        push_change(
+            changes,
+            format!("{}.{}", prefix, key),
+            before.and_then(|m| m.get(key)).map(|v| json!(v)),
+            after.and_then(|m| m.get(key)).map(|v| json!(v)),
+        );
+    }
+}
+
+/// Replace the values of the named env vars in `changes` with [`SECRET_MASK`]
+pub(crate) fn mask_secrets(
+    mut changes: Vec<FieldChange>,
+    secret_env: &BTreeSet<String>,
+) -> Vec<FieldChange> {
+    for change in &mut changes {
+        let is_secret = change
+            .field
+            .strip_prefix("env.")
+            .is_some_and(|name| secret_env.contains(name));
+        if is_secret {
+            for value in [&mut change.before, &mut change.after]
+                .into_iter()
+                .flatten()
+            {
+                *value = json!(SECRET_MASK);
+            }
+        }
+    }
+    changes
+}
+
+fn push_change(
+    changes: &mut Vec<FieldChange>,
+    field: String,
+    before: Option<Value>,
+    after: Option<Value>,
+) {
+    if before != after {
+        changes.push(FieldChange {
+            field,
+            before,
+            after,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ClientType, ServerOverride};
+    use std::fs;
+
+    fn test_instance(dir: &std::path::Path, content: &str) -> ClientInstance {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join("claude_desktop_config.json");
+        fs::write(&path, content).unwrap();
+        ClientInstance::new(
+            "Test".to_string(),
+            ClientType::ClaudeDesktop,
+            path.to_string_lossy().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_sync_preserves_unmanaged_entries() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-test-{}", uuid::Uuid::new_v4()));
+        let mut instance = test_instance(
+            &dir,
+            r#"{"mcpServers":{"manual":{"command":"manual-cmd","args":[]},"old":{"command":"old-cmd","args":[]}}}"#,
+        );
+        let server = McpServer::new("New".to_string(), "new-cmd".to_string(), vec![]);
+        instance.enabled_servers = vec![server.id.clone()];
+
+        let prepared = plan_sync(
+            &instance,
+            std::slice::from_ref(&server),
+            &HashMap::new(),
+            &["old".to_string()],
+        )
+        .unwrap();
+        let outcome = apply_sync(&instance, prepared, None).unwrap();
+        let written = config::read_config_file(&PathBuf::from(&instance.config_path))
+            .unwrap()
+            .mcp_servers;
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(written.contains_key("manual"));
+        assert!(written.contains_key("new"));
+        assert!(!written.contains_key("old"));
+        assert_eq!(outcome.managed_keys.get("new"), Some(&server.id));
+        assert_eq!(outcome.managed_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_plan_reports_field_changes_without_writing() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-test-{}", uuid::Uuid::new_v4()));
+        let original = r#"{"theme":"dark","mcpServers":{"git":{"command":"uvx","args":["mcp-server-git"],"env":{"A":"1"}},"gone":{"command":"x","args":[]}}}"#;
+        let mut instance = test_instance(&dir, original);
+
+        let mut git = McpServer::new(
+            "git".to_string(),
+            "uvx".to_string(),
+            vec!["mcp-server-git".to_string(), "--verbose".to_string()],
+        );
+        git.env.insert("B".to_string(), "2".to_string());
+        let fetch = McpServer::new("fetch".to_string(), "uvx".to_string(), vec![]);
+        instance.enabled_servers = vec![git.id.clone(), fetch.id.clone()];
+
+        let prepared = plan_sync(
+            &instance,
+            &[git, fetch],
+            &HashMap::new(),
+            &["gone".to_string()],
+        )
+        .unwrap();
+        let content = fs::read_to_string(&instance.config_path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let plan = prepared.plan;
+
+        assert_eq!(content, original);
+        assert!(plan.has_changes());
+        assert!(!plan.touches_other_settings);
+        assert_eq!(plan.added.len(), 1);
+        assert_eq!(plan.added[0].key, "fetch");
+        assert_eq!(plan.removed.len(), 1);
+        assert_eq!(plan.removed[0].key, "gone");
+
+        let fields: Vec<&str> = plan.changed[0]
+            .changes
+            .iter()
+            .map(|c| c.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["args", "env.A", "env.B"]);
+    }
+
+    #[test]
+    fn test_build_entries_applies_overrides() {
+        let mut filesystem = McpServer::new(
+            "filesystem".to_string(),
+            "npx".to_string(),
+            vec![
+                "-y".to_string(),
+                "@modelcontextprotocol/server-filesystem".to_string(),
+            ],
+        );
+        filesystem.env.insert("LOG".to_string(), "info".to_string());
+        let servers = std::slice::from_ref(&filesystem);
+
+        let mut work =
+            ClientInstance::new("Work".to_string(), ClientType::ClaudeDesktop, String::new());
+        work.enabled_servers = vec![filesystem.id.clone()];
+        let mut personal = work.clone();
+
+        let mut args = filesystem.args.clone();
+        args.push("~/work".to_string());
+        work.server_overrides.insert(
+            filesystem.id.clone(),
+            ServerOverride {
+                args: Some(args.clone()),
+                env: HashMap::from([("LOG".to_string(), "debug".to_string())]),
+                alias: Some("work-files".to_string()),
+            },
+        );
+        personal.server_overrides.insert(
+            filesystem.id.clone(),
+            ServerOverride {
+                alias: Some("Personal files".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let BuiltEntries {
+            entries, owners, ..
+        } = build_entries(&work, servers, &HashMap::new()).unwrap();
+        assert_eq!(entries["work-files"].args, args);
+        assert_eq!(entries["work-files"].env["LOG"], "debug");
+        assert_eq!(owners["work-files"], filesystem.id);
+
+        let entries = build_entries(&personal, servers, &HashMap::new())
+            .unwrap()
+            .entries;
+        assert_eq!(entries["personal-files"].args, filesystem.args);
+        assert_eq!(entries["personal-files"].env["LOG"], "info");
+
+        // Two servers can't be written under the same key
+        let mut other = filesystem.clone();
+        other.id = "other".to_string();
+        other.name = "Work files".to_string();
+        work.enabled_servers.push(other.id.clone());
+        let servers = [filesystem, other];
+        let error = build_entries(&work, &servers, &HashMap::new())
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), "conflict");
+    }
+}
@@ -0,0 +1,127 @@
+//! Per-instance tool filters.
+//!
+//! Filters are glob patterns over a server's tool names. The proxy applies
+//! them directly; for clients with native per-tool fields, sync spells them
+//! out as concrete tool names using the server's last capability probe.
+
+use std::collections::BTreeSet;
+
+use crate::models::{ToolAccess, ToolFilter};
+
+/// Match a tool name against a glob pattern, where `*` matches any run of
+/// characters and `?` matches exactly one
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, from)) => {
+                    p = star + 1;
+                    n = from + 1;
+                    backtrack = Some((star, from + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Whether a filter lets an instance use a tool
+pub fn permits(filter: &ToolFilter, tool: &str) -> bool {
+    let allowed = filter.allow.is_empty() || filter.allow.iter().any(|p| glob_match(p, tool));
+    allowed && !filter.deny.iter().any(|p| glob_match(p, tool))
+}
+
+/// Spell out a filter as concrete tool names, given the tools the server is
+/// known to offer. Patterns without wildcards count even when the server has
+/// not been probed, so exact names work before the first probe.
+pub fn native_access(filter: &ToolFilter, known_tools: &[String]) -> ToolAccess {
+    let is_literal = |pattern: &&String| !pattern.contains(['*', '?']);
+    let candidates: BTreeSet<&String> = known_tools
+        .iter()
+        .chain(filter.allow.iter().filter(is_literal))
+        .chain(filter.deny.iter().filter(is_literal))
+        .collect();
+
+    let (allowed, denied): (Vec<&String>, Vec<&String>) = candidates
+        .into_iter()
+        .partition(|tool| permits(filter, tool));
+
+    ToolAccess {
+        allowed: (!filter.allow.is_empty())
+            .then(|| allowed.iter().map(|tool| tool.to_string()).collect()),
+        disabled: !known_tools.is_empty() && !known_tools.iter().any(|tool| permits(filter, tool)),
+        denied: denied.into_iter().cloned().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(allow: &[&str], deny: &[&str]) -> ToolFilter {
+        ToolFilter {
+            allow: allow.iter().map(|p| p.to_string()).collect(),
+            deny: deny.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("read_*", "read_file"));
+        assert!(glob_match("*_issue", "create_issue"));
+        assert!(glob_match("*file*", "read_file_lines"));
+        assert!(glob_match("get_?", "get_a"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("read_*", "write_file"));
+        assert!(!glob_match("get_?", "get_ab"));
+        assert!(!glob_match("a*b*c", "abx"));
+    }
+
+    #[test]
+    fn test_native_access() {
+        let known: Vec<String> = ["read_file", "write_file", "delete_file"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        let access = native_access(&filter(&["*_file"], &["delete_*"]), &known);
+        assert_eq!(
+            access.allowed,
+            Some(vec!["read_file".to_string(), "write_file".to_string()])
+        );
+        assert_eq!(access.denied, vec!["delete_file".to_string()]);
+        assert!(!access.disabled);
+
+        // Deny-only filters leave the client's auto-approvals alone
+        let access = native_access(&filter(&[], &["*"]), &known);
+        assert_eq!(access.allowed, None);
+        assert!(access.disabled);
+
+        // Exact names work before the server was ever probed
+        let access = native_access(&filter(&[], &["push"]), &[]);
+        assert_eq!(access.denied, vec!["push".to_string()]);
+        assert!(!access.disabled);
+    }
+}
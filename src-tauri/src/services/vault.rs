@@ -0,0 +1,216 @@
+//! A file of secrets encrypted with a passphrase, for systems without a
+//! usable OS keyring such as headless Linux sessions without a Secret
+//! Service.
+//!
+//! The file is an age file with a scrypt passphrase stanza, holding the
+//! secrets as a JSON object. It is decrypted once when unlocked and then
+//! kept in memory, and encrypted again whenever a secret changes.
+
+use age::secrecy::SecretString;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{HubError, HubResult};
+use crate::services::config;
+use crate::services::credentials::CredentialStore;
+
+/// File name of the vault in the app data dir
+const VAULT_FILE: &str = "secrets.age";
+
+/// Secrets of an unlocked vault, and the passphrase to write them back with
+struct Unlocked {
+    passphrase: SecretString,
+    secrets: HashMap<String, String>,
+}
+
+pub struct VaultStore {
+    path: PathBuf,
+    unlocked: Mutex<Option<Unlocked>>,
+}
+
+impl VaultStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            unlocked: Mutex::new(None),
+        }
+    }
+
+    /// The vault in the app data dir
+    pub fn default_path() -> Option<PathBuf> {
+        config::get_app_data_dir().map(|dir| dir.join(VAULT_FILE))
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.lock().unwrap().is_some()
+    }
+
+    /// Decrypt the vault with the passphrase. Creates an empty vault if
+    /// there is none yet, so the first passphrase given sets it.
+    pub fn unlock(&self, passphrase: &str) -> HubResult<()> {
+        if passphrase.is_empty() {
+            return Err(HubError::Validation(
+                "The vault passphrase can't be empty".to_string(),
+            ));
+        }
+        let passphrase = SecretString::from(passphrase.to_string());
+
+        let secrets = if self.exists() {
+            let ciphertext =
+                fs::read(&self.path).map_err(|e| HubError::io("Failed to read vault", e))?;
+            let identity = age::scrypt::Identity::new(passphrase.clone());
+            let plaintext = age::decrypt(&identity, &ciphertext).map_err(|e| match e {
+                age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+                    HubError::Keyring("Wrong passphrase for the vault".to_string())
+                }
+                e => HubError::Keyring(format!("Failed to decrypt vault: {}", e)),
+            })?;
+            serde_json::from_slice(&plaintext)
+                .map_err(|e| HubError::Parse(format!("Vault is corrupt: {}", e)))?
+        } else {
+            let secrets = HashMap::new();
+            write_vault(&self.path, &passphrase, &secrets)?;
+            secrets
+        };
+
+        *self.unlocked.lock().unwrap() = Some(Unlocked {
+            passphrase,
+            secrets,
+        });
+        Ok(())
+    }
+
+    /// Forget the passphrase and the decrypted secrets
+    pub fn lock(&self) {
+        *self.unlocked.lock().unwrap() = None;
+    }
+
+    /// Change the secrets of the unlocked vault and write it back
+    fn update(&self, change: impl FnOnce(&mut HashMap<String, String>) -> bool) -> HubResult<()> {
+        let mut guard = self.unlocked.lock().unwrap();
+        let unlocked = guard.as_mut().ok_or_else(locked_error)?;
+        let mut secrets = unlocked.secrets.clone();
+        if change(&mut secrets) {
+            write_vault(&self.path, &unlocked.passphrase, &secrets)?;
+            unlocked.secrets = secrets;
+        }
+        Ok(())
+    }
+}
+
+impl CredentialStore for VaultStore {
+    fn set(&self, key: &str, value: &str) -> HubResult<()> {
+        self.update(|secrets| {
+            secrets
+                .insert(key.to_string(), value.to_string())
+                .as_deref()
+                != Some(value)
+        })
+    }
+
+    fn get(&self, key: &str) -> HubResult<Option<String>> {
+        let guard = self.unlocked.lock().unwrap();
+        let unlocked = guard.as_ref().ok_or_else(locked_error)?;
+        Ok(unlocked.secrets.get(key).cloned())
+    }
+
+    fn delete(&self, key: &str) -> HubResult<()> {
+        self.update(|secrets| secrets.remove(key).is_some())
+    }
+
+    /// Writing the vault takes a second for the key derivation, so rather
+    /// than a round trip this checks that it is unlocked and its directory
+    /// can be written to
+    fn probe(&self) -> HubResult<()> {
+        if !self.is_unlocked() {
+            return Err(locked_error());
+        }
+        let dir = self
+            .path
+            .parent()
+            .ok_or_else(|| HubError::Keyring("Vault path has no directory".to_string()))?;
+        let probe = dir.join(format!(".{}.probe", VAULT_FILE));
+        fs::write(&probe, b"").map_err(|e| HubError::io("Vault directory is not writable", e))?;
+        let _ = fs::remove_file(&probe);
+        Ok(())
+    }
+}
+
+fn locked_error() -> HubError {
+    HubError::Keyring("The vault is locked; unlock it with its passphrase first".to_string())
+}
+
+/// Encrypt the secrets into the vault file, replacing it in one step so an
+/// interrupted write can't lose it
+fn write_vault(
+    path: &Path,
+    passphrase: &SecretString,
+    secrets: &HashMap<String, String>,
+) -> HubResult<()> {
+    let plaintext = serde_json::to_vec(secrets).map_err(|e| HubError::Other(e.to_string()))?;
+    #[allow(unused_mut)]
+    let mut recipient = age::scrypt::Recipient::new(passphrase.clone());
+    // The work factor is otherwise calibrated to take about a second
+    #[cfg(test)]
+    recipient.set_work_factor(10);
+    let ciphertext = age::encrypt(&recipient, &plaintext)
+        .map_err(|e| HubError::Keyring(format!("Failed to encrypt vault: {}", e)))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| HubError::io("Failed to create vault directory", e))?;
+    }
+    let tmp = path.with_extension("age.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| HubError::io("Failed to write vault", e))?;
+    file.write_all(&ciphertext)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| HubError::io("Failed to write vault", e))?;
+    fs::rename(&tmp, path).map_err(|e| HubError::io("Failed to write vault", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_round_trip() {
+        let dir = std::env::temp_dir().join(format!("mcp-hub-vault-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(VAULT_FILE);
+
+        let vault = VaultStore::new(path.clone());
+        assert!(vault.get("API_KEY").is_err());
+        vault.unlock("correct horse").unwrap();
+        vault.set("API_KEY", "sk-123").unwrap();
+        vault.set("OTHER", "value").unwrap();
+        vault.delete("OTHER").unwrap();
+        vault.probe().unwrap();
+
+        let reopened = VaultStore::new(path);
+        assert_eq!(
+            reopened.unlock("wrong horse").unwrap_err().to_string(),
+            "Wrong passphrase for the vault"
+        );
+        reopened.unlock("correct horse").unwrap();
+        assert_eq!(reopened.get("API_KEY").unwrap().as_deref(), Some("sk-123"));
+        assert_eq!(reopened.get("OTHER").unwrap(), None);
+        reopened.lock();
+        assert!(reopened.probe().is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
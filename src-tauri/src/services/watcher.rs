@@ -0,0 +1,224 @@
+//! Watches client config files and keeps each instance's status current.
+//!
+//! The parent directory of every instance's config file is watched (inotify
+//! on Linux), so files that are created later or replaced by an editor's
+//! atomic save are picked up too. Changes are debounced, the affected
+//! instances are re-checked, their status is stored, and a
+//! [`CONFIG_FILE_EVENT`] is emitted to the frontend.
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::db::Database;
+use crate::models::{ClientInstance, ConfigFileEvent, DriftStatus, InstanceStatus};
+use crate::services::{config, drift};
+
+/// Event emitted when a watched config file changes
+pub const CONFIG_FILE_EVENT: &str = "config-file-changed";
+
+/// How long to wait for a burst of file events to settle
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+enum Message {
+    Fs(notify::Result<Event>),
+    /// Re-check these config paths without a file event
+    Refresh(Vec<PathBuf>),
+}
+
+/// Handle to the running config file watcher
+pub struct ConfigWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched_dirs: Mutex<HashSet<PathBuf>>,
+    /// Config paths being watched, mapped to the instances using them
+    targets: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
+    sender: Sender<Message>,
+}
+
+impl ConfigWatcher {
+    /// Start watching the config files of every instance in `db`
+    pub fn start(app: AppHandle, db: Database) -> Result<Self, String> {
+        let instances = db.get_all_instances().map_err(|e| e.to_string())?;
+        let (sender, receiver) = mpsc::channel();
+
+        let fs_sender = sender.clone();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = fs_sender.send(Message::Fs(event));
+        })
+        .map_err(|e| format!("Failed to start config watcher: {}", e))?;
+
+        let targets = Arc::new(Mutex::new(HashMap::new()));
+        let thread_targets = targets.clone();
+        thread::Builder::new()
+            .name("config-watcher".to_string())
+            .spawn(move || process_events(app, db, receiver, thread_targets))
+            .map_err(|e| format!("Failed to start config watcher: {}", e))?;
+
+        let config_watcher = Self {
+            watcher: Mutex::new(watcher),
+            watched_dirs: Mutex::new(HashSet::new()),
+            targets,
+            sender,
+        };
+        config_watcher.watch_instances(&instances);
+
+        Ok(config_watcher)
+    }
+
+    /// Watch exactly the config files of `instances`, and check the status of
+    /// any that were not watched before
+    pub fn watch_instances(&self, instances: &[ClientInstance]) {
+        let mut new_targets: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        for instance in instances {
+            new_targets
+                .entry(canonical(Path::new(&instance.config_path)))
+                .or_default()
+                .push(instance.id.clone());
+        }
+
+        let dirs: HashSet<PathBuf> = new_targets
+            .keys()
+            .filter_map(|path| path.parent().map(|p| p.to_path_buf()))
+            .collect();
+
+        let mut watcher = self.watcher.lock().unwrap();
+        let mut watched_dirs = self.watched_dirs.lock().unwrap();
+
+        for dir in watched_dirs.difference(&dirs) {
+            let _ = watcher.unwatch(dir);
+        }
+        watched_dirs.retain(|dir| dirs.contains(dir));
+        for dir in dirs {
+            if watched_dirs.contains(&dir) {
+                continue;
+            }
+            // The client may not have created its config directory yet
+            match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    watched_dirs.insert(dir);
+                }
+                Err(e) => log::debug!("Not watching {}: {}", dir.display(), e),
+            }
+        }
+
+        let mut targets = self.targets.lock().unwrap();
+        let refresh: Vec<PathBuf> = new_targets
+            .iter()
+            .filter(|(path, ids)| targets.get(*path) != Some(ids))
+            .map(|(path, _)| path.clone())
+            .collect();
+        *targets = new_targets;
+
+        if !refresh.is_empty() {
+            let _ = self.sender.send(Message::Refresh(refresh));
+        }
+    }
+}
+
+fn process_events(
+    app: AppHandle,
+    db: Database,
+    receiver: Receiver<Message>,
+    targets: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
+) {
+    while let Ok(message) = receiver.recv() {
+        let mut paths = HashSet::new();
+        collect_paths(message, &mut paths);
+        while let Ok(message) = receiver.recv_timeout(DEBOUNCE) {
+            collect_paths(message, &mut paths);
+        }
+
+        let instance_ids: Vec<String> = {
+            let targets = targets.lock().unwrap();
+            paths
+                .iter()
+                .filter_map(|path| targets.get(path))
+                .flatten()
+                .cloned()
+                .collect()
+        };
+
+        for instance_id in instance_ids {
+            if let Err(e) = refresh_instance(&app, &db, &instance_id) {
+                log::error!(
+                    "Failed to refresh status of instance {}: {}",
+                    instance_id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+fn collect_paths(message: Message, paths: &mut HashSet<PathBuf>) {
+    match message {
+        Message::Fs(Ok(event)) => paths.extend(event.paths.iter().map(|p| canonical(p))),
+        Message::Fs(Err(e)) => log::warn!("Config watcher error: {}", e),
+        Message::Refresh(refresh) => paths.extend(refresh),
+    }
+}
+
+/// `path` with symlinks resolved, so watched paths and the paths of events
+/// (e.g. under /private/var on macOS for /var) compare equal. A file that
+/// does not exist is resolved through its parent directory.
+fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Re-check an instance's config file, store its status and notify the frontend
+fn refresh_instance(app: &AppHandle, db: &Database, instance_id: &str) -> Result<(), String> {
+    let Some(instance) = db.get_instance(instance_id).map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+
+    let (status, message) = check_instance_status(db, &instance);
+    db.set_instance_status(&instance.id, status, message.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    let event = ConfigFileEvent {
+        instance_id: instance.id,
+        config_path: instance.config_path,
+        status,
+        message,
+    };
+    app.emit(CONFIG_FILE_EVENT, event)
+        .map_err(|e| e.to_string())
+}
+
+/// Determine the status of an instance's config file
+pub fn check_instance_status(
+    db: &Database,
+    instance: &ClientInstance,
+) -> (InstanceStatus, Option<String>) {
+    let path = PathBuf::from(&instance.config_path);
+    if !config::config_exists(&path) {
+        return (InstanceStatus::Missing, None);
+    }
+
+    if let Err(e) = config::read_instance_entries(instance) {
+        return (InstanceStatus::Invalid, Some(e.to_string()));
+    }
+
+    match drift::check_drift(db, &instance.id) {
+        Ok(report) => match report.status {
+            DriftStatus::Modified => (InstanceStatus::Drifted, None),
+            DriftStatus::Missing => (InstanceStatus::Missing, None),
+            DriftStatus::InSync | DriftStatus::NeverSynced => (InstanceStatus::Ok, None),
+        },
+        Err(e) => (InstanceStatus::Invalid, Some(e.to_string())),
+    }
+}
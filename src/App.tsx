@@ -3,6 +3,8 @@ import { Layout } from "@/components/Layout";
 import { Dashboard } from "@/pages/Dashboard";
 import { Servers } from "@/pages/Servers";
 import { Instances } from "@/pages/Instances";
+import { Capabilities } from "@/pages/Capabilities";
+import { Doctor } from "@/pages/Doctor";
 import { Settings } from "@/pages/Settings";
 import { useEffect } from "react";
 import { useStore } from "@/store";
@@ -44,6 +46,8 @@ function App() {
           <Route index element={<Dashboard />} />
           <Route path="servers" element={<Servers />} />
           <Route path="instances" element={<Instances />} />
+          <Route path="capabilities" element={<Capabilities />} />
+          <Route path="doctor" element={<Doctor />} />
           <Route path="settings" element={<Settings />} />
         </Route>
       </Routes>
@@ -21,8 +21,15 @@ import {
   SelectValue,
 } from "@/components/ui/select";
 import { useStore } from "@/store";
-import type { RegistryServer, RegistrySource, DetectedClient, ClientType } from "@/types";
+import type {
+  RegistryServer,
+  RegistrySource,
+  DetectedClient,
+  ClientType,
+  ImportResult,
+} from "@/types";
 import { CLIENT_TYPE_LABELS } from "@/types";
+import { errorMessage } from "@/lib/utils";
 import {
   FileJson,
   Download,
@@ -144,7 +151,7 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
       const servers = await getRegistryServers(registryId);
       setRegistryServers(servers);
     } catch (err) {
-      setError(err instanceof Error ? err.message : "Failed to load registry");
+      setError(errorMessage(err, "Failed to load registry"));
       setRegistryServers([]);
     } finally {
       setLoading(false);
@@ -241,6 +248,35 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
     setSelectedServers(new Set());
   };
 
+  // Report what was imported and which secrets went into the keyring. The
+  // dialog stays open when some could not be stored there.
+  const showResult = (result: ImportResult) => {
+    const describe = (secrets: ImportResult["secrets"]) =>
+      secrets.map((s) => `${s.envVar} (${s.serverName})`).join(", ");
+    const moved = result.secrets.filter((s) => !s.error);
+    const kept = result.secrets.filter((s) => s.error);
+
+    let message = `Successfully imported ${result.servers.length} server(s)`;
+    if (moved.length > 0) {
+      message += ` and moved ${describe(moved)} into the keyring`;
+    }
+    setSuccessMessage(message);
+
+    if (kept.length > 0) {
+      setError(
+        `${describe(kept)} looked like secrets but were imported as they are: ${kept[0].error}`
+      );
+      return;
+    }
+    setTimeout(
+      () => {
+        onOpenChange(false);
+        setSuccessMessage(null);
+      },
+      moved.length > 0 ? 4000 : 1500
+    );
+  };
+
   const handleImportFromRegistry = async () => {
     if (selectedServers.size === 0) return;
 
@@ -250,15 +286,11 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
       const serversToImport = registryServers.filter((s) =>
         selectedServers.has(s.name)
       );
-      const imported = await importFromRegistry(selectedRegistry, serversToImport);
-      setSuccessMessage(`Successfully imported ${imported.length} server(s)`);
+      const result = await importFromRegistry(selectedRegistry, serversToImport);
       setSelectedServers(new Set());
-      setTimeout(() => {
-        onOpenChange(false);
-        setSuccessMessage(null);
-      }, 1500);
+      showResult(result);
     } catch (err) {
-      setError(err instanceof Error ? err.message : "Failed to import servers");
+      setError(errorMessage(err, "Failed to import servers"));
     } finally {
       setImporting(false);
     }
@@ -270,8 +302,8 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
         multiple: false,
         filters: [
           {
-            name: "JSON",
-            extensions: ["json"],
+            name: "Config files",
+            extensions: ["json", "yaml", "yml", "toml"],
           },
         ],
       });
@@ -279,15 +311,10 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
       if (selected) {
         setImporting(true);
         setError(null);
-        const imported = await importFromFile(selected);
-        setSuccessMessage(`Successfully imported ${imported.length} server(s)`);
-        setTimeout(() => {
-          onOpenChange(false);
-          setSuccessMessage(null);
-        }, 1500);
+        showResult(await importFromFile(selected));
       }
     } catch (err) {
-      setError(err instanceof Error ? err.message : "Failed to import file");
+      setError(errorMessage(err, "Failed to import file"));
     } finally {
       setImporting(false);
     }
@@ -299,14 +326,9 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
     setImporting(true);
     setError(null);
     try {
-      const imported = await importFromFile(client.configPath);
-      setSuccessMessage(`Successfully imported ${imported.length} server(s)`);
-      setTimeout(() => {
-        onOpenChange(false);
-        setSuccessMessage(null);
-      }, 1500);
+      showResult(await importFromFile(client.configPath));
     } catch (err) {
-      setError(err instanceof Error ? err.message : "Failed to import from client");
+      setError(errorMessage(err, "Failed to import from client"));
     } finally {
       setImporting(false);
     }
@@ -561,7 +583,7 @@ export function ImportDialog({ open, onOpenChange }: ImportDialogProps) {
               <FolderOpen className="h-12 w-12 mx-auto text-muted-foreground mb-4" />
               <h3 className="font-medium mb-2">Import from Config File</h3>
               <p className="text-sm text-muted-foreground mb-4">
-                Select a JSON configuration file containing MCP server definitions
+                Select a JSON, YAML or TOML configuration file containing MCP server definitions
               </p>
               <Button onClick={handleImportFromFile} disabled={importing}>
                 {importing && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
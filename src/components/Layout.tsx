@@ -1,24 +1,52 @@
+import { useEffect } from "react";
 import { NavLink, Outlet } from "react-router-dom";
+import { listen } from "@tauri-apps/api/event";
 import {
   LayoutDashboard,
   Server,
   Layers,
+  Wrench,
+  Stethoscope,
   Settings,
   RefreshCw,
 } from "lucide-react";
 import { cn } from "@/lib/utils";
 import { Button } from "@/components/ui/button";
 import { useStore } from "@/store";
+import type { ConfigFileEvent, HealthChangeEvent } from "@/types";
 
 const navigation = [
   { name: "Dashboard", href: "/", icon: LayoutDashboard },
   { name: "Servers", href: "/servers", icon: Server },
   { name: "Instances", href: "/instances", icon: Layers },
+  { name: "Capabilities", href: "/capabilities", icon: Wrench },
+  { name: "Doctor", href: "/doctor", icon: Stethoscope },
   { name: "Settings", href: "/settings", icon: Settings },
 ];
 
 export function Layout() {
-  const { syncAllInstances, instances } = useStore();
+  const { syncAllInstances, instances, applyConfigFileEvent, loadHealthStats } =
+    useStore();
+
+  // Keep instance status current as the backend watcher sees config files change
+  useEffect(() => {
+    const unlisten = listen<ConfigFileEvent>("config-file-changed", (event) =>
+      applyConfigFileEvent(event.payload)
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applyConfigFileEvent]);
+
+  // Refresh uptime figures when the health monitor sees a server change state
+  useEffect(() => {
+    const unlisten = listen<HealthChangeEvent>("server-health-changed", () =>
+      loadHealthStats()
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadHealthStats]);
 
   const handleSyncAll = async () => {
     try {
@@ -1,6 +1,27 @@
 import { type ClassValue, clsx } from "clsx";
 import { twMerge } from "tailwind-merge";
+import type { HubError } from "@/types";
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+export function isHubError(error: unknown): error is HubError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    "kind" in error &&
+    "message" in error
+  );
+}
+
+/** The message of an error thrown by a backend command or in the frontend */
+export function errorMessage(error: unknown, fallback?: string): string {
+  if (isHubError(error) || error instanceof Error) {
+    return error.message;
+  }
+  if (typeof error === "string") {
+    return error;
+  }
+  return fallback ?? String(error);
+}
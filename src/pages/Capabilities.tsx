@@ -0,0 +1,150 @@
+import { useEffect, useState } from "react";
+import { RefreshCw, Search, Wrench } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Badge } from "@/components/ui/badge";
+import {
+  Card,
+  CardContent,
+  CardDescription,
+  CardHeader,
+  CardTitle,
+} from "@/components/ui/card";
+import { useStore } from "@/store";
+import type { CapabilityInventory, CapabilityMatch } from "@/types";
+
+export function Capabilities() {
+  const { servers, loadServers, searchCapabilities, probeAllCapabilities } =
+    useStore();
+  const [query, setQuery] = useState("");
+  const [matches, setMatches] = useState<CapabilityMatch[]>([]);
+  const [probing, setProbing] = useState(false);
+  const [probed, setProbed] = useState<CapabilityInventory[]>([]);
+
+  useEffect(() => {
+    loadServers();
+  }, [loadServers]);
+
+  useEffect(() => {
+    const handle = setTimeout(() => {
+      searchCapabilities(query)
+        .then(setMatches)
+        .catch((error) => console.error("Failed to search capabilities:", error));
+    }, 200);
+    return () => clearTimeout(handle);
+  }, [query, searchCapabilities]);
+
+  const handleProbeAll = async () => {
+    setProbing(true);
+    try {
+      setProbed(await probeAllCapabilities());
+      setMatches(await searchCapabilities(query));
+    } catch (error) {
+      console.error("Failed to probe servers:", error);
+    } finally {
+      setProbing(false);
+    }
+  };
+
+  const serverName = (id: string) =>
+    servers.find((s) => s.id === id)?.name ?? id;
+  const changed = probed.filter((inventory) => inventory.changes.length > 0);
+
+  return (
+    <div className="p-8">
+      <div className="flex items-center justify-between mb-8">
+        <div>
+          <h1 className="text-3xl font-bold tracking-tight">Capabilities</h1>
+          <p className="text-muted-foreground mt-2">
+            Find which server offers a tool, resource or prompt
+          </p>
+        </div>
+        <Button onClick={handleProbeAll} disabled={probing}>
+          <RefreshCw
+            className={`w-4 h-4 mr-2 ${probing ? "animate-spin" : ""}`}
+          />
+          Probe All Servers
+        </Button>
+      </div>
+
+      {changed.length > 0 && (
+        <Card className="mb-6">
+          <CardHeader>
+            <CardTitle>Changes Since Last Probe</CardTitle>
+            <CardDescription>
+              Servers whose capabilities differ from the previous probe
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-3">
+            {changed.map((inventory) => (
+              <div key={inventory.serverId}>
+                <p className="font-medium">{serverName(inventory.serverId)}</p>
+                <ul className="text-sm text-muted-foreground">
+                  {inventory.changes.map((change) => (
+                    <li key={`${change.kind}:${change.name}`}>
+                      {change.change === "added"
+                        ? "+"
+                        : change.change === "removed"
+                          ? "-"
+                          : "~"}{" "}
+                      {change.kind} <code>{change.name}</code>
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            ))}
+          </CardContent>
+        </Card>
+      )}
+
+      <div className="mb-6">
+        <div className="relative">
+          <Search className="absolute left-3 top-1/2 -translate-y-1/2 w-4 h-4 text-muted-foreground" />
+          <Input
+            placeholder="Search tools, resources and prompts..."
+            value={query}
+            onChange={(e) => setQuery(e.target.value)}
+            className="pl-9 max-w-sm"
+          />
+        </div>
+      </div>
+
+      {matches.length === 0 ? (
+        <Card>
+          <CardContent className="flex flex-col items-center justify-center py-12">
+            <Wrench className="w-12 h-12 text-muted-foreground mb-4" />
+            <h3 className="text-lg font-medium mb-2">No capabilities found</h3>
+            <p className="text-muted-foreground text-center">
+              {query
+                ? "Try a different search term"
+                : "Probe your servers to see what they offer"}
+            </p>
+          </CardContent>
+        </Card>
+      ) : (
+        <div className="space-y-2">
+          {matches.map((match) => (
+            <Card key={`${match.serverId}:${match.kind}:${match.name}`}>
+              <CardContent className="py-3 flex items-start justify-between gap-4">
+                <div>
+                  <div className="flex items-center gap-2">
+                    <code className="font-medium">{match.name}</code>
+                    <Badge variant="outline" className="text-xs">
+                      {match.kind}
+                    </Badge>
+                  </div>
+                  {match.description && (
+                    <p className="text-sm text-muted-foreground mt-1">
+                      {match.description}
+                    </p>
+                  )}
+                </div>
+                <Badge variant="secondary">{match.serverName}</Badge>
+              </CardContent>
+            </Card>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
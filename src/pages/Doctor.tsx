@@ -0,0 +1,161 @@
+import { useEffect, useState } from "react";
+import { AlertCircle, CheckCircle, RefreshCw, XCircle } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Badge } from "@/components/ui/badge";
+import {
+  Card,
+  CardContent,
+  CardDescription,
+  CardHeader,
+  CardTitle,
+} from "@/components/ui/card";
+import { useStore } from "@/store";
+import type { DoctorIssue, DoctorReport } from "@/types";
+
+function IssueItem({ issue }: { issue: DoctorIssue }) {
+  return (
+    <div className="flex items-start gap-2 text-sm">
+      {issue.severity === "error" ? (
+        <XCircle className="w-4 h-4 mt-0.5 text-red-500 shrink-0" />
+      ) : (
+        <AlertCircle className="w-4 h-4 mt-0.5 text-yellow-500 shrink-0" />
+      )}
+      <div>
+        <p>{issue.message}</p>
+        <p className="text-muted-foreground">{issue.fix}</p>
+      </div>
+    </div>
+  );
+}
+
+export function Doctor() {
+  const { runDoctor } = useStore();
+  const [report, setReport] = useState<DoctorReport | null>(null);
+  const [running, setRunning] = useState(false);
+
+  const handleRun = async () => {
+    setRunning(true);
+    try {
+      setReport(await runDoctor());
+    } catch (error) {
+      console.error("Failed to run doctor:", error);
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  useEffect(() => {
+    runDoctor()
+      .then(setReport)
+      .catch((error) => console.error("Failed to run doctor:", error));
+  }, [runDoctor]);
+
+  const serverName = (id: string) =>
+    report?.servers.find((s) => s.serverId === id)?.serverName ?? id;
+  // Issues that depend on how an instance's client starts its servers; the
+  // rest are already shown with the server
+  const particularIssues = (issues: DoctorIssue[]) =>
+    issues.filter(
+      (issue) =>
+        !report?.servers.some((s) =>
+          s.issues.some(
+            (i) => i.serverId === issue.serverId && i.message === issue.message
+          )
+        )
+    );
+  const instances = (report?.instances ?? [])
+    .map((instance) => ({
+      ...instance,
+      issues: particularIssues(instance.issues),
+    }))
+    .filter((instance) => instance.issues.length > 0);
+
+  return (
+    <div className="p-8">
+      <div className="flex items-center justify-between mb-8">
+        <div>
+          <h1 className="text-3xl font-bold tracking-tight">Doctor</h1>
+          <p className="text-muted-foreground mt-2">
+            Check that server commands and the runtimes they need can be found
+          </p>
+        </div>
+        <Button onClick={handleRun} disabled={running}>
+          <RefreshCw
+            className={`w-4 h-4 mr-2 ${running ? "animate-spin" : ""}`}
+          />
+          Run Checks
+        </Button>
+      </div>
+
+      {instances.length > 0 && (
+        <Card className="mb-6">
+          <CardHeader>
+            <CardTitle>Instances</CardTitle>
+            <CardDescription>
+              Problems particular to how each client starts its servers
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            {instances.map((instance) => (
+              <div key={instance.instanceId} className="space-y-2">
+                <p className="font-medium">{instance.instanceName}</p>
+                {instance.issues.map((issue) => (
+                  <div key={`${issue.serverId}:${issue.message}`}>
+                    <p className="text-sm font-medium text-muted-foreground">
+                      {serverName(issue.serverId)}
+                    </p>
+                    <IssueItem issue={issue} />
+                  </div>
+                ))}
+              </div>
+            ))}
+          </CardContent>
+        </Card>
+      )}
+
+      <div className="space-y-2">
+        {report?.servers.map((server) => {
+          const location =
+            server.resolvedPath ?? server.guiPath ?? server.foundElsewhere;
+          return (
+            <Card key={server.serverId}>
+              <CardContent className="py-3 space-y-2">
+                <div className="flex items-center justify-between gap-4">
+                  <div className="flex items-center gap-2">
+                    {server.issues.length === 0 ? (
+                      <CheckCircle className="w-4 h-4 text-green-500" />
+                    ) : server.issues.some((i) => i.severity === "error") ? (
+                      <XCircle className="w-4 h-4 text-red-500" />
+                    ) : (
+                      <AlertCircle className="w-4 h-4 text-yellow-500" />
+                    )}
+                    <span className="font-medium">{server.serverName}</span>
+                    <code className="text-sm text-muted-foreground">
+                      {location ?? server.command}
+                    </code>
+                  </div>
+                  <div className="flex gap-1">
+                    {server.versionManager && (
+                      <Badge variant="outline">{server.versionManager}</Badge>
+                    )}
+                    {server.runtimes.map((runtime) => (
+                      <Badge
+                        key={runtime.name}
+                        variant={runtime.ok ? "secondary" : "destructive"}
+                      >
+                        {runtime.name} {runtime.version ?? "?"}
+                      </Badge>
+                    ))}
+                  </div>
+                </div>
+                {server.issues.map((issue) => (
+                  <IssueItem key={issue.message} issue={issue} />
+                ))}
+              </CardContent>
+            </Card>
+          );
+        })}
+      </div>
+    </div>
+  );
+}
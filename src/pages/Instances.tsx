@@ -24,6 +24,7 @@ import {
 import { Badge } from "@/components/ui/badge";
 import { Checkbox } from "@/components/ui/checkbox";
 import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
 import { Switch } from "@/components/ui/switch";
 import {
   Dialog,
@@ -34,13 +35,22 @@ import {
   DialogTitle,
 } from "@/components/ui/dialog";
 import { useStore } from "@/store";
-import { CLIENT_TYPE_LABELS, type ClientInstance, type ClientType, type McpServerEntry } from "@/types";
+import {
+  CLIENT_TYPE_LABELS,
+  type ClientInstance,
+  type ClientType,
+  type McpServerEntry,
+  type ToolFilter,
+  type ServerOverride,
+} from "@/types";
 
 interface InstanceFormData {
   name: string;
   clientType: ClientType;
   configPath: string;
   isDefault: boolean;
+  resolveCommands: boolean;
+  injectPath: boolean;
 }
 
 const emptyFormData: InstanceFormData = {
@@ -48,6 +58,8 @@ const emptyFormData: InstanceFormData = {
   clientType: "claude-desktop",
   configPath: "",
   isDefault: false,
+  resolveCommands: false,
+  injectPath: false,
 };
 
 export function Instances() {
@@ -60,6 +72,8 @@ export function Instances() {
     updateInstance,
     deleteInstance,
     setServerEnabled,
+    setToolFilter,
+    setServerOverride,
     syncInstance,
     detectClients,
     detectedClients,
@@ -140,6 +154,8 @@ export function Instances() {
         clientType: instance.clientType,
         configPath: instance.configPath,
         isDefault: instance.isDefault,
+        resolveCommands: instance.resolveCommands,
+        injectPath: instance.injectPath,
       });
     } else {
       setEditingInstance(null);
@@ -151,6 +167,8 @@ export function Instances() {
           clientType: first.clientType,
           configPath: first.configPath,
           isDefault: false,
+          resolveCommands: false,
+          injectPath: false,
         };
         setFormData(newFormData);
         // Check for existing config
@@ -183,6 +201,8 @@ export function Instances() {
           clientType: formData.clientType,
           configPath: formData.configPath,
           isDefault: formData.isDefault,
+          resolveCommands: formData.resolveCommands,
+          injectPath: formData.injectPath,
         });
       } else {
         await createInstance({
@@ -191,7 +211,10 @@ export function Instances() {
           clientType: formData.clientType,
           configPath: formData.configPath,
           enabledServers: [],
+          resolveCommands: formData.resolveCommands,
+          injectPath: formData.injectPath,
           isDefault: formData.isDefault,
+          status: "unknown",
           createdAt: now,
         });
       }
@@ -242,6 +265,86 @@ export function Instances() {
     }
   };
 
+  const parsePatterns = (text: string) =>
+    text
+      .split(",")
+      .map((pattern) => pattern.trim())
+      .filter(Boolean);
+
+  const handleToolFilterChange = async (
+    serverId: string,
+    field: keyof ToolFilter,
+    text: string
+  ) => {
+    if (!selectedInstance) return;
+    const current = selectedInstance.toolFilters?.[serverId] ?? {
+      allow: [],
+      deny: [],
+    };
+    const filter = { ...current, [field]: parsePatterns(text) };
+    if (filter[field].join(",") === current[field].join(",")) return;
+    try {
+      await setToolFilter(selectedInstance.id, serverId, filter);
+      setSelectedInstance((prev) => {
+        if (!prev) return prev;
+        return {
+          ...prev,
+          toolFilters: { ...prev.toolFilters, [serverId]: filter },
+        };
+      });
+    } catch (error) {
+      console.error("Failed to update tool filter:", error);
+    }
+  };
+
+  const parseLines = (text: string) =>
+    text
+      .split("\n")
+      .map((line) => line.trim())
+      .filter(Boolean);
+
+  const handleOverrideChange = async (
+    serverId: string,
+    field: keyof ServerOverride,
+    text: string
+  ) => {
+    if (!selectedInstance) return;
+    const current: ServerOverride =
+      selectedInstance.serverOverrides?.[serverId] ?? {};
+    const serverOverride: ServerOverride = { ...current };
+    if (field === "args") {
+      const args = parseLines(text);
+      serverOverride.args = args.length > 0 ? args : undefined;
+    } else if (field === "env") {
+      serverOverride.env = Object.fromEntries(
+        parseLines(text)
+          .map((line) => line.split("="))
+          .filter(([key]) => key.trim())
+          .map(([key, ...value]) => [key.trim(), value.join("=").trim()])
+      );
+    } else {
+      serverOverride.alias = text.trim() || undefined;
+    }
+    const unchanged =
+      JSON.stringify(serverOverride[field]) === JSON.stringify(current[field]);
+    if (unchanged) return;
+    try {
+      await setServerOverride(selectedInstance.id, serverId, serverOverride);
+      setSelectedInstance((prev) => {
+        if (!prev) return prev;
+        return {
+          ...prev,
+          serverOverrides: {
+            ...prev.serverOverrides,
+            [serverId]: serverOverride,
+          },
+        };
+      });
+    } catch (error) {
+      console.error("Failed to update server override:", error);
+    }
+  };
+
   const handleSync = async (instanceId: string) => {
     setSyncing(instanceId);
     try {
@@ -367,6 +470,20 @@ export function Instances() {
                             Needs sync
                           </Badge>
                         )}
+                        {instance.status === "drifted" && (
+                          <Badge variant="outline" className="text-xs text-yellow-600 border-yellow-400 bg-yellow-50 dark:bg-yellow-950 dark:text-yellow-400">
+                            Edited externally
+                          </Badge>
+                        )}
+                        {(instance.status === "missing" || instance.status === "invalid") && (
+                          <Badge
+                            variant="destructive"
+                            className="text-xs"
+                            title={instance.statusMessage}
+                          >
+                            {instance.status === "missing" ? "Config missing" : "Invalid config"}
+                          </Badge>
+                        )}
                       </CardTitle>
                       <CardDescription>
                         {CLIENT_TYPE_LABELS[instance.clientType]}
@@ -600,6 +717,44 @@ export function Instances() {
                 }
               />
             </div>
+            <div className="flex items-center justify-between">
+              <div>
+                <Label htmlFor="resolveCommands">Absolute Command Paths</Label>
+                <p className="text-xs text-muted-foreground">
+                  Write commands like npx as full paths, for clients started
+                  without your shell's PATH
+                </p>
+              </div>
+              <Switch
+                id="resolveCommands"
+                checked={formData.resolveCommands}
+                onCheckedChange={(checked) =>
+                  setFormData({
+                    ...formData,
+                    resolveCommands: checked,
+                    injectPath: checked && formData.injectPath,
+                  })
+                }
+              />
+            </div>
+            {formData.resolveCommands && (
+              <div className="flex items-center justify-between">
+                <div>
+                  <Label htmlFor="injectPath">Pass Shell PATH</Label>
+                  <p className="text-xs text-muted-foreground">
+                    Give servers your login shell's PATH, so programs they start
+                    are found too
+                  </p>
+                </div>
+                <Switch
+                  id="injectPath"
+                  checked={formData.injectPath}
+                  onCheckedChange={(checked) =>
+                    setFormData({ ...formData, injectPath: checked })
+                  }
+                />
+              </div>
+            )}
           </div>
           <DialogFooter>
             <Button variant="outline" onClick={handleCloseDialog}>
@@ -635,11 +790,11 @@ export function Instances() {
                   const isEnabled =
                     selectedInstance?.enabledServers.includes(server.id) ??
                     false;
+                  const toolFilter = selectedInstance?.toolFilters?.[server.id];
+                  const serverOverride =
+                    selectedInstance?.serverOverrides?.[server.id];
                   return (
-                    <div
-                      key={server.id}
-                      className="flex items-center justify-between p-3 rounded-lg border"
-                    >
+                    <div key={server.id} className="p-3 rounded-lg border">
                       <div className="flex items-center gap-3">
                         <Checkbox
                           id={server.id}
@@ -660,6 +815,76 @@ export function Instances() {
                           </p>
                         </div>
                       </div>
+                      {isEnabled && (
+                        <div className="grid grid-cols-2 gap-2 mt-3">
+                          <Input
+                            placeholder="Allow tools, e.g. read_*"
+                            defaultValue={toolFilter?.allow.join(", ")}
+                            onBlur={(e) =>
+                              handleToolFilterChange(
+                                server.id,
+                                "allow",
+                                e.target.value
+                              )
+                            }
+                            className="h-8 text-xs"
+                          />
+                          <Input
+                            placeholder="Deny tools, e.g. delete_*"
+                            defaultValue={toolFilter?.deny.join(", ")}
+                            onBlur={(e) =>
+                              handleToolFilterChange(
+                                server.id,
+                                "deny",
+                                e.target.value
+                              )
+                            }
+                            className="h-8 text-xs"
+                          />
+                          <Input
+                            placeholder={`Name in this config (${server.name})`}
+                            defaultValue={serverOverride?.alias}
+                            onBlur={(e) =>
+                              handleOverrideChange(
+                                server.id,
+                                "alias",
+                                e.target.value
+                              )
+                            }
+                            className="h-8 text-xs col-span-2"
+                          />
+                          <Textarea
+                            placeholder="Arguments instead of the server's, one per line"
+                            defaultValue={serverOverride?.args?.join("\n")}
+                            onBlur={(e) =>
+                              handleOverrideChange(
+                                server.id,
+                                "args",
+                                e.target.value
+                              )
+                            }
+                            rows={2}
+                            className="text-xs"
+                          />
+                          <Textarea
+                            placeholder="Extra env vars, KEY=value"
+                            defaultValue={Object.entries(
+                              serverOverride?.env ?? {}
+                            )
+                              .map(([k, v]) => `${k}=${v}`)
+                              .join("\n")}
+                            onBlur={(e) =>
+                              handleOverrideChange(
+                                server.id,
+                                "env",
+                                e.target.value
+                              )
+                            }
+                            rows={2}
+                            className="text-xs"
+                          />
+                        </div>
+                      )}
                     </div>
                   );
                 })}
@@ -28,16 +28,20 @@ import {
 } from "@/components/ui/dialog";
 import { Label } from "@/components/ui/label";
 import { Textarea } from "@/components/ui/textarea";
+import { Switch } from "@/components/ui/switch";
 import { useStore } from "@/store";
 import { ImportDialog } from "@/components/ImportDialog";
-import type { McpServer } from "@/types";
+import type { ArgsMode, McpServer } from "@/types";
 
 interface ServerFormData {
   name: string;
   description: string;
   command: string;
   args: string;
+  argsMode: ArgsMode;
   env: string;
+  /** Move plain env values into the keyring when saving */
+  secureEnv: boolean;
   tags: string;
 }
 
@@ -46,13 +50,24 @@ const emptyFormData: ServerFormData = {
   description: "",
   command: "",
   args: "",
+  argsMode: "replace",
   env: "",
+  secureEnv: false,
   tags: "",
 };
 
 export function Servers() {
-  const { servers, loadServers, createServer, updateServer, deleteServer } =
-    useStore();
+  const {
+    servers,
+    loadServers,
+    createServer,
+    updateServer,
+    deleteServer,
+    getEffectiveServer,
+    secureServerEnv,
+    healthStats,
+    loadHealthStats,
+  } = useStore();
   const [searchQuery, setSearchQuery] = useState("");
   const [isDialogOpen, setIsDialogOpen] = useState(false);
   const [isImportDialogOpen, setIsImportDialogOpen] = useState(false);
@@ -60,10 +75,27 @@ export function Servers() {
   const [formData, setFormData] = useState<ServerFormData>(emptyFormData);
   const [isDeleteDialogOpen, setIsDeleteDialogOpen] = useState(false);
   const [serverToDelete, setServerToDelete] = useState<McpServer | null>(null);
+  // Instances with what they inherit from their parents filled in
+  const [effectiveServers, setEffectiveServers] = useState<
+    Record<string, McpServer>
+  >({});
 
   useEffect(() => {
     loadServers();
-  }, [loadServers]);
+    loadHealthStats();
+  }, [loadServers, loadHealthStats]);
+
+  useEffect(() => {
+    const instances = servers.filter((s) => s.parentId);
+    Promise.all(
+      instances.map((s) => getEffectiveServer(s.id).catch(() => s))
+    ).then((resolved) =>
+      setEffectiveServers(Object.fromEntries(resolved.map((s) => [s.id, s])))
+    );
+  }, [servers, getEffectiveServer]);
+
+  const getEffective = (server: McpServer) =>
+    effectiveServers[server.id] ?? server;
 
   // Filter servers based on search query
   const matchesSearch = (server: McpServer) =>
@@ -126,9 +158,11 @@ export function Servers() {
         description: server.description || "",
         command: server.command,
         args: server.args.join("\n"),
+        argsMode: server.argsMode ?? "replace",
         env: Object.entries(server.env)
           .map(([k, v]) => `${k}=${v}`)
           .join("\n"),
+        secureEnv: false,
         tags: server.tags.join(", "),
       });
     } else {
@@ -169,25 +203,29 @@ export function Servers() {
 
       const now = new Date().toISOString();
 
+      let saved: McpServer;
       if (editingServer) {
-        await updateServer({
+        saved = await updateServer({
           ...editingServer,
           name: formData.name,
           description: formData.description || undefined,
           command: formData.command,
           args,
+          argsMode: formData.argsMode,
           env,
           tags,
           updatedAt: now,
         });
       } else {
-        await createServer({
+        saved = await createServer({
           id: crypto.randomUUID(),
           name: formData.name,
           description: formData.description || undefined,
           command: formData.command,
           args,
+          argsMode: formData.argsMode,
           env,
+          transport: "stdio",
           tags,
           source: { sourceType: "manual" },
           parentId: duplicatingFromId || undefined,
@@ -196,6 +234,13 @@ export function Servers() {
         });
       }
 
+      const plainKeys = Object.entries(env)
+        .filter(([, value]) => !value.startsWith("keyring:"))
+        .map(([key]) => key);
+      if (formData.secureEnv && plainKeys.length > 0) {
+        await secureServerEnv(saved.id, plainKeys);
+      }
+
       handleCloseDialog();
     } catch (error) {
       console.error("Failed to save server:", error);
@@ -220,20 +265,16 @@ export function Servers() {
   };
 
   const handleDuplicate = (server: McpServer) => {
-    // Pre-fill the form with the parent server's data but with a new name
+    // Start from an empty config so the instance inherits everything from
+    // the parent server until a field is overridden
     const instanceNumber = servers.filter(
       (s) => s.parentId === server.id || s.id === server.id
     ).length;
     setEditingServer(null);
     setFormData({
+      ...emptyFormData,
       name: `${server.name} (${instanceNumber + 1})`,
       description: server.description || "",
-      command: server.command,
-      args: server.args.join("\n"),
-      env: Object.entries(server.env)
-        .map(([k, v]) => `${k}=${v}`)
-        .join("\n"),
-      tags: server.tags.join(", "),
     });
     // Store the parent ID for when we create
     setDuplicatingFromId(server.id);
@@ -242,6 +283,13 @@ export function Servers() {
 
   const [duplicatingFromId, setDuplicatingFromId] = useState<string | null>(null);
 
+  // The server the one in the form inherits from
+  const formParentId = editingServer?.parentId ?? duplicatingFromId;
+  const formParent = formParentId
+    ? servers.find((s) => s.id === formParentId)
+    : undefined;
+  const effectiveParent = formParent && getEffective(formParent);
+
   return (
     <div className="p-8">
       <div className="flex items-center justify-between mb-8">
@@ -314,6 +362,19 @@ export function Servers() {
                           instance
                         </Badge>
                       )}
+                      {healthStats[server.id]?.uptime !== undefined && (
+                        <Badge
+                          variant={
+                            healthStats[server.id].lastStatus === "error"
+                              ? "destructive"
+                              : "secondary"
+                          }
+                          className="text-xs"
+                          title={healthStats[server.id].lastError}
+                        >
+                          {Math.round(healthStats[server.id].uptime! * 100)}% up
+                        </Badge>
+                      )}
                     </div>
                     {server.parentId && (
                       <p className="text-xs text-muted-foreground mt-0.5">
@@ -361,17 +422,17 @@ export function Servers() {
                 <div className="space-y-2">
                   <div className="flex items-center gap-2 text-sm">
                     <code className="px-2 py-1 bg-muted rounded text-xs">
-                      {server.command}
+                      {getEffective(server).command}
                     </code>
                   </div>
-                  {server.args.length > 0 && (
+                  {getEffective(server).args.length > 0 && (
                     <div className="text-xs text-muted-foreground truncate">
-                      Args: {server.args.join(" ")}
+                      Args: {getEffective(server).args.join(" ")}
                     </div>
                   )}
-                  {server.tags.length > 0 && (
+                  {getEffective(server).tags.length > 0 && (
                     <div className="flex flex-wrap gap-1 mt-2">
-                      {server.tags.map((tag) => (
+                      {getEffective(server).tags.map((tag) => (
                         <Badge key={tag} variant="secondary" className="text-xs">
                           {tag}
                         </Badge>
@@ -428,10 +489,12 @@ export function Servers() {
               />
             </div>
             <div className="space-y-2">
-              <Label htmlFor="command">Command</Label>
+              <Label htmlFor="command">
+                Command{effectiveParent && " (optional)"}
+              </Label>
               <Input
                 id="command"
-                placeholder="npx"
+                placeholder={effectiveParent?.command || "npx"}
                 value={formData.command}
                 onChange={(e) =>
                   setFormData({ ...formData, command: e.target.value })
@@ -442,13 +505,34 @@ export function Servers() {
               <Label htmlFor="args">Arguments (one per line)</Label>
               <Textarea
                 id="args"
-                placeholder={"-y\n@modelcontextprotocol/server-filesystem\n/path/to/dir"}
+                placeholder={
+                  effectiveParent
+                    ? effectiveParent.args.join("\n")
+                    : "-y\n@modelcontextprotocol/server-filesystem\n/path/to/dir"
+                }
                 value={formData.args}
                 onChange={(e) =>
                   setFormData({ ...formData, args: e.target.value })
                 }
                 rows={3}
               />
+              {effectiveParent && (
+                <div className="flex items-center justify-between">
+                  <Label htmlFor="argsMode">
+                    Add to {effectiveParent.name}'s arguments
+                  </Label>
+                  <Switch
+                    id="argsMode"
+                    checked={formData.argsMode === "append"}
+                    onCheckedChange={(checked) =>
+                      setFormData({
+                        ...formData,
+                        argsMode: checked ? "append" : "replace",
+                      })
+                    }
+                  />
+                </div>
+              )}
             </div>
             <div className="space-y-2">
               <Label htmlFor="env">
@@ -463,6 +547,26 @@ export function Servers() {
                 }
                 rows={2}
               />
+              <p className="text-xs text-muted-foreground">
+                Values written as keyring:NAME are read from the keyring when
+                syncing
+              </p>
+              {effectiveParent && (
+                <p className="text-xs text-muted-foreground">
+                  Variables of {effectiveParent.name} are inherited unless set
+                  here
+                </p>
+              )}
+              <div className="flex items-center justify-between">
+                <Label htmlFor="secureEnv">Store values in the keyring</Label>
+                <Switch
+                  id="secureEnv"
+                  checked={formData.secureEnv}
+                  onCheckedChange={(checked) =>
+                    setFormData({ ...formData, secureEnv: checked })
+                  }
+                />
+              </div>
             </div>
             <div className="space-y-2">
               <Label htmlFor="tags">Tags (comma separated)</Label>
@@ -480,7 +584,10 @@ export function Servers() {
             <Button variant="outline" onClick={handleCloseDialog}>
               Cancel
             </Button>
-            <Button onClick={handleSubmit} disabled={!formData.name || !formData.command}>
+            <Button
+              onClick={handleSubmit}
+              disabled={!formData.name || (!formData.command && !formParent)}
+            >
               {editingServer ? "Save Changes" : "Add Server"}
             </Button>
           </DialogFooter>
@@ -11,6 +11,8 @@ import {
   ExternalLink,
   CheckCircle2,
   XCircle,
+  KeyRound,
+  Lock,
 } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
@@ -24,7 +26,14 @@ import {
   CardTitle,
 } from "@/components/ui/card";
 import { useStore } from "@/store";
-import type { AppSettings, DiscoveryStatus, DiscoverySettings } from "@/types";
+import { errorMessage } from "@/lib/utils";
+import type {
+  AppSettings,
+  CredentialStatus,
+  DiscoveryStatus,
+  DiscoverySettings,
+  IntegrityReport,
+} from "@/types";
 import { invoke } from "@tauri-apps/api/core";
 
 const DEFAULT_DISCOVERY_SETTINGS: DiscoverySettings = {
@@ -42,6 +51,13 @@ export function Settings() {
   const [discoveryStatus, setDiscoveryStatus] =
     useState<DiscoveryStatus | null>(null);
   const [refreshingDiscovery, setRefreshingDiscovery] = useState(false);
+  const [credentialStatus, setCredentialStatus] =
+    useState<CredentialStatus | null>(null);
+  const [passphrase, setPassphrase] = useState("");
+  const [vaultError, setVaultError] = useState<string | null>(null);
+  const [integrityReport, setIntegrityReport] =
+    useState<IntegrityReport | null>(null);
+  const [checkingIntegrity, setCheckingIntegrity] = useState(false);
 
   const loadDiscoveryStatus = useCallback(async () => {
     try {
@@ -52,11 +68,21 @@ export function Settings() {
     }
   }, []);
 
+  const loadCredentialStatus = useCallback(async () => {
+    try {
+      const status = await invoke<CredentialStatus>("get_credential_status");
+      setCredentialStatus(status);
+    } catch (error) {
+      console.error("Failed to load credential status:", error);
+    }
+  }, []);
+
   useEffect(() => {
     loadSettings();
     invoke<string>("get_app_data_dir").then(setAppDataDir).catch(console.error);
     loadDiscoveryStatus();
-  }, [loadSettings, loadDiscoveryStatus]);
+    loadCredentialStatus();
+  }, [loadSettings, loadDiscoveryStatus, loadCredentialStatus]);
 
   useEffect(() => {
     // Ensure discovery settings have defaults
@@ -88,6 +114,9 @@ export function Settings() {
         });
         await loadDiscoveryStatus();
       }
+      if (localSettings.credentialBackend !== settings.credentialBackend) {
+        await loadCredentialStatus();
+      }
     } catch (error) {
       console.error("Failed to save settings:", error);
     } finally {
@@ -107,6 +136,39 @@ export function Settings() {
     }
   };
 
+  const handleUnlockVault = async () => {
+    setVaultError(null);
+    try {
+      setCredentialStatus(
+        await invoke<CredentialStatus>("unlock_vault", { passphrase })
+      );
+      setPassphrase("");
+    } catch (error) {
+      setVaultError(errorMessage(error));
+    }
+  };
+
+  const handleLockVault = async () => {
+    try {
+      setCredentialStatus(await invoke<CredentialStatus>("lock_vault"));
+    } catch (error) {
+      console.error("Failed to lock vault:", error);
+    }
+  };
+
+  const handleCheckIntegrity = async (repair: boolean) => {
+    setCheckingIntegrity(true);
+    try {
+      setIntegrityReport(
+        await invoke<IntegrityReport>("check_database_integrity", { repair })
+      );
+    } catch (error) {
+      console.error("Failed to check database:", error);
+    } finally {
+      setCheckingIntegrity(false);
+    }
+  };
+
   const updateDiscoverySettings = (
     updates: Partial<DiscoverySettings>
   ) => {
@@ -242,6 +304,27 @@ export function Settings() {
                 Number of days to keep backup files
               </p>
             </div>
+            <div className="space-y-2">
+              <Label htmlFor="retentionCount">Backups per Instance</Label>
+              <Input
+                id="retentionCount"
+                type="number"
+                min="1"
+                max="500"
+                value={localSettings.backupRetentionCount}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    backupRetentionCount: parseInt(e.target.value) || 20,
+                  })
+                }
+                className="max-w-[120px]"
+                disabled={!localSettings.createBackups}
+              />
+              <p className="text-xs text-muted-foreground">
+                Number of most recent backups to keep for each instance
+              </p>
+            </div>
           </CardContent>
         </Card>
 
@@ -270,6 +353,204 @@ export function Settings() {
           </CardContent>
         </Card>
 
+        {/* Health Checks */}
+        <Card>
+          <CardHeader>
+            <CardTitle>Health Checks</CardTitle>
+            <CardDescription>
+              Configure how servers are checked with an MCP handshake
+            </CardDescription>
+          </CardHeader>
+          <CardContent>
+            <div className="space-y-2">
+              <Label htmlFor="healthTimeout">Timeout (seconds)</Label>
+              <Input
+                id="healthTimeout"
+                type="number"
+                min="1"
+                max="300"
+                value={localSettings.healthCheckTimeoutSecs}
+                onChange={(e) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    healthCheckTimeoutSecs: parseInt(e.target.value) || 30,
+                  })
+                }
+                className="max-w-[120px]"
+              />
+              <p className="text-xs text-muted-foreground">
+                How long a server may take to start and answer the handshake
+              </p>
+            </div>
+            <div className="flex items-center justify-between mt-6">
+              <div className="space-y-0.5">
+                <Label htmlFor="healthMonitor">Background checks</Label>
+                <p className="text-sm text-muted-foreground">
+                  Periodically check servers used by an instance and notify
+                  when one stops working
+                </p>
+              </div>
+              <Switch
+                id="healthMonitor"
+                checked={localSettings.healthMonitorEnabled}
+                onCheckedChange={(checked) =>
+                  setLocalSettings({
+                    ...localSettings,
+                    healthMonitorEnabled: checked,
+                  })
+                }
+              />
+            </div>
+            <div className="grid grid-cols-2 gap-4 mt-4">
+              <div className="space-y-2">
+                <Label htmlFor="healthInterval">Check every (minutes)</Label>
+                <Input
+                  id="healthInterval"
+                  type="number"
+                  min="1"
+                  max="1440"
+                  value={localSettings.healthCheckIntervalMins}
+                  disabled={!localSettings.healthMonitorEnabled}
+                  onChange={(e) =>
+                    setLocalSettings({
+                      ...localSettings,
+                      healthCheckIntervalMins: parseInt(e.target.value) || 15,
+                    })
+                  }
+                  className="max-w-[120px]"
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="healthHistory">Keep history (days)</Label>
+                <Input
+                  id="healthHistory"
+                  type="number"
+                  min="0"
+                  max="365"
+                  value={localSettings.healthHistoryDays}
+                  onChange={(e) =>
+                    setLocalSettings({
+                      ...localSettings,
+                      healthHistoryDays: parseInt(e.target.value) || 0,
+                    })
+                  }
+                  className="max-w-[120px]"
+                />
+                <p className="text-xs text-muted-foreground">
+                  0 keeps every check
+                </p>
+              </div>
+            </div>
+          </CardContent>
+        </Card>
+
+        {/* Secrets */}
+        <Card>
+          <CardHeader>
+            <CardTitle>Secrets</CardTitle>
+            <CardDescription>
+              Where env values stored as <code>keyring:NAME</code> references
+              are kept
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid grid-cols-2 gap-2">
+              <button
+                type="button"
+                onClick={() =>
+                  setLocalSettings({
+                    ...localSettings,
+                    credentialBackend: "keyring",
+                  })
+                }
+                className={`flex flex-col items-center gap-2 p-4 rounded-lg border transition-colors ${
+                  localSettings.credentialBackend === "keyring"
+                    ? "border-primary bg-primary/5"
+                    : "border-input hover:bg-muted"
+                }`}
+              >
+                <KeyRound className="w-5 h-5" />
+                <span className="text-sm font-medium">OS Keyring</span>
+              </button>
+              <button
+                type="button"
+                onClick={() =>
+                  setLocalSettings({
+                    ...localSettings,
+                    credentialBackend: "vault",
+                  })
+                }
+                className={`flex flex-col items-center gap-2 p-4 rounded-lg border transition-colors ${
+                  localSettings.credentialBackend === "vault"
+                    ? "border-primary bg-primary/5"
+                    : "border-input hover:bg-muted"
+                }`}
+              >
+                <Lock className="w-5 h-5" />
+                <span className="text-sm font-medium">Encrypted File</span>
+              </button>
+            </div>
+            <p className="text-xs text-muted-foreground">
+              Use the encrypted file where no keyring service runs, e.g. on
+              headless Linux. Secrets already stored are not moved when
+              switching.
+            </p>
+
+            {credentialStatus && (
+              <div className="flex items-center gap-2 text-sm">
+                {credentialStatus.available ? (
+                  <CheckCircle2 className="w-4 h-4 text-green-500" />
+                ) : (
+                  <XCircle className="w-4 h-4 text-red-500" />
+                )}
+                <span>
+                  {credentialStatus.available
+                    ? "Secrets can be stored"
+                    : credentialStatus.error}
+                </span>
+              </div>
+            )}
+
+            {credentialStatus?.backend === "vault" &&
+              (credentialStatus.vaultUnlocked ? (
+                <Button variant="outline" size="sm" onClick={handleLockVault}>
+                  <Lock className="w-4 h-4 mr-2" />
+                  Lock
+                </Button>
+              ) : (
+                <div className="space-y-2">
+                  <Label htmlFor="vaultPassphrase">
+                    {credentialStatus.vaultExists
+                      ? "Passphrase"
+                      : "Choose a passphrase"}
+                  </Label>
+                  <div className="flex gap-2">
+                    <Input
+                      id="vaultPassphrase"
+                      type="password"
+                      value={passphrase}
+                      onChange={(e) => setPassphrase(e.target.value)}
+                      onKeyDown={(e) => {
+                        if (e.key === "Enter") handleUnlockVault();
+                      }}
+                      className="max-w-[240px]"
+                    />
+                    <Button
+                      variant="outline"
+                      onClick={handleUnlockVault}
+                      disabled={!passphrase}
+                    >
+                      {credentialStatus.vaultExists ? "Unlock" : "Create"}
+                    </Button>
+                  </div>
+                  {vaultError && (
+                    <p className="text-xs text-red-500">{vaultError}</p>
+                  )}
+                </div>
+              ))}
+          </CardContent>
+        </Card>
+
         {/* MCP Discovery */}
         <Card>
           <CardHeader>
@@ -458,6 +739,68 @@ export function Settings() {
               <FolderOpen className="w-4 h-4 text-muted-foreground" />
               <code className="text-sm">{appDataDir || "Loading..."}</code>
             </div>
+            <div className="flex items-center gap-2 mt-4">
+              <Button
+                variant="outline"
+                size="sm"
+                onClick={() => handleCheckIntegrity(false)}
+                disabled={checkingIntegrity}
+              >
+                <RefreshCw
+                  className={`w-4 h-4 mr-2 ${checkingIntegrity ? "animate-spin" : ""}`}
+                />
+                Check Database
+              </Button>
+              {integrityReport &&
+                !integrityReport.repaired &&
+                integrityReport.orphans.length > 0 && (
+                  <Button
+                    size="sm"
+                    onClick={() => handleCheckIntegrity(true)}
+                    disabled={checkingIntegrity}
+                  >
+                    Repair
+                  </Button>
+                )}
+            </div>
+            {integrityReport && (
+              <div className="mt-3 space-y-1 text-sm">
+                {integrityReport.corruption.length === 0 &&
+                integrityReport.orphans.length === 0 ? (
+                  <p className="flex items-center gap-2 text-muted-foreground">
+                    <CheckCircle2 className="w-4 h-4 text-green-500" />
+                    No problems found
+                  </p>
+                ) : (
+                  <>
+                    {integrityReport.corruption.map((problem) => (
+                      <p key={problem} className="flex items-center gap-2">
+                        <XCircle className="w-4 h-4 text-red-500" />
+                        {problem}
+                      </p>
+                    ))}
+                    {integrityReport.orphans.map((orphans) => (
+                      <p
+                        key={`${orphans.table}.${orphans.column}`}
+                        className="flex items-center gap-2"
+                      >
+                        {integrityReport.repaired ? (
+                          <CheckCircle2 className="w-4 h-4 text-green-500" />
+                        ) : (
+                          <XCircle className="w-4 h-4 text-yellow-500" />
+                        )}
+                        {orphans.count} {orphans.table} rows refer to missing{" "}
+                        {orphans.parentTable}
+                        {integrityReport.repaired &&
+                          (orphans.repair === "delete"
+                            ? " (deleted)"
+                            : " (reference cleared)")}
+                      </p>
+                    ))}
+                  </>
+                )}
+              </div>
+            )}
           </CardContent>
         </Card>
 
@@ -2,12 +2,29 @@ import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import type {
   McpServer,
+  McpServerEntry,
   ClientInstance,
   AppSettings,
   DetectedClient,
   RegistrySource,
   RegistryServer,
+  SyncPlan,
+  ConfigBackup,
+  BackupPreview,
+  DriftReport,
+  ConfigFileEvent,
+  ReconcileAction,
+  ReconcileResult,
+  CapabilityInventory,
+  CapabilityMatch,
+  ToolFilter,
+  ServerOverride,
+  HealthCheck,
+  HealthStats,
+  DoctorReport,
+  ImportResult,
 } from "@/types";
+import { errorMessage } from "@/lib/utils";
 
 interface AppState {
   // Servers
@@ -20,6 +37,9 @@ interface AppState {
   instancesLoading: boolean;
   instancesError: string | null;
 
+  // Health history, by server ID
+  healthStats: Record<string, HealthStats>;
+
   // Settings
   settings: AppSettings;
   settingsLoading: boolean;
@@ -32,6 +52,8 @@ interface AppState {
   createServer: (server: McpServer) => Promise<McpServer>;
   updateServer: (server: McpServer) => Promise<McpServer>;
   deleteServer: (id: string) => Promise<void>;
+  getEffectiveServer: (id: string) => Promise<McpServer>;
+  secureServerEnv: (serverId: string, names: string[]) => Promise<McpServer>;
 
   loadInstances: () => Promise<void>;
   createInstance: (instance: ClientInstance) => Promise<ClientInstance>;
@@ -43,19 +65,50 @@ interface AppState {
     serverId: string,
     enabled: boolean
   ) => Promise<void>;
-  syncInstance: (instanceId: string) => Promise<string | null>;
-  syncAllInstances: () => Promise<string[]>;
+  setToolFilter: (
+    instanceId: string,
+    serverId: string,
+    filter: ToolFilter
+  ) => Promise<void>;
+  setServerOverride: (
+    instanceId: string,
+    serverId: string,
+    serverOverride: ServerOverride
+  ) => Promise<void>;
+  previewSync: (instanceIds?: string[]) => Promise<SyncPlan[]>;
+  syncInstance: (instanceId: string, fingerprint?: string) => Promise<string | null>;
+  syncAllInstances: (fingerprints?: Record<string, string>) => Promise<string[]>;
+  applyConfigFileEvent: (event: ConfigFileEvent) => void;
+  checkDrift: (instanceIds?: string[]) => Promise<DriftReport[]>;
+  reconcileInstance: (instanceId: string, action: ReconcileAction) => Promise<ReconcileResult>;
+
+  getBackups: (instanceId: string) => Promise<ConfigBackup[]>;
+  previewBackup: (backupId: string) => Promise<BackupPreview>;
+  restoreBackup: (backupId: string) => Promise<ConfigBackup | null>;
+  pruneBackups: () => Promise<number>;
+
+  loadHealthStats: () => Promise<void>;
+  getHealthHistory: (serverId: string, limit?: number) => Promise<HealthCheck[]>;
+
+  probeServerCapabilities: (serverId: string) => Promise<CapabilityInventory>;
+  probeAllCapabilities: () => Promise<CapabilityInventory[]>;
+  getServerCapabilities: (serverId: string) => Promise<CapabilityInventory | null>;
+  searchCapabilities: (query: string) => Promise<CapabilityMatch[]>;
+
+  runDoctor: () => Promise<DoctorReport>;
 
   loadSettings: () => Promise<void>;
   saveSettings: (settings: AppSettings) => Promise<void>;
 
   detectClients: () => Promise<void>;
-  importFromFile: (path: string) => Promise<McpServer[]>;
+  importFromFile: (path: string) => Promise<ImportResult>;
+  getUnmanagedServers: (instanceId: string) => Promise<Record<string, McpServerEntry>>;
+  adoptUnmanagedServers: (instanceId: string, keys?: string[]) => Promise<ImportResult>;
 
   // Registry
   getRegistries: () => Promise<RegistrySource[]>;
   getRegistryServers: (registryId: string) => Promise<RegistryServer[]>;
-  importFromRegistry: (registryId: string, servers: RegistryServer[]) => Promise<McpServer[]>;
+  importFromRegistry: (registryId: string, servers: RegistryServer[]) => Promise<ImportResult>;
 
   // Config reading
   readConfigFile: (path: string) => Promise<{ mcpServers: Record<string, { command: string; args: string[]; env?: Record<string, string> }> } | null>;
@@ -76,6 +129,12 @@ export const useStore = create<AppState>((set, get) => ({
     autoStart: false,
     createBackups: true,
     backupRetentionDays: 30,
+    backupRetentionCount: 20,
+    healthCheckTimeoutSecs: 30,
+    healthMonitorEnabled: false,
+    healthCheckIntervalMins: 15,
+    healthHistoryDays: 14,
+    credentialBackend: "keyring",
     discovery: {
       mcpDirectoryEnabled: false,
       httpServerEnabled: false,
@@ -84,6 +143,8 @@ export const useStore = create<AppState>((set, get) => ({
   },
   settingsLoading: false,
 
+  healthStats: {},
+
   detectedClients: [],
 
   // Server actions
@@ -94,7 +155,7 @@ export const useStore = create<AppState>((set, get) => ({
       set({ servers, serversLoading: false });
     } catch (error) {
       set({
-        serversError: error instanceof Error ? error.message : String(error),
+        serversError: errorMessage(error),
         serversLoading: false,
       });
     }
@@ -119,6 +180,21 @@ export const useStore = create<AppState>((set, get) => ({
     set({ servers: get().servers.filter((s) => s.id !== id) });
   },
 
+  getEffectiveServer: async (id: string) => {
+    return await invoke<McpServer>("get_effective_server", { id });
+  },
+
+  secureServerEnv: async (serverId: string, names: string[]) => {
+    const updated = await invoke<McpServer>("secure_server_env", {
+      serverId,
+      names,
+    });
+    set({
+      servers: get().servers.map((s) => (s.id === updated.id ? updated : s)),
+    });
+    return updated;
+  },
+
   // Instance actions
   loadInstances: async () => {
     set({ instancesLoading: true, instancesError: null });
@@ -127,7 +203,7 @@ export const useStore = create<AppState>((set, get) => ({
       set({ instances, instancesLoading: false });
     } catch (error) {
       set({
-        instancesError: error instanceof Error ? error.message : String(error),
+        instancesError: errorMessage(error),
         instancesLoading: false,
       });
     }
@@ -176,21 +252,161 @@ export const useStore = create<AppState>((set, get) => ({
     });
   },
 
-  syncInstance: async (instanceId: string) => {
+  setToolFilter: async (
+    instanceId: string,
+    serverId: string,
+    filter: ToolFilter
+  ) => {
+    await invoke("set_tool_filter", { instanceId, serverId, filter });
+    set({
+      instances: get().instances.map((instance) => {
+        if (instance.id !== instanceId) return instance;
+        const toolFilters = { ...instance.toolFilters };
+        if (filter.allow.length === 0 && filter.deny.length === 0) {
+          delete toolFilters[serverId];
+        } else {
+          toolFilters[serverId] = filter;
+        }
+        return { ...instance, toolFilters };
+      }),
+    });
+  },
+
+  setServerOverride: async (
+    instanceId: string,
+    serverId: string,
+    serverOverride: ServerOverride
+  ) => {
+    await invoke("set_server_override", {
+      instanceId,
+      serverId,
+      serverOverride,
+    });
+    set({
+      instances: get().instances.map((instance) => {
+        if (instance.id !== instanceId) return instance;
+        const serverOverrides = { ...instance.serverOverrides };
+        if (
+          !serverOverride.args &&
+          Object.keys(serverOverride.env ?? {}).length === 0 &&
+          !serverOverride.alias
+        ) {
+          delete serverOverrides[serverId];
+        } else {
+          serverOverrides[serverId] = serverOverride;
+        }
+        return { ...instance, serverOverrides };
+      }),
+    });
+  },
+
+  previewSync: async (instanceIds?: string[]) => {
+    return await invoke<SyncPlan[]>("preview_sync", {
+      instanceIds: instanceIds ?? null,
+    });
+  },
+
+  syncInstance: async (instanceId: string, fingerprint?: string) => {
     const backupPath = await invoke<string | null>("sync_instance", {
       instanceId,
+      fingerprint: fingerprint ?? null,
     });
     // Reload instances to get updated lastSynced
     await get().loadInstances();
     return backupPath;
   },
 
-  syncAllInstances: async () => {
-    const synced = await invoke<string[]>("sync_all_instances");
+  syncAllInstances: async (fingerprints?: Record<string, string>) => {
+    const synced = await invoke<string[]>("sync_all_instances", {
+      fingerprints: fingerprints ?? null,
+    });
     await get().loadInstances();
     return synced;
   },
 
+  applyConfigFileEvent: (event: ConfigFileEvent) => {
+    set({
+      instances: get().instances.map((instance) =>
+        instance.id === event.instanceId
+          ? { ...instance, status: event.status, statusMessage: event.message }
+          : instance
+      ),
+    });
+  },
+
+  checkDrift: async (instanceIds?: string[]) => {
+    return await invoke<DriftReport[]>("check_drift", {
+      instanceIds: instanceIds ?? null,
+    });
+  },
+
+  reconcileInstance: async (instanceId: string, action: ReconcileAction) => {
+    const result = await invoke<ReconcileResult>("reconcile_instance", {
+      instanceId,
+      action,
+    });
+    await Promise.all([get().loadServers(), get().loadInstances()]);
+    return result;
+  },
+
+  // Backup actions
+  getBackups: async (instanceId: string) => {
+    return await invoke<ConfigBackup[]>("get_backups", { instanceId });
+  },
+
+  previewBackup: async (backupId: string) => {
+    return await invoke<BackupPreview>("preview_backup", { backupId });
+  },
+
+  restoreBackup: async (backupId: string) => {
+    return await invoke<ConfigBackup | null>("restore_backup", { backupId });
+  },
+
+  pruneBackups: async () => {
+    return await invoke<number>("prune_backups");
+  },
+
+  // Health actions
+  loadHealthStats: async () => {
+    try {
+      const stats = await invoke<HealthStats[]>("get_health_stats");
+      set({
+        healthStats: Object.fromEntries(stats.map((s) => [s.serverId, s])),
+      });
+    } catch (error) {
+      console.error("Failed to load health stats:", error);
+    }
+  },
+
+  getHealthHistory: async (serverId: string, limit?: number) => {
+    return await invoke<HealthCheck[]>("get_health_history", {
+      serverId,
+      limit: limit ?? null,
+    });
+  },
+
+  // Capability actions
+  probeServerCapabilities: async (serverId: string) => {
+    return await invoke<CapabilityInventory>("probe_server_capabilities", { serverId });
+  },
+
+  probeAllCapabilities: async () => {
+    return await invoke<CapabilityInventory[]>("probe_all_capabilities");
+  },
+
+  getServerCapabilities: async (serverId: string) => {
+    return await invoke<CapabilityInventory | null>("get_server_capabilities", { serverId });
+  },
+
+  searchCapabilities: async (query: string) => {
+    return await invoke<CapabilityMatch[]>("search_capabilities", { query });
+  },
+
+  // Doctor actions
+  runDoctor: async () => {
+    return await invoke<DoctorReport>("run_doctor");
+  },
+
   // Settings actions
   loadSettings: async () => {
     set({ settingsLoading: true });
@@ -215,9 +431,23 @@ export const useStore = create<AppState>((set, get) => ({
   },
 
   importFromFile: async (path: string) => {
-    const servers = await invoke<McpServer[]>("import_from_file", { path });
-    set({ servers: [...get().servers, ...servers] });
-    return servers;
+    const result = await invoke<ImportResult>("import_from_file", { path });
+    set({ servers: [...get().servers, ...result.servers] });
+    return result;
+  },
+
+  getUnmanagedServers: async (instanceId: string) => {
+    return await invoke<Record<string, McpServerEntry>>("get_unmanaged_servers", { instanceId });
+  },
+
+  adoptUnmanagedServers: async (instanceId: string, keys?: string[]) => {
+    const result = await invoke<ImportResult>("adopt_unmanaged_servers", {
+      instanceId,
+      keys: keys ?? null,
+    });
+    set({ servers: [...get().servers, ...result.servers] });
+    await get().loadInstances();
+    return result;
   },
 
   // Registry actions
@@ -230,9 +460,9 @@ export const useStore = create<AppState>((set, get) => ({
   },
 
   importFromRegistry: async (registryId: string, servers: RegistryServer[]) => {
-    const imported = await invoke<McpServer[]>("import_from_registry", { registryId, servers });
-    set({ servers: [...get().servers, ...imported] });
-    return imported;
+    const result = await invoke<ImportResult>("import_from_registry", { registryId, servers });
+    set({ servers: [...get().servers, ...result.servers] });
+    return result;
   },
 
   // Config reading
@@ -4,15 +4,28 @@ export interface McpServer {
   description?: string;
   command: string;
   args: string[];
+  /** Whether the args replace or follow the parent's */
+  argsMode?: ArgsMode;
   env: Record<string, string>;
+  transport: TransportType;
+  /** Endpoint URL for remote (SSE / streamable HTTP) servers */
+  url?: string;
+  headers?: Record<string, string>;
   tags: string[];
   source?: ServerSource;
-  /** If set, this server is an instance of another server (the parent) */
+  /** If set, this server is an instance of another server (the parent) and
+   * inherits its command, args, env and tags */
   parentId?: string;
+  /** IDs of the servers an effective server inherits from, nearest first */
+  inheritsFrom?: string[];
   createdAt: string;
   updatedAt: string;
 }
 
+export type ArgsMode = "replace" | "append";
+
+export type TransportType = "stdio" | "sse" | "streamable-http";
+
 export interface ServerSource {
   sourceType: "manual" | "imported" | "registry";
   url?: string;
@@ -64,12 +77,56 @@ export interface ClientInstance {
   clientType: ClientType;
   configPath: string;
   enabledServers: string[];
+  /** Tool filters by server ID, for servers that have one */
+  toolFilters?: Record<string, ToolFilter>;
+  /** Overrides by server ID, for servers that have one */
+  serverOverrides?: Record<string, ServerOverride>;
+  /** Write server commands as absolute paths at sync */
+  resolveCommands: boolean;
+  /** Pass the login shell's PATH to servers at sync */
+  injectPath: boolean;
   isDefault: boolean;
   lastSynced?: string;
   lastModified?: string;
+  /** State of the config file as last seen by the watcher */
+  status: InstanceStatus;
+  statusMessage?: string;
   createdAt: string;
 }
 
+/** How an instance runs a server differently from its other instances */
+export interface ServerOverride {
+  /** Args used instead of the server's */
+  args?: string[];
+  /** Env vars set on top of the server's */
+  env?: Record<string, string>;
+  /** Config entry key used instead of the server's name */
+  alias?: string;
+}
+
+/** Glob patterns (`*`, `?`) limiting which of a server's tools an instance may use */
+export interface ToolFilter {
+  allow: string[];
+  deny: string[];
+}
+
+/** A tool filter as the per-tool fields of clients like Cline and Roo Code */
+export interface ToolAccess {
+  allowed?: string[];
+  denied?: string[];
+  disabled: boolean;
+}
+
+export type InstanceStatus = "unknown" | "ok" | "drifted" | "missing" | "invalid";
+
+/** Payload of the "config-file-changed" event */
+export interface ConfigFileEvent {
+  instanceId: string;
+  configPath: string;
+  status: InstanceStatus;
+  message?: string;
+}
+
 export interface ConfigBackup {
   id: string;
   instanceId: string;
@@ -77,6 +134,15 @@ export interface ConfigBackup {
   createdAt: string;
 }
 
+/** A backup's content compared with the current config file */
+export interface BackupPreview {
+  backup: ConfigBackup;
+  content: string;
+  currentContent: string;
+  /** Unified diff from the current file to the backup */
+  diff: string;
+}
+
 export interface DiscoverySettings {
   mcpDirectoryEnabled: boolean;
   httpServerEnabled: boolean;
@@ -88,7 +154,26 @@ export interface AppSettings {
   autoStart: boolean;
   createBackups: boolean;
   backupRetentionDays: number;
+  backupRetentionCount: number;
+  healthCheckTimeoutSecs: number;
+  /** Check servers used by an instance in the background */
+  healthMonitorEnabled: boolean;
+  healthCheckIntervalMins: number;
+  /** Health checks older than this are pruned (0 keeps them regardless of age) */
+  healthHistoryDays: number;
   discovery: DiscoverySettings;
+  /** Where secrets referred to from server env are kept */
+  credentialBackend: CredentialBackend;
+}
+
+export type CredentialBackend = "keyring" | "vault";
+
+export interface CredentialStatus {
+  backend: CredentialBackend;
+  available: boolean;
+  error?: string;
+  vaultExists: boolean;
+  vaultUnlocked: boolean;
 }
 
 export interface DiscoveryStatus {
@@ -103,11 +188,54 @@ export interface DiscoveryStatus {
 
 export type HealthStatus = "healthy" | "error" | "unknown";
 
+export interface McpImplementation {
+  name: string;
+  version: string;
+}
+
 export interface ServerHealth {
   serverId: string;
   status: HealthStatus;
   errorMessage?: string;
   lastChecked: string;
+  latencyMs?: number;
+  protocolVersion?: string;
+  serverInfo?: McpImplementation;
+  capabilities?: Record<string, unknown>;
+  toolCount?: number;
+  stderr?: string;
+}
+
+export interface HealthCheck {
+  serverId: string;
+  status: HealthStatus;
+  errorMessage?: string;
+  latencyMs?: number;
+  checkedAt: string;
+}
+
+export interface HealthStats {
+  serverId: string;
+  /** Checks that reached a verdict (healthy or error) */
+  checks: number;
+  /** Share of those checks that were healthy, from 0 to 1 */
+  uptime?: number;
+  /** Share of consecutive checks whose status differed, from 0 to 1 */
+  flakiness: number;
+  averageLatencyMs?: number;
+  lastStatus?: HealthStatus;
+  lastChecked?: string;
+  lastError?: string;
+}
+
+/** Payload of the `server-health-changed` event */
+export interface HealthChangeEvent {
+  serverId: string;
+  serverName: string;
+  previous: HealthStatus;
+  current: HealthStatus;
+  errorMessage?: string;
+  checkedAt: string;
 }
 
 export interface DetectedClient {
@@ -121,9 +249,74 @@ export interface McpConfigFile {
 }
 
 export interface McpServerEntry {
+  transport?: TransportType;
   command: string;
   args: string[];
   env?: Record<string, string>;
+  url?: string;
+  headers?: Record<string, string>;
+  tools?: ToolAccess;
+}
+
+export interface FieldChange {
+  /** Field name; env variables and headers are reported as `env.NAME` / `headers.NAME` */
+  field: string;
+  before?: unknown;
+  after?: unknown;
+}
+
+export interface EntryChange {
+  key: string;
+  serverId?: string;
+  changes: FieldChange[];
+}
+
+/** What syncing an instance would do to its config file */
+export interface SyncPlan {
+  instanceId: string;
+  instanceName: string;
+  configPath: string;
+  added: EntryChange[];
+  changed: EntryChange[];
+  removed: EntryChange[];
+  unchanged: string[];
+  createsFile: boolean;
+  touchesOtherSettings: boolean;
+  /** Pass back to syncInstance / syncAllInstances to apply exactly this plan */
+  fingerprint: string;
+}
+
+export type DriftStatus = "in-sync" | "modified" | "missing" | "never-synced";
+
+/** Out-of-band changes to an instance's config file since the last sync */
+export interface DriftReport {
+  instanceId: string;
+  status: DriftStatus;
+  syncedAt?: string;
+  fileModifiedAt?: string;
+  added: EntryChange[];
+  changed: EntryChange[];
+  removed: EntryChange[];
+  otherSettingsChanged: boolean;
+}
+
+export type ReconcileAction = "import" | "overwrite" | "merge";
+
+export interface MergeConflict {
+  key: string;
+  field: string;
+  file?: unknown;
+  registry?: unknown;
+}
+
+export interface ReconcileResult {
+  instanceId: string;
+  action: ReconcileAction;
+  updatedServers: string[];
+  disabledServers: string[];
+  /** Conflicting edits; the registry's value was kept */
+  conflicts: MergeConflict[];
+  backupPath?: string;
 }
 
 export const CLIENT_TYPE_LABELS: Record<ClientType, string> = {
@@ -228,3 +421,137 @@ export interface RegistryServer {
   repository?: string;
   homepage?: string;
 }
+
+export interface ServerTool {
+  name: string;
+  description?: string;
+  inputSchema: Record<string, unknown>;
+}
+
+export interface ServerResource {
+  uri: string;
+  name: string;
+  description?: string;
+  mimeType?: string;
+}
+
+export interface ServerPrompt {
+  name: string;
+  description?: string;
+  arguments: unknown[];
+}
+
+export type CapabilityKind = "tool" | "resource" | "prompt";
+
+export interface CapabilityChange {
+  kind: CapabilityKind;
+  name: string;
+  change: "added" | "removed" | "changed";
+}
+
+export interface CapabilityInventory {
+  serverId: string;
+  probedAt: string;
+  tools: ServerTool[];
+  resources: ServerResource[];
+  prompts: ServerPrompt[];
+  changes: CapabilityChange[];
+}
+
+export interface CapabilityMatch {
+  serverId: string;
+  serverName: string;
+  kind: CapabilityKind;
+  name: string;
+  description?: string;
+}
+
+export interface DoctorIssue {
+  serverId: string;
+  severity: "warning" | "error";
+  message: string;
+  fix: string;
+}
+
+export type VersionManager = "nvm" | "fnm" | "volta" | "asdf" | "mise" | "pyenv";
+
+export interface RuntimeCheck {
+  name: string;
+  path?: string;
+  version?: string;
+  minimumVersion?: string;
+  ok: boolean;
+}
+
+export interface ServerDiagnosis {
+  serverId: string;
+  serverName: string;
+  command: string;
+  resolvedPath?: string;
+  guiPath?: string;
+  foundElsewhere?: string;
+  versionManager?: VersionManager;
+  runtimes: RuntimeCheck[];
+  issues: DoctorIssue[];
+}
+
+export interface InstanceDiagnosis {
+  instanceId: string;
+  instanceName: string;
+  clientType: ClientType;
+  issues: DoctorIssue[];
+}
+
+export interface DoctorReport {
+  checkedAt: string;
+  servers: ServerDiagnosis[];
+  instances: InstanceDiagnosis[];
+}
+
+export type SecretReason = "name" | "prefix" | "entropy";
+
+export interface ExtractedSecret {
+  serverId: string;
+  serverName: string;
+  envVar: string;
+  reason: SecretReason;
+  error?: string;
+}
+
+export interface ImportResult {
+  servers: McpServer[];
+  secrets: ExtractedSecret[];
+}
+
+export type OrphanRepair = "delete" | "clear-reference";
+
+export interface OrphanedRows {
+  table: string;
+  column: string;
+  parentTable: string;
+  count: number;
+  repair: OrphanRepair;
+}
+
+export interface IntegrityReport {
+  checkedAt: string;
+  corruption: string[];
+  orphans: OrphanedRows[];
+  repaired: boolean;
+}
+
+export type HubErrorKind =
+  | "not-found"
+  | "parse"
+  | "io"
+  | "keyring"
+  | "conflict"
+  | "validation"
+  | "database"
+  | "other";
+
+/** How backend commands reject */
+export interface HubError {
+  kind: HubErrorKind;
+  message: string;
+}